@@ -0,0 +1,98 @@
+//! Runtime opt-out for Aptabase analytics.
+//!
+//! Whether the Aptabase plugin is compiled in depends on `APTABASE_KEY` at
+//! build time; this flag lets users switch telemetry off without a rebuild.
+//! Rust-side events (`app_started`, `app_exited`) check it before tracking,
+//! and the frontend reads it via `get_analytics_enabled` before sending its
+//! own events.
+//!
+//! The flag lives in `<appConfigDir>/analytics.json` rather than the
+//! frontend's settings store because `app_started` fires before any webview
+//! has loaded. A missing or unreadable file means enabled, which preserves
+//! the behavior from before the opt-out existed.
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager, State};
+
+const SETTINGS_FILE: &str = "analytics.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AnalyticsSettings {
+    enabled: bool,
+}
+
+/// Managed app state holding the live opt-out flag.
+pub struct AnalyticsState {
+    enabled: AtomicBool,
+}
+
+impl AnalyticsState {
+    /// Read the persisted flag, defaulting to enabled.
+    pub fn load(app: &AppHandle) -> Self {
+        let enabled = settings_path(app)
+            .and_then(|path| std::fs::read_to_string(path).map_err(|e| e.to_string()))
+            .and_then(|json| {
+                serde_json::from_str::<AnalyticsSettings>(&json).map_err(|e| e.to_string())
+            })
+            .map(|settings| settings.enabled)
+            .unwrap_or(true);
+        if !enabled {
+            info!("Analytics disabled by user setting");
+        }
+        Self {
+            enabled: AtomicBool::new(enabled),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+}
+
+/// True when analytics may be sent: the opt-out flag is on, or the state was
+/// never registered (keeps the pre-opt-out default).
+pub fn analytics_enabled(app: &AppHandle) -> bool {
+    app.try_state::<AnalyticsState>()
+        .is_none_or(|state| state.is_enabled())
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .map_err(|e| format!("resolve app config directory: {e}"))
+}
+
+/// Turn analytics on or off. Takes effect immediately and is persisted, so
+/// a disabled app sends nothing on the next launch either (including
+/// `app_started`).
+#[tauri::command]
+#[specta::specta]
+pub fn set_analytics_enabled(
+    enabled: bool,
+    app: AppHandle,
+    state: State<'_, AnalyticsState>,
+) -> Result<(), String> {
+    let path = settings_path(&app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+    }
+    let json = serde_json::to_string(&AnalyticsSettings { enabled })
+        .map_err(|e| format!("serialize analytics settings: {e}"))?;
+    std::fs::write(&path, json).map_err(|e| format!("write {}: {e}", path.display()))?;
+
+    state.enabled.store(enabled, Ordering::Relaxed);
+    info!("Analytics {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Current opt-out flag. Independent of whether the build has an Aptabase
+/// key; with no key nothing is sent regardless.
+#[tauri::command]
+#[specta::specta]
+pub fn get_analytics_enabled(state: State<'_, AnalyticsState>) -> bool {
+    state.is_enabled()
+}
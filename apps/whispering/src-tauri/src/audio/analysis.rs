@@ -0,0 +1,295 @@
+//! Energy analysis over decoded PCM: short-window RMS levels in dBFS, the
+//! spans that stay below a threshold, whether any speech is present, and how
+//! much of a recording clipped.
+//!
+//! Pure functions over `&[f32]` so they are unit-testable without an
+//! `AppHandle` or an artifact on disk; the command layer decodes by id and
+//! hands the samples in.
+
+use serde::Serialize;
+
+/// RMS window length. 10 ms is short enough to place boundaries precisely
+/// and long enough that a single zero-crossing does not read as silence.
+const WINDOW_MS: u32 = 10;
+
+/// Magnitude from which a sample counts as clipped (about -0.09 dBFS).
+/// Slightly below 1.0 because converters and limiters rarely sit exactly at
+/// full scale when overdriven, and an int-to-float conversion tops out one
+/// step short of it on the positive side.
+const CLIP_THRESHOLD: f32 = 0.99;
+
+/// Window for `detect_speech`: long enough to hold a few pitch periods of a
+/// low voice, so its zero-crossing rate means something.
+const SPEECH_WINDOW_MS: u32 = 30;
+
+/// Zero crossings per second of a window that can be speech. Fewer is hum
+/// or drift (mains hum crosses 100-120 times a second); more is broadband
+/// hiss, which crosses about half the sample rate times a second.
+const SPEECH_CROSSINGS_PER_SECOND: std::ops::RangeInclusive<u32> = 150..=5_000;
+
+/// Least speech `detect_speech` needs for `has_speech`, so a cough or a
+/// click does not count.
+const MIN_SPEECH_MS: u32 = 200;
+
+/// A span of audio that stayed below the silence threshold, in milliseconds
+/// from the start of the recording. `end_ms` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SilenceRegion {
+    pub start_ms: u32,
+    pub end_ms: u32,
+}
+
+/// RMS level of `samples` in dBFS (0 dBFS = a full-scale square wave; a
+/// full-scale sine reads about -3 dBFS).
+/// Digital silence is `f32::NEG_INFINITY`.
+pub fn rms_dbfs(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return f32::NEG_INFINITY;
+    }
+    let sum_sq: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
+    let rms = (sum_sq / samples.len() as f64).sqrt();
+    (20.0 * rms.log10()) as f32
+}
+
+/// Spans where every 10 ms window's RMS is below `threshold_dbfs` for at
+/// least `min_duration_ms`.
+///
+/// This is an energy gate, not a voice detector: speech quieter than the
+/// threshold (a soft-spoken trailing word, a distant speaker) is reported
+/// as silence, and steady background noise above it is not. Pick the
+/// threshold relative to the recording's noise floor; -40 to -50 dBFS suits
+/// a close microphone in a quiet room.
+pub fn detect_silence_regions(
+    samples: &[f32],
+    sample_rate: u32,
+    threshold_dbfs: f32,
+    min_duration_ms: u32,
+) -> Vec<SilenceRegion> {
+    let window = (sample_rate * WINDOW_MS / 1000).max(1) as usize;
+    let to_ms = |index: usize| (index as u64 * 1000 / sample_rate as u64) as u32;
+
+    let mut regions = Vec::new();
+    let mut silent_since: Option<usize> = None;
+    let close = |start: usize, end: usize, regions: &mut Vec<SilenceRegion>| {
+        let (start_ms, end_ms) = (to_ms(start), to_ms(end));
+        if end_ms - start_ms >= min_duration_ms {
+            regions.push(SilenceRegion { start_ms, end_ms });
+        }
+    };
+
+    for (i, chunk) in samples.chunks(window).enumerate() {
+        let start = i * window;
+        let silent = rms_dbfs(chunk) < threshold_dbfs;
+        match (silent, silent_since) {
+            (true, None) => silent_since = Some(start),
+            (false, Some(since)) => {
+                close(since, start, &mut regions);
+                silent_since = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(since) = silent_since {
+        close(since, samples.len(), &mut regions);
+    }
+    regions
+}
+
+/// Whether a recording holds speech, from `detect_speech`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechPresence {
+    pub has_speech: bool,
+    /// Share of the audio (0.0-1.0) in windows that look like speech.
+    pub speech_fraction: f32,
+}
+
+/// Cheap voice activity check: a 30 ms window counts as speech when its RMS
+/// reaches `threshold_dbfs` and its zero-crossing rate is in the range of a
+/// voice rather than hum or hiss. `has_speech` needs at least
+/// `MIN_SPEECH_MS` of such windows.
+///
+/// Like `detect_silence_regions` this judges level and rough pitch, not
+/// words: loud music or a TV passes, and speech quieter than the threshold
+/// does not. It exists to skip loading a model for recordings that are
+/// plainly empty, not to judge what was said.
+pub fn detect_speech(samples: &[f32], sample_rate: u32, threshold_dbfs: f32) -> SpeechPresence {
+    let window = (sample_rate * SPEECH_WINDOW_MS / 1000).max(2) as usize;
+    let mut speech_samples = 0usize;
+    for chunk in samples.chunks(window) {
+        if rms_dbfs(chunk) < threshold_dbfs {
+            continue;
+        }
+        let crossings = chunk
+            .windows(2)
+            .filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0))
+            .count();
+        let per_second = (crossings as u64 * sample_rate as u64 / chunk.len() as u64) as u32;
+        if SPEECH_CROSSINGS_PER_SECOND.contains(&per_second) {
+            speech_samples += chunk.len();
+        }
+    }
+
+    let speech_ms = (speech_samples as u64 * 1000 / sample_rate.max(1) as u64) as u32;
+    SpeechPresence {
+        has_speech: speech_ms >= MIN_SPEECH_MS,
+        speech_fraction: if samples.is_empty() {
+            0.0
+        } else {
+            speech_samples as f32 / samples.len() as f32
+        },
+    }
+}
+
+/// How hot a recording ran. Computed on the source samples, before any
+/// resampling, which would smooth flat-topped peaks away.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ClippingReport {
+    /// Share of samples (across all channels, 0.0-1.0) at or near full scale.
+    pub clipped_sample_fraction: f32,
+    /// Longest run of consecutive clipped samples on any one channel.
+    pub longest_clip_run_ms: u32,
+    /// Highest sample magnitude in dBFS. `None` for digital silence.
+    pub peak_dbfs: Option<f32>,
+}
+
+/// Clipping statistics for interleaved `samples` with `channels` per frame.
+/// Runs are tracked per channel, so one clipped channel next to a clean one
+/// still reports its full run length.
+pub fn analyze_clipping(samples: &[f32], sample_rate: u32, channels: u16) -> ClippingReport {
+    let channels = channels.max(1) as usize;
+    let mut clipped = 0usize;
+    let mut peak = 0.0f32;
+    let mut runs = vec![0usize; channels];
+    let mut longest_run = 0usize;
+
+    for (i, &sample) in samples.iter().enumerate() {
+        let magnitude = sample.abs();
+        peak = peak.max(magnitude);
+        let run = &mut runs[i % channels];
+        if magnitude >= CLIP_THRESHOLD {
+            clipped += 1;
+            *run += 1;
+            longest_run = longest_run.max(*run);
+        } else {
+            *run = 0;
+        }
+    }
+
+    ClippingReport {
+        clipped_sample_fraction: if samples.is_empty() {
+            0.0
+        } else {
+            clipped as f32 / samples.len() as f32
+        },
+        longest_clip_run_ms: (longest_run as u64 * 1000 / sample_rate.max(1) as u64) as u32,
+        peak_dbfs: amplitude_to_dbfs(peak),
+    }
+}
+
+/// `amplitude` in dBFS, or `None` for zero (digital silence has no level).
+pub fn amplitude_to_dbfs(amplitude: f32) -> Option<f32> {
+    (amplitude > 0.0).then(|| 20.0 * amplitude.log10())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    fn tone(ms: u32) -> Vec<f32> {
+        (0..(RATE * ms / 1000))
+            .map(|i| (i as f32 * 0.05).sin() * 0.5)
+            .collect()
+    }
+
+    fn silence(ms: u32) -> Vec<f32> {
+        vec![0.0; (RATE * ms / 1000) as usize]
+    }
+
+    #[test]
+    fn rms_of_full_scale_square_is_zero_dbfs() {
+        assert!(rms_dbfs(&[1.0, -1.0, 1.0, -1.0]).abs() < 1e-6);
+        assert_eq!(rms_dbfs(&[0.0; 4]), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn finds_gaps_longer_than_the_minimum() {
+        let samples = [
+            silence(300),
+            tone(500),
+            silence(100),
+            tone(200),
+            silence(400),
+        ]
+        .concat();
+
+        let regions = detect_silence_regions(&samples, RATE, -40.0, 250);
+
+        assert_eq!(
+            regions,
+            vec![
+                SilenceRegion {
+                    start_ms: 0,
+                    end_ms: 300
+                },
+                SilenceRegion {
+                    start_ms: 1100,
+                    end_ms: 1500
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn speech_needs_level_and_a_voice_like_crossing_rate() {
+        let speech = detect_speech(&[silence(1000), tone(500)].concat(), RATE, -40.0);
+        assert!(speech.has_speech);
+        assert!(
+            (speech.speech_fraction - 1.0 / 3.0).abs() < 0.02,
+            "{speech:?}"
+        );
+
+        assert!(!detect_speech(&silence(2000), RATE, -40.0).has_speech);
+        // Loud, but a 50 Hz hum crosses too rarely and hiss too often.
+        let hum: Vec<f32> = (0..RATE * 2)
+            .map(|i| (i as f32 * std::f32::consts::TAU * 50.0 / RATE as f32).sin() * 0.5)
+            .collect();
+        assert!(!detect_speech(&hum, RATE, -40.0).has_speech);
+        let mut state = 1u32;
+        let hiss: Vec<f32> = (0..RATE * 2)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+            })
+            .collect();
+        assert!(!detect_speech(&hiss, RATE, -40.0).has_speech);
+        // Too short to be more than a click.
+        assert!(!detect_speech(&tone(100), RATE, -40.0).has_speech);
+    }
+
+    #[test]
+    fn clipping_runs_are_counted_per_channel() {
+        // Stereo: the left channel pins at full scale for 160 frames (10 ms),
+        // the right stays quiet throughout.
+        let mut samples = Vec::new();
+        for frame in 0..1600 {
+            let left = if (100..260).contains(&frame) {
+                1.0
+            } else {
+                0.1
+            };
+            samples.extend([left, -0.25]);
+        }
+
+        let report = analyze_clipping(&samples, RATE, 2);
+
+        assert_eq!(report.longest_clip_run_ms, 10);
+        assert!((report.clipped_sample_fraction - 160.0 / 3200.0).abs() < 1e-6);
+        assert_eq!(report.peak_dbfs, Some(0.0));
+        assert_eq!(analyze_clipping(&silence(10), RATE, 1).peak_dbfs, None);
+    }
+}
@@ -1,15 +1,30 @@
-//! Tauri command surface for the audio module. One endpoint:
+//! Tauri command surface for the audio module.
 //! `encode_recording_for_upload(recording_id)` resolves the durable audio
 //! artifact by id, decodes it to mono 16 kHz PCM (same path the local
 //! transcription engines use via `read_artifact_samples`), and re-encodes
-//! to OGG/Opus for cloud upload.
+//! to OGG/Opus for cloud upload. The analysis commands decode the same way
+//! and return small summaries instead of audio. `split_stereo_to_mono` and
+//! `analyze_clipping` work on WAV paths directly, since they need the
+//! source format and samples untouched. `reprocess_recording` decodes any
+//! supported file by path and writes a cleaned 16 kHz mono WAV.
+//! `audio_duration` reads only headers and metadata, never decoding.
+//! `repair_wav_header` fixes the sizes in a WAV header in place.
 
 use log::warn;
+use std::path::Path;
 use tauri::ipc::Response;
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager};
 
+use super::analysis::{self, ClippingReport, SilenceRegion, SpeechPresence};
+use super::decode::decode_to_pcm16k_mono;
 use super::encode::encode_pcm_to_opus_ogg;
+use super::probe::{probe_duration, AudioDuration};
+use super::process::{self, ProcessingOptions, ReprocessReport};
+use super::repair::repair_wav_file;
+use super::split::split_stereo_wav;
+use super::wav::{read_wav_samples, samples_to_wav, samples_to_wav_dithered};
 use crate::recorder::read_artifact_samples;
+use crate::temp_files::StagingDirs;
 
 /// Compress a saved recording artifact into OGG/Opus for cloud upload.
 ///
@@ -46,3 +61,175 @@ pub async fn encode_recording_for_upload(
         e
     })
 }
+
+/// Silent spans of a saved recording, for skip markers in a review UI.
+///
+/// A 10 ms window counts as silent when its RMS is below `threshold_dbfs`
+/// (e.g. `-45.0`); only runs of at least `min_duration_ms` are returned.
+/// This is an energy gate, so speech quieter than the threshold can be
+/// reported as silence; lower the threshold if soft words get marked.
+#[tauri::command]
+#[specta::specta]
+pub async fn detect_silence_regions(
+    recording_id: String,
+    threshold_dbfs: f32,
+    min_duration_ms: u32,
+    app_handle: AppHandle,
+) -> Result<Vec<SilenceRegion>, String> {
+    check_threshold("Silence", threshold_dbfs)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let samples = read_artifact_samples(&app_handle, &recording_id)?;
+        // `read_artifact_samples` always yields 16 kHz (see above).
+        Ok(analysis::detect_silence_regions(
+            &samples,
+            16_000,
+            threshold_dbfs,
+            min_duration_ms,
+        ))
+    })
+    .await
+    .map_err(|e| format!("background analysis task failed: {e}"))?
+}
+
+/// Whether a saved recording holds any speech, checked without loading a
+/// model, so the UI can say "no speech detected" at once instead of after a
+/// transcription that comes back empty.
+///
+/// Audio counts as speech where it is louder than `threshold_dbfs` (e.g.
+/// `-45.0`) and crosses zero at a voice-like rate; see
+/// `analysis::detect_speech` for what that does and does not catch.
+#[tauri::command]
+#[specta::specta]
+pub async fn contains_speech(
+    recording_id: String,
+    threshold_dbfs: f32,
+    app_handle: AppHandle,
+) -> Result<SpeechPresence, String> {
+    check_threshold("Speech", threshold_dbfs)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let samples = read_artifact_samples(&app_handle, &recording_id)?;
+        Ok(analysis::detect_speech(&samples, 16_000, threshold_dbfs))
+    })
+    .await
+    .map_err(|e| format!("background analysis task failed: {e}"))?
+}
+
+fn check_threshold(kind: &str, threshold_dbfs: f32) -> Result<(), String> {
+    if !threshold_dbfs.is_finite() || threshold_dbfs > 0.0 {
+        return Err(format!(
+            "{kind} threshold must be a finite dBFS value at or below 0, got {threshold_dbfs}"
+        ));
+    }
+    Ok(())
+}
+
+/// Write each channel of the stereo WAV at `source_path` to its own mono
+/// WAV, keeping sample rate and bit depth. For dual-mic recordings with one
+/// speaker per side. Errors if the source is not exactly 2 channels, or if
+/// any two of the paths are the same file. Existing outputs are replaced
+/// only once both channels are written.
+#[tauri::command]
+#[specta::specta]
+pub async fn split_stereo_to_mono(
+    app: AppHandle,
+    source_path: String,
+    left_output: String,
+    right_output: String,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let staging = app.state::<StagingDirs>();
+        staging.remember_parent(Path::new(&left_output));
+        staging.remember_parent(Path::new(&right_output));
+        split_stereo_wav(
+            Path::new(&source_path),
+            Path::new(&left_output),
+            Path::new(&right_output),
+        )
+        .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("background split task failed: {e}"))?
+}
+
+/// How much of the WAV at `file_path` clipped, for spotting recordings made
+/// with the input gain too high. Reads the file at its native rate and
+/// channel count; see `ClippingReport` for the fields.
+#[tauri::command]
+#[specta::specta]
+pub async fn analyze_clipping(file_path: String) -> Result<ClippingReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let (samples, sample_rate, channels) =
+            read_wav_samples(Path::new(&file_path)).map_err(|e| e.to_string())?;
+        Ok(analysis::analyze_clipping(&samples, sample_rate, channels))
+    })
+    .await
+    .map_err(|e| format!("background analysis task failed: {e}"))?
+}
+
+/// Decode `source_path` to 16 kHz mono, run the clean-up stages `options`
+/// enables (see `process` for their order), and write the result to
+/// `output_path` as a 16-bit WAV ready for transcription. The source is not
+/// modified, so the cleaned and original files can be compared.
+#[tauri::command]
+#[specta::specta]
+pub async fn reprocess_recording(
+    source_path: String,
+    output_path: String,
+    options: ProcessingOptions,
+) -> Result<ReprocessReport, String> {
+    options.validate()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let bytes = std::fs::read(&source_path)
+            .map_err(|e| format!("Failed to read {source_path}: {e}"))?;
+        let mut samples = decode_to_pcm16k_mono(&bytes).map_err(|e| e.to_string())?;
+        let peak_before = process::peak(&samples);
+
+        process::process_samples(&mut samples, &options);
+
+        let write = if options.dither {
+            samples_to_wav_dithered
+        } else {
+            samples_to_wav
+        };
+        let wav = write(&samples, 16_000, 1).map_err(|e| e.to_string())?;
+        std::fs::write(&output_path, wav)
+            .map_err(|e| format!("Failed to write {output_path}: {e}"))?;
+        Ok(ReprocessReport {
+            peak_before_dbfs: analysis::amplitude_to_dbfs(peak_before),
+            peak_after_dbfs: analysis::amplitude_to_dbfs(process::peak(&samples).min(1.0)),
+        })
+    })
+    .await
+    .map_err(|e| format!("background reprocess task failed: {e}"))?
+}
+
+/// Duration of `audio_data` in seconds and its container format, read from
+/// headers and metadata without decoding, so the UI can apply the
+/// max-duration guard and show time estimates before transcribing.
+/// `durationSeconds` is `null` when the container does not record its
+/// length.
+#[tauri::command]
+#[specta::specta]
+pub async fn audio_duration(audio_data: Vec<u8>) -> Result<AudioDuration, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        probe_duration(&audio_data).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("background probe task failed: {e}"))?
+}
+
+/// Fix the RIFF and data chunk sizes of the WAV at `file_path` so it can be
+/// opened, for files from other tools that wrote a placeholder or
+/// overrunning length. The file is rewritten atomically (temp file, then
+/// rename) and only when a size was wrong; returns whether it was.
+#[tauri::command]
+#[specta::specta]
+pub async fn repair_wav_header(app: AppHandle, file_path: String) -> Result<bool, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        app.state::<StagingDirs>()
+            .remember_parent(Path::new(&file_path));
+        repair_wav_file(Path::new(&file_path)).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("background repair task failed: {e}"))?
+}
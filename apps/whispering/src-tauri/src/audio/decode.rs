@@ -12,6 +12,7 @@ use audiopus::{
     SampleRate as OpusSampleRate,
 };
 use log::{debug, warn};
+use serde::{Deserialize, Serialize};
 use symphonia::core::{
     audio::SampleBuffer,
     codecs::{DecoderOptions, CODEC_TYPE_NULL, CODEC_TYPE_OPUS},
@@ -33,16 +34,130 @@ const TARGET_RATE: u32 = 16_000;
 /// produces output at this rate.
 const OPUS_RATE: u32 = 48_000;
 
+/// How multichannel audio is folded to mono. Mono and stereo input is
+/// always averaged; the other modes only change 3+ channel files, where
+/// surround and LFE channels carry rumble and room noise rather than voice.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, specta::Type)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum Downmix {
+    /// Every channel equally.
+    #[default]
+    Average,
+    /// Only the front channels, assuming the standard (WAV/SMPTE) channel
+    /// order: left, right, and center, i.e. all of a 3-channel file and the
+    /// first three of 5.1 or 7.1, or left and right of a quad file.
+    Front,
+    /// A weight per channel, in channel order, as a percentage (0 mutes the
+    /// channel). Applied to files with exactly that many channels; any
+    /// other file is averaged.
+    #[serde(rename_all = "camelCase")]
+    Weighted { weights_percent: Vec<u32> },
+}
+
+impl Downmix {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if let Self::Weighted { weights_percent } = self {
+            if weights_percent.iter().any(|&w| w > 100) {
+                return Err("weightsPercent must each be at most 100".to_string());
+            }
+            if weights_percent.iter().all(|&w| w == 0) {
+                return Err("weightsPercent must give some channel a weight".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Weight of each of `channels` channels; `None` means average.
+    fn weights(&self, channels: usize) -> Option<Vec<f32>> {
+        match self {
+            Self::Average => None,
+            Self::Front => {
+                let front = match channels {
+                    0..=3 => return None,
+                    4 => 2,
+                    _ => 3,
+                };
+                Some((0..channels).map(|c| f32::from(c < front)).collect())
+            }
+            Self::Weighted { weights_percent } if weights_percent.len() == channels => {
+                Some(weights_percent.iter().map(|&w| w as f32).collect())
+            }
+            Self::Weighted { .. } => None,
+        }
+    }
+}
+
 /// Decode arbitrary audio bytes into 16 kHz mono interleaved f32 PCM.
 ///
 /// Returns an empty `Vec` when the input decodes to zero audible samples
 /// (very short clips, all-silence trimmed to nothing); the caller is
 /// expected to short-circuit to an empty transcript in that case.
 pub fn decode_to_pcm16k_mono(bytes: &[u8]) -> Result<Vec<f32>, AudioError> {
+    decode_to_pcm16k_mono_with(bytes, &Downmix::Average)
+}
+
+/// `decode_to_pcm16k_mono`, folding channels to mono per `downmix`.
+pub fn decode_to_pcm16k_mono_with(bytes: &[u8], downmix: &Downmix) -> Result<Vec<f32>, AudioError> {
+    let Some((samples, source_rate, channel_count)) = decode_interleaved(bytes)? else {
+        return Ok(Vec::new());
+    };
+
+    let mono = downmix_to_mono(samples, channel_count as usize, downmix);
+    debug!("[Audio Decode] downmix to mono: {} samples", mono.len());
+
+    resample_to_target(mono, source_rate)
+}
+
+fn downmix_to_mono(samples: Vec<f32>, channels: usize, downmix: &Downmix) -> Vec<f32> {
+    if channels <= 1 {
+        return samples;
+    }
+    let weights = downmix
+        .weights(channels)
+        .unwrap_or_else(|| vec![1.0; channels]);
+    let total: f32 = weights.iter().sum();
+    samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().zip(&weights).map(|(s, w)| s * w).sum::<f32>() / total)
+        .collect()
+}
+
+/// Decode arbitrary audio bytes and keep only channel `channel_index`
+/// (0-based), resampled to 16 kHz. For multi-mic captures where each speaker
+/// has their own channel, this transcribes one side without the crosstalk a
+/// downmix would mix in.
+///
+/// Fails with `UnsupportedFormat` when the file has no such channel.
+pub fn decode_channel_to_pcm16k(bytes: &[u8], channel_index: u16) -> Result<Vec<f32>, AudioError> {
+    let Some((samples, source_rate, channel_count)) = decode_interleaved(bytes)? else {
+        return Ok(Vec::new());
+    };
+    if channel_index >= channel_count {
+        return Err(AudioError::unsupported(format!(
+            "channel {channel_index} requested but the audio has {channel_count} channel(s)"
+        )));
+    }
+
+    let channel: Vec<f32> = samples
+        .chunks_exact(channel_count as usize)
+        .map(|frame| frame[channel_index as usize])
+        .collect();
+    debug!(
+        "[Audio Decode] extracted channel {}: {} samples",
+        channel_index,
+        channel.len()
+    );
+
+    resample_to_target(channel, source_rate)
+}
+
+/// Demux and decode to interleaved f32 at the source rate. `None` for empty
+/// input.
+fn decode_interleaved(bytes: &[u8]) -> Result<Option<(Vec<f32>, u32, u16)>, AudioError> {
     debug!("[Audio Decode] starting decode for {} bytes", bytes.len());
 
     if bytes.is_empty() {
-        return Ok(Vec::new());
+        return Ok(None);
     }
 
     // Symphonia's `MediaSource` trait requires `'static`, so the bytes are
@@ -81,17 +196,10 @@ pub fn decode_to_pcm16k_mono(bytes: &[u8]) -> Result<Vec<f32>, AudioError> {
         channel_count
     );
 
-    let mono = if channel_count <= 1 {
-        samples
-    } else {
-        let n = channel_count as usize;
-        samples
-            .chunks_exact(n)
-            .map(|chunk| chunk.iter().sum::<f32>() / n as f32)
-            .collect()
-    };
-    debug!("[Audio Decode] downmix to mono: {} samples", mono.len());
+    Ok(Some((samples, source_rate, channel_count)))
+}
 
+fn resample_to_target(mono: Vec<f32>, source_rate: u32) -> Result<Vec<f32>, AudioError> {
     let resampled = resample_mono(mono, source_rate, TARGET_RATE)?;
     debug!(
         "[Audio Decode] resampled to {} Hz: {} samples",
@@ -259,36 +367,21 @@ fn decode_via_libopus(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use hound::{SampleFormat, WavSpec, WavWriter};
-    use std::io::Cursor as IoCursor;
+    use crate::audio::wav::samples_to_wav;
 
-    /// Write a `samples_per_channel`-long, `channels`-channel WAV at
-    /// `sample_rate` to memory. Sample values are i16 PCM derived from
-    /// the provided f32 input (clamped + scaled).
+    /// Write a `samples_per_channel`-long, `channels`-channel 16-bit WAV at
+    /// `sample_rate` to memory, taking each sample from `f(frame, channel)`.
     fn make_wav(
         samples_per_channel: usize,
         channels: u16,
         sample_rate: u32,
         f: impl Fn(usize, u16) -> f32,
     ) -> Vec<u8> {
-        let spec = WavSpec {
-            channels,
-            sample_rate,
-            bits_per_sample: 16,
-            sample_format: SampleFormat::Int,
-        };
-        let mut cursor = IoCursor::new(Vec::new());
-        {
-            let mut writer = WavWriter::new(&mut cursor, spec).unwrap();
-            for i in 0..samples_per_channel {
-                for c in 0..channels {
-                    let v = f(i, c).clamp(-1.0, 1.0);
-                    writer.write_sample((v * 32767.0) as i16).unwrap();
-                }
-            }
-            writer.finalize().unwrap();
-        }
-        cursor.into_inner()
+        let samples: Vec<f32> = (0..samples_per_channel)
+            .flat_map(|i| (0..channels).map(move |c| (i, c)))
+            .map(|(i, c)| f(i, c))
+            .collect();
+        samples_to_wav(&samples, sample_rate, channels).unwrap()
     }
 
     fn sine_at(i: usize, freq_hz: f32, sample_rate: u32) -> f32 {
@@ -334,6 +427,90 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decodes_8_and_24_bit_pcm_wav_without_ffmpeg() {
+        // USB interfaces and phones record 24-bit PCM, old tools 8-bit
+        // (stored unsigned, centered at 128). Both must come out scaled like
+        // 16-bit does, at 16 kHz mono.
+        let in_rate = 48_000;
+        for bits in [8u16, 24] {
+            let spec = hound::WavSpec {
+                channels: 2,
+                sample_rate: in_rate,
+                bits_per_sample: bits,
+                sample_format: hound::SampleFormat::Int,
+            };
+            let full_scale = (1i32 << (bits - 1)) as f32;
+            let mut cursor = Cursor::new(Vec::new());
+            let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+            for i in 0..in_rate as usize {
+                let sample = (sine_at(i, 440.0, in_rate) * full_scale).round() as i32;
+                writer.write_sample(sample).unwrap();
+                writer.write_sample(sample).unwrap();
+            }
+            writer.finalize().unwrap();
+
+            let samples = decode_to_pcm16k_mono(&cursor.into_inner())
+                .unwrap_or_else(|e| panic!("{bits}-bit decode: {e}"));
+
+            assert!(
+                samples.len().abs_diff(TARGET_RATE as usize) <= 1,
+                "{bits}-bit: got {} samples",
+                samples.len()
+            );
+            let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+            assert!((peak - 0.5).abs() < 0.02, "{bits}-bit peak {peak}");
+        }
+    }
+
+    #[test]
+    fn extracts_a_single_channel_and_validates_the_index() {
+        let rate = 16_000;
+        let bytes = make_wav(
+            rate as usize,
+            2,
+            rate,
+            |_, c| if c == 0 { 0.0 } else { 0.5 },
+        );
+
+        let right = decode_channel_to_pcm16k(&bytes, 1).expect("decode right");
+        assert_eq!(right.len(), rate as usize);
+        assert!(right.iter().all(|s| (s - 0.5).abs() < 1e-3));
+
+        let left = decode_channel_to_pcm16k(&bytes, 0).expect("decode left");
+        assert!(left.iter().all(|s| s.abs() < 1e-3));
+
+        assert!(matches!(
+            decode_channel_to_pcm16k(&bytes, 2),
+            Err(AudioError::UnsupportedFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn downmix_modes_weight_surround_channels() {
+        // One 5.1 frame: FL, FR, FC, LFE, SL, SR.
+        let frame = vec![0.2, 0.4, 0.6, 1.0, -1.0, -1.0];
+        let mix = |downmix: &Downmix| downmix_to_mono(frame.clone(), 6, downmix)[0];
+
+        assert!((mix(&Downmix::Average) - 0.2 / 6.0).abs() < 1e-6);
+        assert!((mix(&Downmix::Front) - 0.4).abs() < 1e-6);
+        let weighted = Downmix::Weighted {
+            weights_percent: vec![100, 100, 100, 0, 50, 50],
+        };
+        assert!((mix(&weighted) - 0.2 / 4.0).abs() < 1e-6);
+        // Weights for another layout leave the file averaged.
+        let stereo_weights = Downmix::Weighted {
+            weights_percent: vec![100, 0],
+        };
+        assert_eq!(mix(&stereo_weights), mix(&Downmix::Average));
+
+        assert!(Downmix::Weighted {
+            weights_percent: vec![0, 0]
+        }
+        .validate()
+        .is_err());
+    }
+
     #[test]
     fn returns_empty_for_empty_input() {
         let samples = decode_to_pcm16k_mono(&[]).expect("decode");
@@ -208,30 +208,20 @@ fn encode_audio_pages<W: std::io::Write>(
 mod tests {
     use super::*;
     use crate::audio::decode::decode_to_pcm16k_mono;
-    use hound::{SampleFormat as HoundSampleFormat, WavSpec, WavWriter};
+    use crate::audio::wav::samples_to_wav;
 
     /// Synthesize a `secs`-long, single-channel WAV at `sample_rate`,
     /// containing a sine wave at `freq_hz`. Matches the shape of the
     /// fixtures used in decode.rs's tests.
     fn make_sine_wav(secs: f32, sample_rate: u32, freq_hz: f32) -> Vec<u8> {
-        let spec = WavSpec {
-            channels: 1,
-            sample_rate,
-            bits_per_sample: 16,
-            sample_format: HoundSampleFormat::Int,
-        };
         let total = (secs * sample_rate as f32) as usize;
-        let mut cursor = Cursor::new(Vec::new());
-        {
-            let mut writer = WavWriter::new(&mut cursor, spec).unwrap();
-            for i in 0..total {
+        let samples: Vec<f32> = (0..total)
+            .map(|i| {
                 let t = i as f32 / sample_rate as f32;
-                let v = (2.0 * std::f32::consts::PI * freq_hz * t).sin() * 0.5;
-                writer.write_sample((v * 32767.0) as i16).unwrap();
-            }
-            writer.finalize().unwrap();
-        }
-        cursor.into_inner()
+                (2.0 * std::f32::consts::PI * freq_hz * t).sin() * 0.5
+            })
+            .collect();
+        samples_to_wav(&samples, sample_rate, 1).unwrap()
     }
 
     #[test]
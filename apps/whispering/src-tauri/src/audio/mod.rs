@@ -10,15 +10,43 @@
 //! formats. The new pipeline is one path built on Symphonia (demux +
 //! non-Opus decode), libopus via `audiopus` (Opus decode), and rubato
 //! (resample to 16 kHz).
+//!
+//! Everything happens in memory: nothing in decode, resample, or encode
+//! writes intermediate files, so a small or read-only system temp directory
+//! cannot break conversion and there is no temp location to configure.
+//!
+//! `analysis` holds energy measurements (RMS levels, silence spans, speech
+//! presence, clipping) over the decoded samples; `process` is the offline clean-up
+//! chain (high-pass, noise gate, gain or normalize) behind
+//! `reprocess_recording`. `probe` reads durations from headers and
+//! container metadata without decoding. `repair` fixes the chunk sizes of
+//! WAVs whose header disagrees with the file. `split` de-interleaves stereo
+//! WAVs into per-channel mono files. `wav::samples_to_wav` builds an in-memory
+//! WAV from samples, mostly for test fixtures.
 
+mod analysis;
 mod command;
 mod decode;
 mod encode;
 mod error;
+mod probe;
+mod process;
+mod repair;
 mod resample;
+mod split;
+mod wav;
 
-pub use command::encode_recording_for_upload;
-pub use decode::decode_to_pcm16k_mono;
+pub use analysis::{detect_speech, ClippingReport, SilenceRegion, SpeechPresence};
+pub use command::{
+    analyze_clipping, audio_duration, contains_speech, detect_silence_regions,
+    encode_recording_for_upload, repair_wav_header, reprocess_recording, split_stereo_to_mono,
+};
+pub use decode::{
+    decode_channel_to_pcm16k, decode_to_pcm16k_mono, decode_to_pcm16k_mono_with, Downmix,
+};
 pub use encode::encode_pcm_to_opus_ogg;
 pub use error::AudioError;
+pub use probe::AudioDuration;
+pub use process::{ProcessingOptions, ReprocessReport};
 pub use resample::resample_mono;
+pub use wav::{samples_to_wav, samples_to_wav_dithered};
@@ -0,0 +1,134 @@
+//! Duration of an audio blob without decoding it.
+//!
+//! A cheap pre-check for the max-duration guard and time estimates. WAV
+//! duration is header arithmetic (data chunk length over block size and
+//! rate). Other containers go through Symphonia's probe, which reads the
+//! frame count from container metadata (MP3 Xing/Info header, MP4 `mdhd`,
+//! FLAC STREAMINFO, the last OGG granule position). When a container does
+//! not record its length (streamed WebM, a bare MP3 with no Xing header),
+//! the duration is `None` rather than falling back to a full decode, so the
+//! caller decides whether an unknown length is worth a decode.
+
+use std::io::Cursor;
+
+use hound::WavReader;
+use serde::Serialize;
+use symphonia::core::{
+    codecs::CODEC_TYPE_NULL, formats::FormatOptions, io::MediaSourceStream, meta::MetadataOptions,
+    probe::Hint,
+};
+
+use super::error::AudioError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDuration {
+    /// `None` when the container does not record its length.
+    pub duration_seconds: Option<f32>,
+    /// Container sniffed from the leading bytes: `wav`, `mp3`, `mp4`,
+    /// `ogg`, `flac`, `webm`, or `unknown`.
+    pub format: String,
+}
+
+/// Duration of `bytes` from headers and metadata only.
+pub fn probe_duration(bytes: &[u8]) -> Result<AudioDuration, AudioError> {
+    let format = sniff_format(bytes);
+    let duration_seconds = if format == "wav" {
+        Some(wav_duration(bytes)?)
+    } else {
+        metadata_duration(bytes)?
+    };
+    Ok(AudioDuration {
+        duration_seconds,
+        format: format.to_string(),
+    })
+}
+
+fn wav_duration(bytes: &[u8]) -> Result<f32, AudioError> {
+    // `WavReader::new` parses the header and stops at the data chunk.
+    let reader = WavReader::new(Cursor::new(bytes))
+        .map_err(|e| AudioError::decode(format!("wav header: {e}")))?;
+    let rate = reader.spec().sample_rate;
+    if rate == 0 {
+        return Err(AudioError::unsupported("wav header reports 0 Hz"));
+    }
+    Ok(reader.duration() as f32 / rate as f32)
+}
+
+fn metadata_duration(bytes: &[u8]) -> Result<Option<f32>, AudioError> {
+    // See `decode::decode_interleaved` for why the bytes are copied.
+    let mss = MediaSourceStream::new(Box::new(Cursor::new(bytes.to_vec())), Default::default());
+    let probed = symphonia::default::get_probe()
+        .format(
+            &Hint::new(),
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| AudioError::decode(format!("container probe failed: {e}")))?;
+    let track = probed
+        .format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| AudioError::unsupported("no audio track in container".to_string()))?;
+
+    let params = &track.codec_params;
+    let Some(frames) = params.n_frames else {
+        return Ok(None);
+    };
+    // Encoder delay and padding (Opus pre-skip, LAME/iTunes gapless info)
+    // are not audio.
+    let frames = frames
+        .saturating_sub(u64::from(params.delay.unwrap_or(0)))
+        .saturating_sub(u64::from(params.padding.unwrap_or(0)));
+    // Opus tracks carry no sample rate; their time base is the frame rate.
+    let seconds = match (params.sample_rate, params.time_base) {
+        (Some(rate), _) if rate > 0 => frames as f64 / f64::from(rate),
+        (_, Some(time_base)) => {
+            let time = time_base.calc_time(frames);
+            time.seconds as f64 + time.frac
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(seconds as f32))
+}
+
+/// Container name from magic bytes. Only used for reporting; the probe
+/// itself does its own detection.
+fn sniff_format(bytes: &[u8]) -> &'static str {
+    match bytes {
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => "wav",
+        [b'O', b'g', b'g', b'S', ..] => "ogg",
+        [b'f', b'L', b'a', b'C', ..] => "flac",
+        [0x1A, 0x45, 0xDF, 0xA3, ..] => "webm",
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => "mp4",
+        [b'I', b'D', b'3', ..] => "mp3",
+        [0xFF, second, ..] if second & 0xE0 == 0xE0 => "mp3",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::wav::samples_to_wav;
+
+    #[test]
+    fn wav_duration_comes_from_the_header() {
+        let bytes = samples_to_wav(&vec![0.0; 44_100 * 2 * 3 / 2], 44_100, 2).unwrap();
+
+        let probed = probe_duration(&bytes).unwrap();
+
+        assert_eq!(probed.format, "wav");
+        assert!((probed.duration_seconds.unwrap() - 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sniffs_common_containers() {
+        assert_eq!(sniff_format(b"OggS\0\x02"), "ogg");
+        assert_eq!(sniff_format(b"\0\0\0\x20ftypM4A "), "mp4");
+        assert_eq!(sniff_format(&[0xFF, 0xFB, 0x90]), "mp3");
+        assert_eq!(sniff_format(b"hello"), "unknown");
+    }
+}
@@ -0,0 +1,208 @@
+//! Offline clean-up chain for decoded 16 kHz mono recordings.
+//!
+//! Stages run in a fixed order, each only when its option is set:
+//!
+//! 1. High-pass: removes DC offset and low rumble (desk bumps, HVAC) first,
+//!    so it neither trips the gate nor inflates the peak that normalization
+//!    measures.
+//! 2. Noise gate: attenuates 10 ms windows quieter than the threshold. This
+//!    is the "denoise" stage: it quiets hiss between phrases but does not
+//!    touch noise under speech.
+//! 3. Gain or normalize: a fixed gain, or scaling so the peak lands on a
+//!    target level. They are mutually exclusive, since normalization would
+//!    undo any gain applied before it.
+//!
+//! Output is clamped to full scale when written, so a large gain clips
+//! rather than wraps.
+
+use serde::{Deserialize, Serialize};
+
+use super::analysis::rms_dbfs;
+
+const RATE: f32 = 16_000.0;
+const GATE_WINDOW: usize = 160; // 10 ms at 16 kHz
+/// Gated windows are turned down by 30 dB rather than muted, which sounds
+/// less like a dropout and leaves the engine some room tone.
+const GATE_FLOOR: f32 = 0.031_622_8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProcessingOptions {
+    /// High-pass cutoff in Hz (20-1000).
+    pub highpass_hz: Option<f32>,
+    /// Gate windows whose RMS is below this level in dBFS (-90 to -20).
+    pub noise_gate_dbfs: Option<f32>,
+    /// Fixed gain in dB (-40 to 40). Exclusive with `normalize_dbfs`.
+    pub gain_db: Option<f32>,
+    /// Scale so the peak lands at this level in dBFS (-40 to 0).
+    pub normalize_dbfs: Option<f32>,
+    /// Add TPDF dither when writing the 16-bit output. Off by default, which
+    /// keeps the output byte-for-byte as before; turn it on when the cleaned
+    /// file is for listening rather than transcription (see `wav`).
+    pub dither: bool,
+}
+
+/// Peak level of the decoded input and of the written output, so the effect
+/// of the chain on level is visible without opening an editor.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ReprocessReport {
+    /// `None` for digital silence.
+    pub peak_before_dbfs: Option<f32>,
+    /// After clamping to full scale. `None` for digital silence.
+    pub peak_after_dbfs: Option<f32>,
+}
+
+impl ProcessingOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        let check = |name: &str, value: Option<f32>, min: f32, max: f32| match value {
+            Some(v) if !(min..=max).contains(&v) => {
+                Err(format!("{name} must be between {min} and {max}, got {v}"))
+            }
+            _ => Ok(()),
+        };
+        check("highpassHz", self.highpass_hz, 20.0, 1000.0)?;
+        check("noiseGateDbfs", self.noise_gate_dbfs, -90.0, -20.0)?;
+        check("gainDb", self.gain_db, -40.0, 40.0)?;
+        check("normalizeDbfs", self.normalize_dbfs, -40.0, 0.0)?;
+        if self.gain_db.is_some() && self.normalize_dbfs.is_some() {
+            return Err(
+                "gainDb and normalizeDbfs cannot be combined; normalization sets the level on its own"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Run the stages `options` enables over 16 kHz mono `samples`, in place.
+/// `options` must have passed `validate`.
+pub fn process_samples(samples: &mut [f32], options: &ProcessingOptions) {
+    if let Some(cutoff) = options.highpass_hz {
+        highpass(samples, cutoff);
+    }
+    if let Some(threshold) = options.noise_gate_dbfs {
+        noise_gate(samples, threshold);
+    }
+    let gain = match (options.gain_db, options.normalize_dbfs) {
+        (Some(db), _) => Some(db_to_amplitude(db)),
+        (None, Some(target)) => {
+            let peak = peak(samples);
+            (peak > 0.0).then(|| db_to_amplitude(target) / peak)
+        }
+        (None, None) => None,
+    };
+    if let Some(gain) = gain {
+        samples.iter_mut().for_each(|s| *s *= gain);
+    }
+}
+
+/// Largest sample magnitude.
+pub fn peak(samples: &[f32]) -> f32 {
+    samples.iter().fold(0.0, |peak, s| peak.max(s.abs()))
+}
+
+fn db_to_amplitude(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Second-order Butterworth high-pass (RBJ cookbook biquad, Q = 1/sqrt(2)).
+fn highpass(samples: &mut [f32], cutoff_hz: f32) {
+    let w0 = 2.0 * std::f32::consts::PI * cutoff_hz / RATE;
+    let alpha = w0.sin() / std::f32::consts::SQRT_2;
+    let cos = w0.cos();
+    let a0 = 1.0 + alpha;
+    let b0 = (1.0 + cos) / 2.0 / a0;
+    let b1 = -(1.0 + cos) / a0;
+    let b2 = b0;
+    let a1 = -2.0 * cos / a0;
+    let a2 = (1.0 - alpha) / a0;
+
+    let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+    for sample in samples.iter_mut() {
+        let x0 = *sample;
+        let y0 = b0 * x0 + b1 * x1 + b2 * x2 - a1 * y1 - a2 * y2;
+        (x2, x1, y2, y1) = (x1, x0, y1, y0);
+        *sample = y0;
+    }
+}
+
+/// Attenuate windows below `threshold_dbfs`. The gain ramps linearly across
+/// each window toward its target, so opening and closing do not click.
+fn noise_gate(samples: &mut [f32], threshold_dbfs: f32) {
+    let mut gain = 1.0f32;
+    for window in samples.chunks_mut(GATE_WINDOW) {
+        let target = if rms_dbfs(window) < threshold_dbfs {
+            GATE_FLOOR
+        } else {
+            1.0
+        };
+        let step = (target - gain) / window.len() as f32;
+        for sample in window.iter_mut() {
+            gain += step;
+            *sample *= gain;
+        }
+        gain = target;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(hz: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * std::f32::consts::PI * hz * i as f32 / RATE).sin() * amplitude)
+            .collect()
+    }
+
+    #[test]
+    fn highpass_removes_dc_and_normalize_sets_the_peak() {
+        let mut samples: Vec<f32> = sine(1000.0, 0.25, 16_000)
+            .into_iter()
+            .map(|s| s + 0.3)
+            .collect();
+        let options = ProcessingOptions {
+            highpass_hz: Some(80.0),
+            normalize_dbfs: Some(-6.0),
+            ..Default::default()
+        };
+
+        process_samples(&mut samples, &options);
+
+        let tail = &samples[8000..];
+        let mean = tail.iter().sum::<f32>() / tail.len() as f32;
+        assert!(mean.abs() < 0.01, "DC should be gone: {mean}");
+        assert!((peak(&samples) - db_to_amplitude(-6.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn gate_quiets_hiss_but_keeps_speech() {
+        let mut samples = [sine(300.0, 0.001, 3200), sine(300.0, 0.5, 3200)].concat();
+        let options = ProcessingOptions {
+            noise_gate_dbfs: Some(-50.0),
+            ..Default::default()
+        };
+
+        process_samples(&mut samples, &options);
+
+        assert!(peak(&samples[320..3200]) < 0.001 * GATE_FLOOR * 1.01);
+        assert!(peak(&samples[3520..]) > 0.49);
+    }
+
+    #[test]
+    fn rejects_conflicting_or_out_of_range_options() {
+        let both = ProcessingOptions {
+            gain_db: Some(6.0),
+            normalize_dbfs: Some(-3.0),
+            ..Default::default()
+        };
+        assert!(both.validate().is_err());
+        let too_low = ProcessingOptions {
+            highpass_hz: Some(5.0),
+            ..Default::default()
+        };
+        assert!(too_low.validate().is_err());
+        assert!(ProcessingOptions::default().validate().is_ok());
+    }
+}
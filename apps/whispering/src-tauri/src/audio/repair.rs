@@ -0,0 +1,116 @@
+//! Fix the chunk sizes in a WAV header so `hound` will open the file.
+//!
+//! Streaming writers (some recorders, `ffmpeg` writing to a pipe) emit the
+//! header before they know the length and leave the RIFF and data sizes as
+//! `0` or `0xFFFFFFFF`; a tool killed mid-write leaves sizes claiming more
+//! audio than the file holds. The samples themselves are fine, so the repair
+//! is header arithmetic: the data chunk runs to the end of the file (cut to
+//! whole sample frames), and the RIFF size covers the whole file. Nothing is
+//! decoded or re-encoded.
+//!
+//! A data size that fits inside the file is trusted, since chunks after the
+//! audio (`LIST`, `id3 `) are legitimate; only `0` or an overrun counts as
+//! wrong.
+
+use std::io::Cursor;
+use std::path::Path;
+
+use hound::WavReader;
+
+use super::error::AudioError;
+use crate::temp_files::atomic_write;
+
+/// Offset of the first chunk after `RIFF<size>WAVE`.
+const FIRST_CHUNK: usize = 12;
+
+/// Rewrite the header of the WAV at `path` if its sizes are wrong. Returns
+/// whether anything changed. The fixed file is written next to the original
+/// and renamed over it, so a failure part-way leaves the original intact.
+pub fn repair_wav_file(path: &Path) -> Result<bool, AudioError> {
+    let mut bytes = std::fs::read(path)
+        .map_err(|e| AudioError::decode(format!("read {}: {e}", path.display())))?;
+    if !repair_header(&mut bytes)? {
+        return Ok(false);
+    }
+    WavReader::new(Cursor::new(&bytes))
+        .map_err(|e| AudioError::unsupported(format!("still unreadable after repair: {e}")))?;
+
+    atomic_write(path, &bytes)
+        .map_err(|e| AudioError::encode(format!("write {}: {e}", path.display())))?;
+    Ok(true)
+}
+
+/// Fix the RIFF and data sizes in `bytes` in place. Returns whether either
+/// changed.
+fn repair_header(bytes: &mut [u8]) -> Result<bool, AudioError> {
+    if bytes.len() < FIRST_CHUNK || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(AudioError::unsupported("not a RIFF/WAVE file"));
+    }
+    let file_len = u32::try_from(bytes.len())
+        .map_err(|_| AudioError::unsupported("file too large for a WAV header"))?;
+
+    let mut block_align = 1u32;
+    let mut offset = FIRST_CHUNK;
+    let data_size_at = loop {
+        if offset + 8 > bytes.len() {
+            return Err(AudioError::unsupported("no data chunk"));
+        }
+        let id = &bytes[offset..offset + 4];
+        let size = read_u32(bytes, offset + 4);
+        if id == b"data" {
+            break offset + 4;
+        }
+        if id == b"fmt " && offset + 22 <= bytes.len() {
+            block_align = u32::from(u16::from_le_bytes([bytes[offset + 20], bytes[offset + 21]]));
+        }
+        // Chunks are padded to an even length.
+        offset = offset
+            .checked_add(8 + size as usize + (size as usize & 1))
+            .ok_or_else(|| AudioError::unsupported("no data chunk"))?;
+    };
+
+    let mut changed = false;
+    let available = file_len - (data_size_at as u32 + 4);
+    let declared = read_u32(bytes, data_size_at);
+    if declared == 0 || declared > available {
+        let actual = available - available % block_align.max(1);
+        changed |= declared != actual;
+        write_u32(bytes, data_size_at, actual);
+    }
+    let riff_size = file_len - 8;
+    changed |= read_u32(bytes, 4) != riff_size;
+    write_u32(bytes, 4, riff_size);
+    Ok(changed)
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+fn write_u32(bytes: &mut [u8], at: usize, value: u32) {
+    bytes[at..at + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::wav::samples_to_wav;
+
+    #[test]
+    fn streamed_sizes_are_rewritten_from_the_file_length() {
+        let mut bytes = samples_to_wav(&vec![0.25; 1_600], 16_000, 1).unwrap();
+        let good = bytes.clone();
+        // What a writer to a pipe leaves behind, plus a torn final sample.
+        write_u32(&mut bytes, 4, 0);
+        write_u32(&mut bytes, 40, u32::MAX);
+        bytes.push(0x7F);
+        assert!(WavReader::new(Cursor::new(&bytes)).is_err());
+
+        assert!(repair_header(&mut bytes).unwrap());
+
+        let reader = WavReader::new(Cursor::new(&bytes)).unwrap();
+        assert_eq!(reader.duration(), 1_600);
+        assert_eq!(read_u32(&bytes, 40), read_u32(&good, 40));
+        assert!(!repair_header(&mut bytes).unwrap());
+    }
+}
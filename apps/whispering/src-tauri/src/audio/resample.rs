@@ -15,6 +15,10 @@ use super::error::AudioError;
 /// `target_rate / 8` cannot be resampled.
 const MAX_RATIO: f64 = 8.0;
 
+/// Bounds on the input frames fed to rubato per call; see `frames_per_call`.
+const MIN_CHUNK_FRAMES: usize = 256;
+const MAX_CHUNK_FRAMES: usize = 8_192;
+
 /// Resample mono `samples` from `source_rate` to `target_rate`. Returns the
 /// input untouched if the rates already match or the input is empty.
 pub fn resample_mono(
@@ -41,33 +45,86 @@ pub fn resample_mono(
         window: WindowFunction::BlackmanHarris2,
     };
 
-    let chunk_size = 1024;
+    let chunk_size = frames_per_call(samples.len(), source_rate);
     let mut resampler = SincFixedIn::<f32>::new(ratio, MAX_RATIO, params, chunk_size, 1)
         .map_err(|e| AudioError::resample(format!("resampler init failed: {e}")))?;
+    let step_err =
+        |e: rubato::ResampleError| AudioError::resample(format!("resample step failed: {e}"));
 
+    // `SincFixedIn` centers its first output on the first input sample
+    // (it starts half a filter back, over zeroed history), so its output is
+    // already aligned; `output_delay()` does not apply and cutting it would
+    // shift the audio early and leave silence at the end. The last input
+    // samples only come out once the filter has moved past them, so run
+    // until the expected length is covered, then cut exactly that.
     let expected_len = (samples.len() as f64 * ratio).round() as usize;
-    let mut output = Vec::with_capacity(expected_len);
-
-    let mut pos = 0;
-    while pos < samples.len() {
-        let end = (pos + chunk_size).min(samples.len());
-        let mut chunk: Vec<f32> = samples[pos..end].to_vec();
-        if chunk.len() < chunk_size {
-            // rubato's fixed-input variant requires every call to be the full
-            // chunk size; pad the trailing chunk with silence.
-            chunk.resize(chunk_size, 0.0);
-        }
+    let mut output = Vec::with_capacity(expected_len + chunk_size);
 
+    let mut chunks = samples.chunks_exact(chunk_size);
+    for chunk in &mut chunks {
+        let waves_out = resampler.process(&[chunk], None).map_err(step_err)?;
+        output.extend_from_slice(&waves_out[0]);
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        // Zero-padded to a full chunk inside rubato; the padding's output
+        // lands past `expected_len` and is cut below.
         let waves_out = resampler
-            .process(&[chunk], None)
-            .map_err(|e| AudioError::resample(format!("resample step failed: {e}")))?;
-
+            .process_partial(Some(&[tail][..]), None)
+            .map_err(step_err)?;
+        output.extend_from_slice(&waves_out[0]);
+    }
+    while output.len() < expected_len {
+        let waves_out = resampler
+            .process_partial(None::<&[&[f32]]>, None)
+            .map_err(step_err)?;
         output.extend_from_slice(&waves_out[0]);
-        pos += chunk_size;
     }
 
-    // Trim the synthetic tail produced by the zero-padded final chunk.
     output.truncate(expected_len);
 
     Ok(output)
 }
+
+/// Input frames per resampler call: about 50 ms of the source, so the
+/// per-call overhead stays small at high rates, and never more than a short
+/// clip itself, so it does not pay for filtering a mostly-silent chunk.
+fn frames_per_call(len: usize, source_rate: u32) -> usize {
+    (source_rate as usize / 20)
+        .clamp(MIN_CHUNK_FRAMES, MAX_CHUNK_FRAMES)
+        .min(len.max(MIN_CHUNK_FRAMES))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq_hz: f32, rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * std::f32::consts::PI * freq_hz * i as f32 / rate as f32).sin() * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn short_clip_keeps_its_duration_and_alignment() {
+        // 200 ms at 48 kHz and 44.1 kHz, both to 16 kHz.
+        for source_rate in [48_000, 44_100] {
+            let input = sine(100.0, source_rate, source_rate as usize / 5);
+
+            let output = resample_mono(input, source_rate, 16_000).unwrap();
+
+            assert!(output.len().abs_diff(3_200) <= 1, "{}", output.len());
+            // Output sample i is the input at the same instant, right up to
+            // the last one.
+            let reference = sine(100.0, 16_000, output.len());
+            let max_error = output[100..output.len() - 100]
+                .iter()
+                .zip(&reference[100..])
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0, f32::max);
+            assert!(max_error < 0.03, "{source_rate} Hz: error {max_error}");
+            let tail_energy: f32 = output[output.len() - 50..].iter().map(|s| s * s).sum();
+            assert!(tail_energy > 1.0, "tail was cut: {tail_energy}");
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! De-interleave a stereo WAV into one mono WAV per channel.
+//!
+//! For dual-mic interviews recorded as stereo (one speaker per side), each
+//! output can be transcribed or edited on its own. Samples are copied as-is:
+//! the outputs keep the source's sample rate, bit depth, and int/float
+//! format, so nothing is resampled or requantized.
+
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+use super::error::AudioError;
+use crate::temp_files::TEMP_FILE_PREFIX;
+
+/// Split the stereo WAV at `source` into `left_output` and `right_output`.
+/// Fails with `UnsupportedFormat` unless the source has exactly 2 channels,
+/// and before anything is written when two of the paths name the same file.
+///
+/// Each output is written to a temp file next to it and renamed into place
+/// only once both are complete, so a failure leaves no partial output and
+/// never touches an existing file at either path.
+pub fn split_stereo_wav(
+    source: &Path,
+    left_output: &Path,
+    right_output: &Path,
+) -> Result<(), AudioError> {
+    check_distinct(source, left_output, right_output)?;
+
+    let reader = WavReader::open(source)
+        .map_err(|e| AudioError::decode(format!("open {}: {e}", source.display())))?;
+    let spec = reader.spec();
+    if spec.channels != 2 {
+        return Err(AudioError::unsupported(format!(
+            "expected a 2-channel WAV, {} has {}",
+            source.display(),
+            spec.channels
+        )));
+    }
+
+    let mono = WavSpec {
+        channels: 1,
+        ..spec
+    };
+    let create = |path: &Path| -> Result<(NamedTempFile, WavWriter<BufWriter<File>>), AudioError> {
+        let err = |e: std::io::Error| AudioError::encode(format!("create {}: {e}", path.display()));
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let temp = tempfile::Builder::new()
+            .prefix(TEMP_FILE_PREFIX)
+            .tempfile_in(dir)
+            .map_err(err)?;
+        let file = temp.as_file().try_clone().map_err(err)?;
+        let writer = WavWriter::new(BufWriter::new(file), mono)
+            .map_err(|e| AudioError::encode(format!("create {}: {e}", path.display())))?;
+        Ok((temp, writer))
+    };
+    // The temp files are removed on drop, so any early return below cleans
+    // up after itself.
+    let (left_temp, mut left) = create(left_output)?;
+    let (right_temp, mut right) = create(right_output)?;
+
+    match spec.sample_format {
+        SampleFormat::Float => {
+            copy_channels::<f32, _>(reader, &mut left, &mut right)?;
+        }
+        SampleFormat::Int => {
+            copy_channels::<i32, _>(reader, &mut left, &mut right)?;
+        }
+    }
+
+    for (writer, path) in [(left, left_output), (right, right_output)] {
+        writer
+            .finalize()
+            .map_err(|e| AudioError::encode(format!("finalize {}: {e}", path.display())))?;
+    }
+    for (temp, path) in [(left_temp, left_output), (right_temp, right_output)] {
+        temp.persist(path)
+            .map_err(|e| AudioError::encode(format!("write {}: {}", path.display(), e.error)))?;
+    }
+    Ok(())
+}
+
+/// Reject any two of the three paths resolving to the same file. Writing an
+/// output over the source would destroy the recording being split.
+fn check_distinct(source: &Path, left: &Path, right: &Path) -> Result<(), AudioError> {
+    let source_resolved = source
+        .canonicalize()
+        .map_err(|e| AudioError::decode(format!("open {}: {e}", source.display())))?;
+    let left_resolved = resolve_output(left)?;
+    let right_resolved = resolve_output(right)?;
+    if left_resolved == source_resolved || right_resolved == source_resolved {
+        return Err(AudioError::encode(format!(
+            "output would overwrite the source {}",
+            source.display()
+        )));
+    }
+    if left_resolved == right_resolved {
+        return Err(AudioError::encode(format!(
+            "left and right outputs are the same file {}",
+            left.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Canonical form of an output path that may not exist yet: its
+/// canonicalized parent joined with its file name.
+fn resolve_output(path: &Path) -> Result<PathBuf, AudioError> {
+    let err = |e: std::io::Error| AudioError::encode(format!("resolve {}: {e}", path.display()));
+    if path.exists() {
+        return path.canonicalize().map_err(err);
+    }
+    let name = path
+        .file_name()
+        .ok_or_else(|| AudioError::encode(format!("{} is not a file path", path.display())))?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    Ok(parent.canonicalize().map_err(err)?.join(name))
+}
+
+fn copy_channels<S, R>(
+    mut reader: WavReader<R>,
+    left: &mut WavWriter<BufWriter<File>>,
+    right: &mut WavWriter<BufWriter<File>>,
+) -> Result<(), AudioError>
+where
+    S: hound::Sample,
+    R: std::io::Read,
+{
+    let mut samples = reader.samples::<S>();
+    while let Some(l) = samples.next() {
+        let l = l.map_err(|e| AudioError::decode(e.to_string()))?;
+        let r = samples
+            .next()
+            .ok_or_else(|| AudioError::decode("stereo WAV ends mid-frame"))?
+            .map_err(|e| AudioError::decode(e.to_string()))?;
+        left.write_sample(l)
+            .map_err(|e| AudioError::encode(e.to_string()))?;
+        right
+            .write_sample(r)
+            .map_err(|e| AudioError::encode(e.to_string()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_channels_and_keeps_the_format() {
+        let dir = std::env::temp_dir().join(format!("whispering-split-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (source, left, right) = (
+            dir.join("stereo.wav"),
+            dir.join("left.wav"),
+            dir.join("right.wav"),
+        );
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: 44_100,
+            bits_per_sample: 24,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(&source, spec).unwrap();
+        for i in 0..100i32 {
+            writer.write_sample(i * 1000).unwrap();
+            writer.write_sample(-i * 1000).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        split_stereo_wav(&source, &left, &right).unwrap();
+
+        for (path, sign) in [(&left, 1), (&right, -1)] {
+            let mut reader = WavReader::open(path).unwrap();
+            assert_eq!(
+                reader.spec(),
+                WavSpec {
+                    channels: 1,
+                    ..spec
+                }
+            );
+            let samples: Vec<i32> = reader.samples().map(Result::unwrap).collect();
+            assert_eq!(
+                samples,
+                (0..100).map(|i| sign * i * 1000).collect::<Vec<_>>()
+            );
+        }
+
+        let err = split_stereo_wav(&left, &dir.join("a.wav"), &dir.join("b.wav")).unwrap_err();
+        assert!(matches!(err, AudioError::UnsupportedFormat { .. }));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn refuses_outputs_that_alias_the_source_or_each_other() {
+        let dir =
+            std::env::temp_dir().join(format!("whispering-split-alias-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("stereo.wav");
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: 16_000,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(&source, spec).unwrap();
+        for _ in 0..10 {
+            writer.write_sample(1i16).unwrap();
+            writer.write_sample(2i16).unwrap();
+        }
+        writer.finalize().unwrap();
+        let original = std::fs::read(&source).unwrap();
+
+        // The same file through a different spelling still counts.
+        let aliased = dir.join(".").join("stereo.wav");
+        assert!(split_stereo_wav(&source, &aliased, &dir.join("r.wav")).is_err());
+        assert!(split_stereo_wav(&source, &dir.join("l.wav"), &source).is_err());
+        assert!(split_stereo_wav(&source, &dir.join("x.wav"), &dir.join("x.wav")).is_err());
+
+        assert_eq!(std::fs::read(&source).unwrap(), original);
+        let left_over: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(left_over, ["stereo.wav"]);
+    }
+}
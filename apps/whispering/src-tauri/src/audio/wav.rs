@@ -0,0 +1,154 @@
+//! Build a WAV file in memory from f32 samples, and read one back at its
+//! native rate and channel count.
+//!
+//! The written shape is the one the rest of the pipeline accepts
+//! everywhere: 16-bit integer PCM, which every decoder and cloud provider
+//! reads. Used for test fixtures and by callers that need to hand a byte
+//! buffer to something expecting a file. Recording artifacts do not go
+//! through here; they are written as 32-bit float straight to disk (see
+//! `recorder::artifact`).
+//!
+//! `samples_to_wav_dithered` adds TPDF dither before quantizing. Plain
+//! truncation to 16 bits turns the rounding error into distortion that
+//! follows the signal, audible in quiet passages and fades; dither trades it
+//! for a constant, benign noise floor around -96 dBFS. Engines cannot hear
+//! the difference, so transcription paths use the plain writer; enable
+//! dither for audio a person will listen to (exports, cleaned recordings).
+//!
+//! `read_wav_samples` skips the 16 kHz mono decode path on purpose: analyses
+//! such as clipping detection need the samples exactly as recorded.
+
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use std::io::Cursor;
+use std::path::Path;
+
+use super::error::AudioError;
+
+/// Encode interleaved `samples` (`channels` per frame, nominally in
+/// `-1.0..=1.0`) as a 16-bit PCM WAV. Out-of-range values are clamped.
+pub fn samples_to_wav(
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+) -> Result<Vec<u8>, AudioError> {
+    write_pcm16(samples, sample_rate, channels, |sample| {
+        (sample.clamp(-1.0, 1.0) * 32767.0) as i16
+    })
+}
+
+/// `samples_to_wav` with triangular (TPDF) dither of +/-1 LSB added before
+/// rounding to 16 bits. The noise comes from a fixed-seed generator, so the
+/// same input always produces the same file.
+pub fn samples_to_wav_dithered(
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+) -> Result<Vec<u8>, AudioError> {
+    let mut state = 0x9E37_79B9u32;
+    let mut uniform = move || {
+        // xorshift32: plenty for dither, which only needs to be uncorrelated
+        // with the signal.
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as f32 / u32::MAX as f32
+    };
+    write_pcm16(samples, sample_rate, channels, |sample| {
+        let dither = uniform() - uniform();
+        (sample.clamp(-1.0, 1.0) * 32767.0 + dither)
+            .round()
+            .clamp(-32768.0, 32767.0) as i16
+    })
+}
+
+fn write_pcm16(
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+    mut quantize: impl FnMut(f32) -> i16,
+) -> Result<Vec<u8>, AudioError> {
+    let spec = WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let encode = |e: hound::Error| AudioError::encode(format!("wav write failed: {e}"));
+
+    let mut cursor = Cursor::new(Vec::new());
+    let mut writer = WavWriter::new(&mut cursor, spec).map_err(encode)?;
+    for &sample in samples {
+        writer.write_sample(quantize(sample)).map_err(encode)?;
+    }
+    writer.finalize().map_err(encode)?;
+    Ok(cursor.into_inner())
+}
+
+/// Interleaved samples of the WAV at `path` scaled to `-1.0..=1.0`, with
+/// its sample rate and channel count. Integer PCM of any bit depth is
+/// divided by its full-scale value; float PCM is returned as stored.
+pub fn read_wav_samples(path: &Path) -> Result<(Vec<f32>, u32, u16), AudioError> {
+    let decode = |e: hound::Error| AudioError::decode(format!("{}: {e}", path.display()));
+    let mut reader = WavReader::open(path).map_err(decode)?;
+    let spec = reader.spec();
+    let samples = match spec.sample_format {
+        SampleFormat::Float => reader.samples::<f32>().collect::<Result<Vec<_>, _>>(),
+        SampleFormat::Int => {
+            let full_scale = (1i64 << (spec.bits_per_sample.clamp(1, 32) - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / full_scale))
+                .collect()
+        }
+    }
+    .map_err(decode)?;
+    Ok((samples, spec.sample_rate, spec.channels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_a_readable_wav_with_the_given_layout() {
+        let bytes = samples_to_wav(&[0.0, 0.5, -0.5, 2.0], 22_050, 2).unwrap();
+
+        let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.spec().sample_rate, 22_050);
+        let samples: Vec<i16> = reader.samples().map(Result::unwrap).collect();
+        assert_eq!(samples, vec![0, 16383, -16383, 32767]);
+    }
+
+    #[test]
+    fn dither_stays_within_one_lsb_and_breaks_up_truncation() {
+        // 0.3 LSB: truncation writes all zeros, losing the signal entirely.
+        let quiet = vec![0.3 / 32767.0; 10_000];
+        let bytes = samples_to_wav_dithered(&quiet, 16_000, 1).unwrap();
+
+        let written: Vec<i16> = WavReader::new(Cursor::new(bytes))
+            .unwrap()
+            .samples()
+            .map(Result::unwrap)
+            .collect();
+        assert!(written.iter().all(|s| (-1..=2).contains(s)));
+        let mean = written.iter().map(|&s| s as f32).sum::<f32>() / written.len() as f32;
+        assert!(
+            (mean - 0.3).abs() < 0.05,
+            "dither should preserve the level: {mean}"
+        );
+    }
+
+    #[test]
+    fn reads_int_pcm_back_at_native_layout() {
+        let path = std::env::temp_dir().join(format!("whispering-wav-{}.wav", std::process::id()));
+        std::fs::write(&path, samples_to_wav(&[0.5, -1.0], 44_100, 2).unwrap()).unwrap();
+
+        let (samples, sample_rate, channels) = read_wav_samples(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!((sample_rate, channels), (44_100, 2));
+        assert!((samples[0] - 0.5).abs() < 1e-4);
+        assert!((samples[1] + 1.0).abs() < 1e-4);
+    }
+}
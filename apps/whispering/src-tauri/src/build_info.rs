@@ -0,0 +1,65 @@
+//! What this build of the app was compiled with, so the UI can hide options
+//! the binary cannot serve instead of letting them fail at transcribe time.
+//!
+//! The crate defines no cargo features of its own: which engines and
+//! accelerators exist is decided per target by the `transcribe-rs` feature
+//! lists in `Cargo.toml`. The `cfg!`s below mirror those target tables and
+//! must stay in sync with them.
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildFeatures {
+    /// whisper.cpp is compiled in.
+    pub whisper_enabled: bool,
+    /// The ONNX engines (Parakeet, Moonshine) are compiled in.
+    pub onnx_enabled: bool,
+    /// GPU backend whisper.cpp was built with: `metal` or `vulkan`.
+    pub whisper_gpu_backend: Option<String>,
+    /// ONNX Runtime execution provider compiled in besides CPU: `directml`
+    /// or `coreml`. CoreML is compiled in on macOS but not selected at
+    /// startup (see `run`).
+    pub onnx_accelerator: Option<String>,
+    /// `std::env::consts::OS`: `windows`, `macos`, or `linux`.
+    pub platform: String,
+    /// `std::env::consts::ARCH`, e.g. `x86_64` or `aarch64`.
+    pub arch: String,
+    pub app_version: String,
+}
+
+/// Report the engines, accelerators, and platform this binary was built for.
+#[tauri::command]
+#[specta::specta]
+pub fn get_build_features(app: AppHandle) -> BuildFeatures {
+    let whisper_gpu_backend = if cfg!(target_os = "macos") {
+        Some("metal")
+    } else if cfg!(any(target_os = "windows", target_os = "linux")) {
+        Some("vulkan")
+    } else {
+        None
+    };
+    let onnx_accelerator = if cfg!(target_os = "windows") {
+        Some("directml")
+    } else if cfg!(target_os = "macos") {
+        Some("coreml")
+    } else {
+        None
+    };
+    let desktop = cfg!(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux"
+    ));
+
+    BuildFeatures {
+        whisper_enabled: desktop,
+        onnx_enabled: desktop,
+        whisper_gpu_backend: whisper_gpu_backend.map(str::to_string),
+        onnx_accelerator: onnx_accelerator.map(str::to_string),
+        platform: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        app_version: app.package_info().version.to_string(),
+    }
+}
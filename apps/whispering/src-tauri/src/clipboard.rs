@@ -0,0 +1,67 @@
+//! Saving and restoring the clipboard around `write_text`'s paste.
+//!
+//! The clipboard plugin can read back text and images, so those round-trip.
+//! It has no way to read a file list or other formats (rich text from a word
+//! processor, app-private data), and it cannot tell such content apart from
+//! an empty clipboard: both just fail to read as text or an image. That case
+//! is reported to the caller as `ClipboardRestore::Unreadable` rather than
+//! papered over, so the UI can tell the user their clipboard now holds the
+//! transcript, or switch them to typing.
+
+use log::debug;
+use serde::Serialize;
+use tauri::image::Image;
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// What happened to the clipboard's previous content after a paste.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ClipboardRestore {
+    /// The previous text or image is back on the clipboard.
+    Restored,
+    /// The clipboard was never saved: the text was typed rather than pasted,
+    /// or restoring is turned off in the paste settings.
+    Skipped,
+    /// The clipboard held nothing readable as text or an image. It was
+    /// either empty or held files or another format the clipboard plugin
+    /// cannot read back; it now holds the transcript.
+    Unreadable,
+}
+
+/// The clipboard's content before the paste.
+pub enum ClipboardSnapshot {
+    Text(String),
+    Image(Image<'static>),
+    Unreadable,
+}
+
+impl ClipboardSnapshot {
+    /// Read whatever the clipboard holds. Text wins when both are offered,
+    /// as it did before images were saved.
+    pub fn take(app: &AppHandle) -> Self {
+        let clipboard = app.clipboard();
+        if let Ok(text) = clipboard.read_text() {
+            return Self::Text(text);
+        }
+        match clipboard.read_image() {
+            Ok(image) => Self::Image(image.to_owned()),
+            Err(e) => {
+                debug!("Clipboard holds neither text nor an image: {e}");
+                Self::Unreadable
+            }
+        }
+    }
+
+    /// Put the saved content back.
+    pub fn restore(self, app: &AppHandle) -> Result<ClipboardRestore, String> {
+        let clipboard = app.clipboard();
+        match self {
+            Self::Text(text) => clipboard.write_text(text),
+            Self::Image(image) => clipboard.write_image(&image),
+            Self::Unreadable => return Ok(ClipboardRestore::Unreadable),
+        }
+        .map_err(|e| format!("Failed to restore clipboard: {}", e))?;
+        Ok(ClipboardRestore::Restored)
+    }
+}
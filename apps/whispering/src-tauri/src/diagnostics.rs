@@ -0,0 +1,119 @@
+//! One report with what support usually has to ask for: OS and version,
+//! the build, the recording devices, the transcription model, and the end
+//! of the crash log. The frontend renders it as copyable text.
+//!
+//! Each part is collected independently; one that fails (no audio host, an
+//! unreadable log) is reported as missing or as its error rather than
+//! failing the whole report, since a broken piece is often what the user is
+//! asking about.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::build_info::{get_build_features, BuildFeatures};
+use crate::recorder::{Recorder, RecordingDevice};
+use crate::transcription::{LoadedModelInfo, LocalModelState, ModelManager};
+
+const CRASH_LOG_FILE: &str = "whispering-crash.log";
+
+/// How much of the end of the crash log to include: the last few panics
+/// with their backtraces, without pasting a log that has grown for months.
+const CRASH_LOG_TAIL_BYTES: u64 = 16 * 1024;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    /// Engines, accelerators, platform, architecture, and app version.
+    pub build: BuildFeatures,
+    /// OS version as the OS reports it, e.g. `14.5.0` or `10.0.22631`.
+    pub os_version: String,
+    pub recording_devices: Vec<RecordingDevice>,
+    /// Why `recording_devices` is empty, when listing them failed.
+    pub recording_devices_error: Option<String>,
+    pub model_state: LocalModelState,
+    pub loaded_model: LoadedModelInfo,
+    /// The end of the panic log, starting at a line boundary. `None` when
+    /// the app has never crashed (or the log was cleared).
+    pub crash_log_tail: Option<String>,
+}
+
+/// Where the panic hook in `run` appends crash reports. The system temp
+/// directory, so it works before any app path is resolved.
+pub(crate) fn crash_log_path() -> PathBuf {
+    std::env::temp_dir().join(CRASH_LOG_FILE)
+}
+
+/// Collect a support report. Never fails; see the module docs.
+#[tauri::command]
+#[specta::specta]
+pub async fn collect_diagnostics(
+    app: AppHandle,
+    recorder: State<'_, Mutex<Recorder>>,
+    model_manager: State<'_, ModelManager>,
+) -> Result<Diagnostics, String> {
+    let devices = recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {e}"))
+        .and_then(|recorder| recorder.list_devices());
+    let (recording_devices, recording_devices_error) = match devices {
+        Ok(devices) => (devices, None),
+        Err(e) => (Vec::new(), Some(e)),
+    };
+
+    Ok(Diagnostics {
+        build: get_build_features(app),
+        os_version: tauri_plugin_os::version().to_string(),
+        recording_devices,
+        recording_devices_error,
+        model_state: model_manager.snapshot(),
+        loaded_model: model_manager.loaded_model_info(),
+        crash_log_tail: read_tail(&crash_log_path(), CRASH_LOG_TAIL_BYTES),
+    })
+}
+
+/// The last `max_bytes` of the file at `path`, from the first full line on.
+/// `None` when the file is missing, unreadable, or empty.
+fn read_tail(path: &Path, max_bytes: u64) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let start = len.saturating_sub(max_bytes);
+    file.seek(SeekFrom::Start(start)).ok()?;
+    let mut bytes = Vec::with_capacity((len - start) as usize);
+    file.read_to_end(&mut bytes).ok()?;
+
+    let text = String::from_utf8_lossy(&bytes);
+    // Reading from mid-file lands inside a line; start at the next one.
+    let text = match (start > 0, text.find('\n')) {
+        (true, Some(newline)) => &text[newline + 1..],
+        _ => &text[..],
+    };
+    (!text.trim().is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tail_starts_at_a_line_boundary() {
+        let path =
+            std::env::temp_dir().join(format!("whispering-diag-tail-{}", std::process::id()));
+        std::fs::write(&path, "first panic\nsecond panic\nthird panic\n").unwrap();
+
+        let tail = read_tail(&path, 20);
+        let whole = read_tail(&path, 1_000);
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(tail.as_deref(), Some("third panic\n"));
+        assert_eq!(
+            whole.as_deref(),
+            Some("first panic\nsecond panic\nthird panic\n")
+        );
+        assert_eq!(read_tail(&path, 20), None);
+    }
+}
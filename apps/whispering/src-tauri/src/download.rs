@@ -15,8 +15,14 @@
 //! `.partial` convention and the size check that promotes it
 //! (`local-model-folder.ts`), and removes the partial on any error (including a
 //! cancel), so there is nothing for Rust to clean up.
+//!
+//! `fetch_to_memory` is the same transfer into a buffer instead of a file,
+//! for audio that is transcribed straight from a link (`transcribe_url`).
+//! Unlike a model download it is capped in size and time, since the URL is
+//! whatever the user pasted.
 
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
@@ -54,6 +60,25 @@ impl DownloadManager {
             .remove(id);
     }
 
+    /// Run `transfer` as a task that `cancel_download(id)` can abort. A
+    /// cancel surfaces as an `Err`.
+    pub(crate) async fn run<T: Send + 'static>(
+        &self,
+        id: &str,
+        transfer: impl Future<Output = Result<T, String>> + Send + 'static,
+    ) -> Result<T, String> {
+        let task = tokio::spawn(transfer);
+        self.register(id, task.abort_handle());
+
+        let outcome = task.await;
+        self.unregister(id);
+
+        match outcome {
+            Ok(inner) => inner,
+            Err(join_err) => Err(format!("download interrupted: {join_err}")),
+        }
+    }
+
     fn abort(&self, id: &str) {
         if let Some(handle) = self
             .inflight
@@ -79,6 +104,70 @@ pub struct DownloadProgress {
     total_bytes: f64,
 }
 
+/// Largest file `fetch_to_memory` accepts from `transcribe_url`: hours of
+/// compressed audio, while a wrong link (a video, an archive) fails instead
+/// of filling memory.
+pub const MAX_FETCH_BYTES: u64 = 512 * 1024 * 1024;
+
+/// How long a `fetch_to_memory` transfer may take, connect to last byte.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Sends byte counts on a progress channel, throttled.
+struct ProgressReporter {
+    channel: Channel<DownloadProgress>,
+    bytes_received: u64,
+    total_bytes: f64,
+    last_emit: Instant,
+}
+
+impl ProgressReporter {
+    // A download fires thousands of small chunks, but each `send` crosses IPC
+    // and repaints the progress bar, and no one reads progress faster than this.
+    // Throttle to ~10/sec; the true final count is force-sent by `finish`.
+    const THROTTLE: Duration = Duration::from_millis(100);
+
+    fn new(channel: Channel<DownloadProgress>, response: &reqwest::Response) -> Self {
+        Self {
+            channel,
+            bytes_received: 0,
+            total_bytes: response.content_length().unwrap_or(0) as f64,
+            last_emit: Instant::now(),
+        }
+    }
+
+    fn received(&mut self, bytes: usize) {
+        self.bytes_received += bytes as u64;
+        if self.last_emit.elapsed() >= Self::THROTTLE {
+            self.send();
+            self.last_emit = Instant::now();
+        }
+    }
+
+    /// Land on the true final byte count (typically 100%); the throttle may
+    /// have skipped the last chunk's update.
+    fn finish(self) {
+        self.send();
+    }
+
+    fn send(&self) {
+        // The receiver may already be gone (e.g. window closed); ignore.
+        let _ = self.channel.send(DownloadProgress {
+            bytes_received: self.bytes_received as f64,
+            total_bytes: self.total_bytes,
+        });
+    }
+}
+
+fn check_status(response: &reqwest::Response) -> Result<(), String> {
+    if !response.status().is_success() {
+        return Err(format!(
+            "request failed with status code {}",
+            response.status().as_u16()
+        ));
+    }
+    Ok(())
+}
+
 /// Stream a URL to a file on disk, reporting whole-file progress on `channel`.
 /// Pure transfer; registration and cancellation live in `download_file`.
 async fn stream_to_file(
@@ -91,13 +180,7 @@ async fn stream_to_file(
         .send()
         .await
         .map_err(|e| e.to_string())?;
-    if !response.status().is_success() {
-        return Err(format!(
-            "request failed with status code {}",
-            response.status().as_u16()
-        ));
-    }
-    let total_bytes = response.content_length().unwrap_or(0) as f64;
+    check_status(&response)?;
 
     let mut file = tokio::io::BufWriter::new(
         tokio::fs::File::create(file_path)
@@ -105,36 +188,58 @@ async fn stream_to_file(
             .map_err(|e| e.to_string())?,
     );
 
-    let mut bytes_received: u64 = 0;
-    let mut last_emit = Instant::now();
-    // A download fires thousands of small chunks, but each `send` crosses IPC
-    // and repaints the progress bar, and no one reads progress faster than this.
-    // Throttle to ~10/sec; the true final count is force-sent after the loop.
-    let throttle = Duration::from_millis(100);
+    let mut progress = ProgressReporter::new(channel, &response);
     let mut stream = response.bytes_stream();
     while let Some(chunk) = stream.next().await {
         let chunk = chunk.map_err(|e| e.to_string())?;
         file.write_all(&chunk).await.map_err(|e| e.to_string())?;
-        bytes_received += chunk.len() as u64;
-        if last_emit.elapsed() >= throttle {
-            // The receiver may already be gone (e.g. window closed); ignore.
-            let _ = channel.send(DownloadProgress {
-                bytes_received: bytes_received as f64,
-                total_bytes,
-            });
-            last_emit = Instant::now();
-        }
+        progress.received(chunk.len());
     }
     file.flush().await.map_err(|e| e.to_string())?;
-    // Land on the true final byte count (typically 100%); the throttle may have
-    // skipped the last chunk's update.
-    let _ = channel.send(DownloadProgress {
-        bytes_received: bytes_received as f64,
-        total_bytes,
-    });
+    progress.finish();
     Ok(())
 }
 
+/// Download `url` into memory, reporting progress on `channel` like
+/// `download_file`. Fails once more than `max_bytes` arrive (up front when
+/// the server announces a larger `content-length`) or after
+/// `FETCH_TIMEOUT`. Pure transfer; wrap in `DownloadManager::run` to make
+/// it cancelable.
+pub(crate) async fn fetch_to_memory(
+    url: &str,
+    max_bytes: u64,
+    channel: Channel<DownloadProgress>,
+) -> Result<Vec<u8>, String> {
+    let too_large = || format!("file is larger than {} MB", max_bytes / (1024 * 1024));
+    let response = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    check_status(&response)?;
+    let announced = response.content_length().unwrap_or(0);
+    if announced > max_bytes {
+        return Err(too_large());
+    }
+
+    let mut bytes = Vec::with_capacity(announced as usize);
+    let mut progress = ProgressReporter::new(channel, &response);
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| e.to_string())?;
+        if (bytes.len() + chunk.len()) as u64 > max_bytes {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+        progress.received(chunk.len());
+    }
+    progress.finish();
+    Ok(bytes)
+}
+
 /// Download `url` to `file_path`, cancelable via `cancel_download(download_id)`.
 ///
 /// Runs the transfer in a `tokio` task whose `AbortHandle` is registered under
@@ -150,16 +255,11 @@ pub async fn download_file(
     on_progress: Channel<DownloadProgress>,
     manager: State<'_, DownloadManager>,
 ) -> Result<(), String> {
-    let task = tokio::spawn(async move { stream_to_file(&url, &file_path, on_progress).await });
-    manager.register(&download_id, task.abort_handle());
-
-    let outcome = task.await;
-    manager.unregister(&download_id);
-
-    match outcome {
-        Ok(inner) => inner,
-        Err(join_err) => Err(format!("download interrupted: {join_err}")),
-    }
+    manager
+        .run(&download_id, async move {
+            stream_to_file(&url, &file_path, on_progress).await
+        })
+        .await
 }
 
 /// Abort the in-flight download registered under `download_id`, if any. The
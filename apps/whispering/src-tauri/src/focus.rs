@@ -0,0 +1,265 @@
+//! Wait for the user's target window to be frontmost again before pasting.
+//!
+//! Dictation starts while the user is in some other app; by the time the
+//! transcript is ready the overlay has been dismissed (or Whispering's own
+//! window was brought up) and focus may still be on its way back. Pasting
+//! right away sends the text to whichever window won that race, which is how
+//! transcripts end up pasted into Whispering itself.
+//!
+//! `start_recording` remembers the frontmost window, and `write_text` calls
+//! `wait_before_paste` before touching the clipboard:
+//!
+//! - With `wait_for_focus` on and a remembered target, poll until that window
+//!   is frontmost again (up to `focus_timeout_ms`), then paste immediately.
+//! - Otherwise, or if the target never comes back, sleep the fixed `delay_ms`.
+//!
+//! Waiting still races with anything else that takes focus. A caller that
+//! knows where the text belongs can instead pass a `ForegroundWindow` (from
+//! `get_foreground_window`, captured before recording) to `write_text`, which
+//! then brings that window to the front itself via `focus_paste_target` and
+//! pastes once it is frontmost. No platform lets us deliver a paste keystroke
+//! to a background window, so re-focusing is the targeting mechanism.
+//!
+//! Frontmost tracking is per window on Windows (`GetForegroundWindow`) and per
+//! application on macOS (`NSWorkspace.frontmostApplication`). Linux has no
+//! portable query (Wayland does not expose one at all), so there the fixed
+//! delay is the only mechanism and an explicit target is ignored.
+
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+const FOCUS_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Paste timing, pushed by the frontend from its settings. The defaults
+/// reproduce the behavior from before this existed: no wait at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PasteFocusSettings {
+    /// Fixed pause before pasting when the focus check is off, unavailable,
+    /// or timed out.
+    pub delay_ms: u32,
+    /// Wait for the window that was frontmost when recording started.
+    pub wait_for_focus: bool,
+    /// Upper bound on the focus wait before falling back to `delay_ms`.
+    pub focus_timeout_ms: u32,
+    /// Pause between putting the transcript on the clipboard and sending the
+    /// paste keystroke, so the clipboard owner change has landed.
+    pub pre_paste_delay_ms: u32,
+    /// Pause between the paste keystroke and restoring the old clipboard.
+    /// On a slow machine the target app may read the clipboard late; too
+    /// short a pause pastes the previous clipboard instead of the transcript.
+    pub post_paste_delay_ms: u32,
+    /// Put the previous clipboard text back after pasting. Off leaves the
+    /// transcript on the clipboard and skips the restore race entirely.
+    pub restore_clipboard: bool,
+}
+
+impl Default for PasteFocusSettings {
+    fn default() -> Self {
+        Self {
+            delay_ms: 0,
+            wait_for_focus: false,
+            focus_timeout_ms: 1000,
+            pre_paste_delay_ms: 50,
+            post_paste_delay_ms: 100,
+            restore_clipboard: true,
+        }
+    }
+}
+
+/// Opaque handle to a frontmost window (Windows) or application (macOS): an
+/// `HWND` or a process id. Window handles are 32-bit significant even on
+/// 64-bit Windows, so both fit an `i32` and cross IPC as a plain number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(transparent)]
+// Only read where windows can be tracked and activated.
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+pub struct ForegroundWindow(i32);
+
+/// Managed state: current settings and the window to paste back into.
+#[derive(Default)]
+pub struct PasteFocus {
+    settings: Mutex<PasteFocusSettings>,
+    target: Mutex<Option<ForegroundWindow>>,
+}
+
+impl PasteFocus {
+    fn settings(&self) -> PasteFocusSettings {
+        self.settings.lock().map(|s| *s).unwrap_or_default()
+    }
+
+    fn target(&self) -> Option<ForegroundWindow> {
+        self.target.lock().ok().and_then(|t| *t)
+    }
+}
+
+/// The current paste settings, or the defaults before the state is managed.
+pub fn paste_settings(app: &AppHandle) -> PasteFocusSettings {
+    app.try_state::<PasteFocus>()
+        .map(|state| state.settings())
+        .unwrap_or_default()
+}
+
+/// Remember the current frontmost window as the paste target. Called when a
+/// recording starts; a no-op where tracking is unavailable.
+pub fn remember_paste_target(app: &AppHandle) {
+    let Some(state) = app.try_state::<PasteFocus>() else {
+        return;
+    };
+    let window = foreground_window();
+    debug!("Paste target remembered: {window:?}");
+    if let Ok(mut target) = state.target.lock() {
+        *target = window;
+    }
+}
+
+/// Block (asynchronously) until it is reasonable to send the paste keystroke.
+/// Never fails: a missing target or timeout degrades to the fixed delay.
+pub async fn wait_before_paste(app: &AppHandle) {
+    let Some(state) = app.try_state::<PasteFocus>() else {
+        return;
+    };
+    let settings = state.settings();
+
+    if settings.wait_for_focus {
+        match state.target() {
+            Some(target) => {
+                let timeout = Duration::from_millis(settings.focus_timeout_ms.into());
+                if wait_for_foreground(target, timeout).await {
+                    return;
+                }
+                warn!(
+                    "Paste target not frontmost after {}ms; falling back to fixed delay",
+                    settings.focus_timeout_ms
+                );
+            }
+            None => debug!("No paste target tracked; using fixed delay"),
+        }
+    }
+
+    if settings.delay_ms > 0 {
+        tokio::time::sleep(Duration::from_millis(settings.delay_ms.into())).await;
+    }
+}
+
+/// Bring `target` to the front, then wait (up to the configured focus
+/// timeout) until it is. If it never gets there the paste goes to whatever
+/// has focus, as it would without a target. Where the window cannot be
+/// activated (gone, or Linux) this falls back to `wait_before_paste`.
+pub async fn focus_paste_target(app: &AppHandle, target: ForegroundWindow) {
+    if !activate_window(target) {
+        debug!("Cannot activate {target:?}; waiting instead");
+        wait_before_paste(app).await;
+        return;
+    }
+    let settings = paste_settings(app);
+    let timeout = Duration::from_millis(settings.focus_timeout_ms.into());
+    if !wait_for_foreground(target, timeout).await {
+        warn!(
+            "Paste target {target:?} did not come to the front within {}ms",
+            settings.focus_timeout_ms
+        );
+    }
+}
+
+async fn wait_for_foreground(target: ForegroundWindow, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if foreground_window() == Some(target) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(FOCUS_POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn foreground_window() -> Option<ForegroundWindow> {
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+    // SAFETY: GetForegroundWindow takes no arguments and only reads state.
+    let hwnd = unsafe { GetForegroundWindow() };
+    (!hwnd.is_null()).then(|| ForegroundWindow(hwnd as isize as i32))
+}
+
+#[cfg(target_os = "macos")]
+fn foreground_window() -> Option<ForegroundWindow> {
+    use objc2_app_kit::NSWorkspace;
+
+    let app = NSWorkspace::sharedWorkspace().frontmostApplication()?;
+    Some(ForegroundWindow(app.processIdentifier()))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn foreground_window() -> Option<ForegroundWindow> {
+    None
+}
+
+/// Ask the OS to bring `target` to the front. Returns false when the window
+/// no longer exists or the platform cannot activate windows. Windows may
+/// still refuse the request (foreground lock), which the caller's
+/// frontmost check then catches.
+#[cfg(target_os = "windows")]
+fn activate_window(target: ForegroundWindow) -> bool {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        IsIconic, IsWindow, SetForegroundWindow, ShowWindow, SW_RESTORE,
+    };
+
+    // Handles are sign-extended from 32 bits (see `ForegroundWindow`).
+    let hwnd = target.0 as isize as windows_sys::Win32::Foundation::HWND;
+    // SAFETY: all four calls accept any handle value and fail gracefully on
+    // a stale one; `IsWindow` rules that case out first.
+    unsafe {
+        if IsWindow(hwnd) == 0 {
+            return false;
+        }
+        if IsIconic(hwnd) != 0 {
+            ShowWindow(hwnd, SW_RESTORE);
+        }
+        SetForegroundWindow(hwnd) != 0
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn activate_window(target: ForegroundWindow) -> bool {
+    use objc2_app_kit::{NSApplicationActivationOptions, NSRunningApplication};
+
+    let Some(app) = NSRunningApplication::runningApplicationWithProcessIdentifier(target.0) else {
+        return false;
+    };
+    // `activateWithOptions` is deprecated from macOS 14 in favour of
+    // cooperative activation, which needs the frontmost app to yield; the
+    // old call still works and is what earlier releases need.
+    #[allow(deprecated)]
+    app.activateWithOptions(NSApplicationActivationOptions::ActivateIgnoringOtherApps)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn activate_window(_target: ForegroundWindow) -> bool {
+    false
+}
+
+/// The window (Windows) or application (macOS) that is frontmost right now,
+/// to pass back to `write_text` as its `targetWindow` later. `None` where
+/// frontmost tracking is unavailable (Linux).
+#[tauri::command]
+#[specta::specta]
+pub fn get_foreground_window() -> Option<ForegroundWindow> {
+    foreground_window()
+}
+
+/// Update paste timing and clipboard restore. Takes effect on the next
+/// `write_text`.
+#[tauri::command]
+#[specta::specta]
+pub fn set_paste_focus_settings(settings: PasteFocusSettings, state: State<'_, PasteFocus>) {
+    info!("Paste focus settings: {settings:?}");
+    if let Ok(mut current) = state.settings.lock() {
+        *current = settings;
+    }
+}
@@ -4,34 +4,93 @@ use tauri::Manager;
 use tauri_plugin_aptabase::EventTracker;
 use tauri_plugin_log::{Target, TargetKind};
 
+pub mod analytics;
+use analytics::{analytics_enabled, get_analytics_enabled, set_analytics_enabled, AnalyticsState};
+
 pub mod audio;
-use audio::encode_recording_for_upload;
+use audio::{
+    analyze_clipping, audio_duration, contains_speech, detect_silence_regions,
+    encode_recording_for_upload, repair_wav_header, reprocess_recording, split_stereo_to_mono,
+};
 pub mod recorder;
 use recorder::commands::{
-    cancel_recording, clear_recording_artifacts, close_recording_session,
-    delete_recording_artifacts, enumerate_recording_devices, get_current_recording_id,
-    init_recording_session, start_recording, stop_recording,
+    cancel_recording, cleanup_recordings, clear_recording_artifacts, close_recording_session,
+    delete_recording_artifacts, enumerate_recording_devices, find_orphaned_recordings,
+    get_current_level, get_current_recording_id, get_level_ballistics, get_level_sensitivity,
+    get_recording_latency, get_stream_config, init_recording_session, list_recording_devices,
+    measure_snr, record_to_memory, set_idle_level_monitoring, set_level_ballistics,
+    set_level_sensitivity, start_recording, stop_memory_recording, stop_recording,
+    sync_recorder_state, MemoryCapture, SessionInitLock,
 };
 use recorder::recorder::Recorder;
 
 pub mod transcription;
 use transcription::{
-    get_transcription_state, set_transcription_config, transcribe_recording, ModelManager,
-    ModelStateEvent,
+    cancel_transcription, clean_transcript, convert_transcript, estimate_model_memory,
+    export_settings_profile, get_loaded_model_info, get_model_capabilities,
+    get_transcription_state, import_settings_profile, infer_engine_for_model, is_transcribing,
+    preview_corrections, reset_transcription_context, set_model_memory_guard,
+    set_transcription_config, set_whisper_defaults, transcribe_and_diff, transcribe_recording,
+    transcribe_recording_channel, transcribe_recording_range, transcribe_recording_with_segments,
+    transcribe_samples_detailed, transcribe_url, unload_transcription_model, ModelManager,
+    ModelStateEvent, TranscriptionLanguageMismatch, TranscriptionProgress,
 };
 
+pub mod build_info;
+use build_info::get_build_features;
+
+pub mod clipboard;
+use clipboard::{ClipboardRestore, ClipboardSnapshot};
+
 pub mod command;
 use command::open_accessibility_settings;
 
+pub mod focus;
+use focus::{get_foreground_window, set_paste_focus_settings, ForegroundWindow, PasteFocus};
+
 pub mod download;
 use download::{cancel_download, download_file, DownloadManager};
 
 pub mod markdown;
 use markdown::write_markdown_files;
 
+pub mod temp_files;
+use temp_files::{cleanup_temp_files, StagingDirs};
+
+pub mod timings;
+use timings::{get_last_pipeline_timings, PipelineTimer};
+
 pub mod media;
 use media::{pause_active_media, resume_media};
 
+pub mod topmost;
+use topmost::ensure_overlay_topmost;
+
+pub mod overlay_position;
+use overlay_position::{
+    get_overlay_position, reset_overlay_position, set_overlay_custom_position, OverlayPlacement,
+};
+
+pub mod overlay_appearance;
+use overlay_appearance::{
+    get_overlay_fade, get_overlay_opacity, set_overlay_fade_ms, set_overlay_opacity,
+    OverlayAppearance,
+};
+
+pub mod overlay_state;
+use overlay_state::{get_overlay_state, set_overlay_state, OverlayStatus};
+
+pub mod update;
+use update::prepare_for_update;
+
+pub mod diagnostics;
+use diagnostics::collect_diagnostics;
+
+// Wayland has no portable input injection; pasting there needs an external
+// tool, and the overlay cannot stay on top.
+#[cfg(target_os = "linux")]
+pub mod wayland;
+
 // Desktop global keyboard trigger backend (rdev listener + binding matcher).
 // Built in isolation in Wave 2; the FE registrar swap and listener start-up
 // land in Wave 3. Desktop-only because rdev is a desktop-only dependency.
@@ -51,22 +110,85 @@ fn make_specta_builder() -> tauri_specta::Builder<tauri::Wry> {
     tauri_specta::Builder::<tauri::Wry>::new()
         .commands(tauri_specta::collect_commands![
             write_text,
+            write_text_typed,
+            set_paste_focus_settings,
+            get_foreground_window,
             simulate_enter_keystroke,
             simulate_copy_keystroke,
             get_current_recording_id,
+            sync_recorder_state,
             enumerate_recording_devices,
+            list_recording_devices,
             init_recording_session,
+            get_stream_config,
+            get_recording_latency,
+            set_level_sensitivity,
+            get_level_sensitivity,
+            set_level_ballistics,
+            set_idle_level_monitoring,
+            get_current_level,
+            measure_snr,
+            get_level_ballistics,
             close_recording_session,
             start_recording,
             stop_recording,
+            record_to_memory,
+            stop_memory_recording,
             cancel_recording,
             delete_recording_artifacts,
             clear_recording_artifacts,
+            find_orphaned_recordings,
+            cleanup_recordings,
             transcribe_recording,
+            transcribe_url,
+            transcribe_recording_range,
+            transcribe_recording_with_segments,
+            transcribe_recording_channel,
+            transcribe_samples_detailed,
+            transcribe_and_diff,
+            detect_silence_regions,
+            contains_speech,
+            analyze_clipping,
+            audio_duration,
+            reprocess_recording,
+            repair_wav_header,
+            split_stereo_to_mono,
+            clean_transcript,
+            preview_corrections,
+            convert_transcript,
             open_accessibility_settings,
+            ensure_overlay_topmost,
+            get_overlay_position,
+            set_overlay_custom_position,
+            reset_overlay_position,
+            get_overlay_opacity,
+            set_overlay_opacity,
+            get_overlay_fade,
+            set_overlay_fade_ms,
+            get_overlay_state,
+            set_overlay_state,
+            set_analytics_enabled,
+            get_analytics_enabled,
+            prepare_for_update,
+            get_build_features,
+            collect_diagnostics,
             write_markdown_files,
+            cleanup_temp_files,
+            get_last_pipeline_timings,
             set_transcription_config,
+            reset_transcription_context,
+            cancel_transcription,
+            unload_transcription_model,
+            infer_engine_for_model,
+            get_model_capabilities,
+            set_model_memory_guard,
+            estimate_model_memory,
+            set_whisper_defaults,
             get_transcription_state,
+            is_transcribing,
+            get_loaded_model_info,
+            export_settings_profile,
+            import_settings_profile,
             download_file,
             cancel_download,
             pause_active_media,
@@ -83,6 +205,8 @@ fn make_specta_builder() -> tauri_specta::Builder<tauri::Wry> {
         // `mount_events` so `Event::emit` and the generated listeners resolve.
         .events(tauri_specta::collect_events![
             ModelStateEvent,
+            TranscriptionLanguageMismatch,
+            TranscriptionProgress,
             keyboard::ShortcutTriggerEvent,
             keyboard::ShortcutCaptureEvent,
         ])
@@ -140,7 +264,7 @@ pub async fn run() {
         {
             use std::fs::OpenOptions;
             use std::io::Write;
-            let crash_log_path = std::env::temp_dir().join("whispering-crash.log");
+            let crash_log_path = diagnostics::crash_log_path();
             if let Ok(mut file) = OpenOptions::new()
                 .create(true)
                 .append(true)
@@ -232,13 +356,35 @@ pub async fn run() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_opener::init())
         .manage(Mutex::new(Recorder::new()))
+        // Rejects a second init_recording_session while one is running.
+        .manage(SessionInitLock::default())
+        .manage(MemoryCapture::default())
         // Registry of in-flight model downloads; `cancel_download` aborts them.
         .manage(DownloadManager::default())
+        // Paste timing and the window to paste back into (see `focus.rs`).
+        .manage(PasteFocus::default())
+        // Per-stage timings of the last dictation (see `timings.rs`).
+        .manage(PipelineTimer::default())
+        // What the overlay shows, for a reloaded main window (see `overlay_state.rs`).
+        .manage(OverlayStatus::default())
         .setup(move |app| {
             // Register the tauri-specta event topics so `Event::emit` (Rust) and
             // the generated `events` listeners (FE) resolve the same names.
             specta_builder.mount_events(app);
 
+            // Runtime analytics opt-out, read before `RunEvent::Ready` so a
+            // disabled app never sends `app_started`.
+            app.manage(AnalyticsState::load(app.handle()));
+
+            // Pinned overlay spot, if the user dragged the overlay somewhere.
+            app.manage(OverlayPlacement::load(app.handle()));
+            // Overlay opacity and fade; the macOS panel below reads them.
+            app.manage(OverlayAppearance::load(app.handle()));
+
+            // Leftovers from a previous run that died mid-write.
+            app.manage(StagingDirs::load(app.handle()));
+            temp_files::cleanup_on_startup(app.handle());
+
             // ModelManager owns an `AppHandle` for emitting model lifecycle
             // events, so it cannot be constructed at builder-time (no app handle
             // exists yet). Move construction into setup; everything that needs it
@@ -298,8 +444,9 @@ pub async fn run() {
         .expect("error while building tauri application");
 
     app.run(|handler, event| {
-        // Only track events if Aptabase is enabled (key is not empty)
-        if !aptabase_key.is_empty() {
+        // Only track events if Aptabase is enabled (key is not empty) and the
+        // user has not opted out at runtime.
+        if !aptabase_key.is_empty() && analytics_enabled(handler) {
             match event {
                 tauri::RunEvent::Exit { .. } => {
                     let _ = handler.track_event("app_exited", None);
@@ -317,6 +464,23 @@ pub async fn run() {
 use enigo::{Direction, Enigo, Key, Keyboard, Settings};
 use tauri_plugin_clipboard_manager::ClipboardExt;
 
+/// Default pause between typed characters. Enough for most apps to keep up
+/// without making a long transcript crawl.
+const DEFAULT_KEYSTROKE_DELAY_MS: u32 = 5;
+/// Upper bound on the per-character pause; past this typing is unusable.
+const MAX_KEYSTROKE_DELAY_MS: u32 = 1000;
+
+/// How `write_text` inserts text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+enum InsertMode {
+    /// Clipboard sandwich plus a paste shortcut.
+    #[default]
+    Paste,
+    /// Type each character as a keystroke; never touches the clipboard.
+    Type,
+}
+
 /// Writes text at the cursor position using the clipboard sandwich technique
 ///
 /// This method preserves the user's existing clipboard content by:
@@ -327,21 +491,174 @@ use tauri_plugin_clipboard_manager::ClipboardExt;
 ///
 /// This approach is faster than typing character-by-character and preserves
 /// the user's clipboard, making it ideal for inserting transcribed text.
+///
+/// Text and images are restored. The result says whether that happened:
+/// `unreadable` means the clipboard held something that could not be read
+/// back, such as copied files, and now holds the transcript instead (see
+/// `clipboard.rs`).
+///
+/// Under Wayland, where no paste mechanism may be available, this fails with
+/// a `PasteUnsupportedOnWayland` error instead of silently doing nothing.
+///
+/// The pauses around the paste keystroke and whether step 4 runs at all come
+/// from `set_paste_focus_settings`.
+///
+/// Before any of that it waits for the paste target to regain focus, or for
+/// the configured fixed delay (see `focus::wait_before_paste`). With a
+/// `target_window` (from `get_foreground_window`) it instead brings that
+/// window to the front first, so the text lands there even if focus drifted
+/// while transcribing.
+///
+/// `mode: "type"` types the text instead (see `write_text_typed`), for apps
+/// that ignore a programmatic paste; `keystroke_delay_ms` only applies there.
 #[tauri::command]
 #[specta::specta]
-async fn write_text(app: tauri::AppHandle, text: String) -> Result<(), String> {
+async fn write_text(
+    app: tauri::AppHandle,
+    text: String,
+    target_window: Option<ForegroundWindow>,
+    mode: Option<InsertMode>,
+    keystroke_delay_ms: Option<u32>,
+) -> Result<ClipboardRestore, String> {
+    let started = std::time::Instant::now();
+    match target_window {
+        Some(target) => focus::focus_paste_target(&app, target).await,
+        None => focus::wait_before_paste(&app).await,
+    }
+
+    let restore = match mode.unwrap_or_default() {
+        InsertMode::Paste => paste_via_clipboard(&app, &text).await?,
+        InsertMode::Type => {
+            let delay_ms = keystroke_delay_ms.unwrap_or(DEFAULT_KEYSTROKE_DELAY_MS);
+            if delay_ms > MAX_KEYSTROKE_DELAY_MS {
+                return Err(format!(
+                    "Keystroke delay must be at most {MAX_KEYSTROKE_DELAY_MS}ms, got {delay_ms}ms"
+                ));
+            }
+            tauri::async_runtime::spawn_blocking(move || type_text(&text, delay_ms))
+                .await
+                .map_err(|e| format!("Typing task failed: {e}"))??;
+            ClipboardRestore::Skipped
+        }
+    };
+
+    timings::record_stage(&app, timings::PipelineStage::Paste, started.elapsed());
+    Ok(restore)
+}
+
+/// Types text at the cursor one keystroke per character, without touching
+/// the clipboard. Slower than `write_text`'s paste, but works in apps that
+/// ignore a programmatic Ctrl+V (some terminal emulators and Electron apps)
+/// and leaves the user's clipboard alone.
+///
+/// Any Unicode text is typed as-is, including characters with no key on the
+/// current layout. `keystroke_delay_ms` (default 5, at most 1000) pauses
+/// between characters for apps that drop input arriving too fast. Waits for
+/// focus exactly like `write_text`.
+#[tauri::command]
+#[specta::specta]
+async fn write_text_typed(
+    app: tauri::AppHandle,
+    text: String,
+    target_window: Option<ForegroundWindow>,
+    keystroke_delay_ms: Option<u32>,
+) -> Result<ClipboardRestore, String> {
+    write_text(
+        app,
+        text,
+        target_window,
+        Some(InsertMode::Type),
+        keystroke_delay_ms,
+    )
+    .await
+}
+
+/// The clipboard sandwich behind `InsertMode::Paste`.
+async fn paste_via_clipboard(
+    app: &tauri::AppHandle,
+    text: &str,
+) -> Result<ClipboardRestore, String> {
+    let settings = focus::paste_settings(app);
+
     // 1. Save current clipboard content
-    let original_clipboard = app.clipboard().read_text().ok();
+    let original_clipboard = settings
+        .restore_clipboard
+        .then(|| ClipboardSnapshot::take(app));
 
     // 2. Write new text to clipboard
     app.clipboard()
-        .write_text(&text)
+        .write_text(text)
         .map_err(|e| format!("Failed to write to clipboard: {}", e))?;
 
     // Small delay to ensure clipboard is updated
-    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(
+        settings.pre_paste_delay_ms.into(),
+    ))
+    .await;
+
+    // 3. Simulate paste operation. On failure the new text is left on the
+    // clipboard so the user can still paste it by hand.
+    send_paste_keystroke()?;
+
+    // Small delay to ensure paste completes
+    tokio::time::sleep(tokio::time::Duration::from_millis(
+        settings.post_paste_delay_ms.into(),
+    ))
+    .await;
+
+    // 4. Restore original clipboard content (skipped when turned off)
+    match original_clipboard {
+        Some(snapshot) => snapshot.restore(app),
+        None => Ok(ClipboardRestore::Skipped),
+    }
+}
+
+/// Type `text` as keystrokes, pausing `delay_ms` between characters.
+/// Blocking. Under Wayland this goes through an external tool, as the paste
+/// does (see `wayland.rs`).
+fn type_text(text: &str, delay_ms: u32) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    if wayland::is_wayland_session() {
+        return wayland::type_text(text, delay_ms);
+    }
+
+    let mut enigo = Enigo::new(&Settings::default()).map_err(|e| e.to_string())?;
+    if delay_ms == 0 {
+        return enigo
+            .text(text)
+            .map_err(|e| format!("Failed to type text: {}", e));
+    }
+
+    let delay = std::time::Duration::from_millis(u64::from(delay_ms));
+    // "\r\n" is one line break, not two.
+    let normalized = text.replace("\r\n", "\n");
+    let mut buf = [0u8; 4];
+    for (i, c) in normalized.chars().enumerate() {
+        if i > 0 {
+            std::thread::sleep(delay);
+        }
+        // Enter and Tab as keys, so apps that treat them specially (submit,
+        // indent) see the real key; everything else, including characters
+        // outside the layout, as Unicode input.
+        let result = match c {
+            '\n' | '\r' => enigo.key(Key::Return, Direction::Click),
+            '\t' => enigo.key(Key::Tab, Direction::Click),
+            _ => enigo.text(c.encode_utf8(&mut buf)),
+        };
+        result.map_err(|e| format!("Failed to type {c:?}: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Press and release the platform paste shortcut (Cmd+V on macOS, Ctrl+V
+/// elsewhere). Under Wayland enigo's X11 injection does not reach native
+/// windows, so this goes through an external tool instead (see `wayland.rs`).
+fn send_paste_keystroke() -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    if wayland::is_wayland_session() {
+        return wayland::send_paste_keystroke();
+    }
 
-    // 3. Simulate paste operation using virtual key codes (layout-independent)
     let mut enigo = Enigo::new(&Settings::default()).map_err(|e| e.to_string())?;
 
     // Use virtual key codes for V to work with any keyboard layout
@@ -368,16 +685,6 @@ async fn write_text(app: tauri::AppHandle, text: String) -> Result<(), String> {
         .key(modifier, Direction::Release)
         .map_err(|e| format!("Failed to release modifier key: {}", e))?;
 
-    // Small delay to ensure paste completes
-    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-
-    // 4. Restore original clipboard content
-    if let Some(content) = original_clipboard {
-        app.clipboard()
-            .write_text(&content)
-            .map_err(|e| format!("Failed to restore clipboard: {}", e))?;
-    }
-
     Ok(())
 }
 
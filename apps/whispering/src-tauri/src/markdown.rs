@@ -1,8 +1,10 @@
 use std::collections::HashSet;
 use std::fs;
-use std::io::Write;
 use std::path::{Component, Path, PathBuf};
-use tempfile::NamedTempFile;
+
+use tauri::{AppHandle, Manager};
+
+use crate::temp_files::{atomic_write, StagingDirs};
 
 // ── Types ──────────────────────────────────────────────────────────────────
 
@@ -42,6 +44,7 @@ fn validate_leaf_filename(filename: &str) -> Result<&str, String> {
 #[tauri::command]
 #[specta::specta]
 pub async fn write_markdown_files(
+    app: AppHandle,
     directory: String,
     files: Vec<MarkdownFile>,
 ) -> Result<(), String> {
@@ -69,16 +72,11 @@ pub async fn write_markdown_files(
 
         fs::create_dir_all(&dir_path)
             .map_err(|e| format!("Failed to create directory {}: {}", directory, e))?;
+        app.state::<StagingDirs>().remember(&dir_path);
 
         for (file, filename) in files.iter().zip(validated.iter()) {
-            let path = dir_path.join(filename);
-            let mut temp = NamedTempFile::new_in(&dir_path)
-                .map_err(|e| format!("Failed to create temp file for {}: {}", filename, e))?;
-
-            temp.write_all(file.content.as_bytes())
+            atomic_write(&dir_path.join(filename), file.content.as_bytes())
                 .map_err(|e| format!("Failed to write {}: {}", filename, e))?;
-            temp.persist(&path)
-                .map_err(|e| format!("Failed to persist {}: {}", filename, e.error))?;
         }
 
         Ok(())
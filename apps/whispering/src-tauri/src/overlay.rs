@@ -14,10 +14,12 @@
 //! window via `getByLabel` before creating one).
 
 // `Manager` is needed in scope because the `tauri_panel!` macro expands to code
-// that calls `.app_handle()` on the window.
+// that calls `.app_handle()` on the window (and for `get_webview_window`).
 use tauri::{AppHandle, Manager, WebviewUrl};
 use tauri_nspanel::{tauri_panel, CollectionBehavior, PanelBuilder, PanelLevel, StyleMask};
 
+use crate::overlay_appearance::{OverlayAppearance, OverlayFade};
+
 // Must stay in sync with the JS window manager's `WINDOW_LABEL` and the pill's
 // size in `src/lib/recording-overlay/`.
 const WINDOW_LABEL: &str = "recording-overlay";
@@ -35,9 +37,28 @@ tauri_panel! {
 
 /// Create the recording overlay panel, hidden. The frontend repositions and
 /// shows it once recording starts, so the initial position here is unused.
+/// The persisted background opacity and fade durations are passed in the
+/// page URL, so the pill is drawn with them from the first frame.
+///
+/// A no-op when the overlay already exists, so it is safe to call again on
+/// demand (see `topmost::wait_for_overlay`). Must run on the main thread.
 pub fn create_recording_overlay(app: &AppHandle) {
+    if app.get_webview_window(WINDOW_LABEL).is_some() {
+        return;
+    }
+    let (opacity, fade) = app
+        .try_state::<OverlayAppearance>()
+        .map_or((1.0, OverlayFade::default()), |appearance| {
+            (appearance.opacity(), appearance.fade())
+        });
     let result = PanelBuilder::<_, RecordingOverlayPanel>::new(app, WINDOW_LABEL)
-        .url(WebviewUrl::App("recording-overlay".into()))
+        .url(WebviewUrl::App(
+            format!(
+                "recording-overlay?opacity={opacity}&fadeIn={}&fadeOut={}",
+                fade.fade_in_ms, fade.fade_out_ms
+            )
+            .into(),
+        ))
         .title("Recording")
         .position(tauri::Position::Logical(tauri::LogicalPosition {
             x: 0.0,
@@ -62,7 +83,11 @@ pub fn create_recording_overlay(app: &AppHandle) {
         .corner_radius(OVERLAY_HEIGHT / 2.0)
         // accept_first_mouse so a click lands on the stop/cancel button even
         // when the panel is not the active window (it never activates).
-        .with_window(|w| w.decorations(false).transparent(true).accept_first_mouse(true))
+        .with_window(|w| {
+            w.decorations(false)
+                .transparent(true)
+                .accept_first_mouse(true)
+        })
         .collection_behavior(
             CollectionBehavior::new()
                 .can_join_all_spaces()
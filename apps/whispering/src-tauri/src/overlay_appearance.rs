@@ -0,0 +1,239 @@
+//! How the recording overlay looks, as opposed to where it goes
+//! (`overlay_position`).
+//!
+//! The overlay window is transparent; its pill is drawn by CSS, so opacity
+//! is the pill background's alpha rather than a window property (Tauri has
+//! no cross-platform window opacity). Rust owns the value so it is known
+//! before any webview loads: the macOS panel receives it in its URL at
+//! creation, the frontend-created window on other platforms reads it with
+//! `get_overlay_opacity`, and a change is pushed to an open overlay on
+//! `overlay-opacity`.
+//!
+//! How long the pill fades in when shown and out when hidden is kept the
+//! same way (`get_overlay_fade`, `set_overlay_fade_ms`, `overlay-fade`), so
+//! the animation length is one value the overlay and whoever hides its
+//! window both read, rather than a CSS duration and a delay kept in step by
+//! hand.
+//!
+//! The values live in `<appConfigDir>/overlay_appearance.json`, like the
+//! pinned overlay spot. A missing or unreadable file means fully opaque with
+//! no fade, which is how the overlay looked before the settings existed; a
+//! file written before a setting was added keeps its other values.
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// Must stay in sync with the JS window manager's `WINDOW_LABEL`.
+const WINDOW_LABEL: &str = "recording-overlay";
+const SETTINGS_FILE: &str = "overlay_appearance.json";
+
+/// Event sent to the overlay window with the new opacity (a number).
+const OVERLAY_OPACITY_EVENT: &str = "overlay-opacity";
+/// Event sent to the overlay window with the new `OverlayFade`.
+const OVERLAY_FADE_EVENT: &str = "overlay-fade";
+
+const DEFAULT_OPACITY: f64 = 1.0;
+/// Longest fade accepted. Past this the pill lingers after recording stops
+/// and reads as stuck rather than fading.
+const MAX_FADE_MS: u32 = 2_000;
+
+/// Fade durations for the overlay pill, in milliseconds. Zero means no fade.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayFade {
+    pub fade_in_ms: u32,
+    /// Also how long to wait after starting the fade before hiding the
+    /// window, so the animation is not cut off.
+    pub fade_out_ms: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+struct AppearanceSettings {
+    opacity: f64,
+    fade: OverlayFade,
+}
+
+impl Default for AppearanceSettings {
+    fn default() -> Self {
+        Self {
+            opacity: DEFAULT_OPACITY,
+            fade: OverlayFade::default(),
+        }
+    }
+}
+
+/// Managed app state holding the overlay's current appearance.
+pub struct OverlayAppearance {
+    settings: Mutex<AppearanceSettings>,
+}
+
+impl OverlayAppearance {
+    /// Read the persisted appearance, defaulting to opaque with no fade.
+    pub fn load(app: &AppHandle) -> Self {
+        let settings = settings_path(app)
+            .and_then(|path| std::fs::read_to_string(path).map_err(|e| e.to_string()))
+            .and_then(|json| {
+                serde_json::from_str::<AppearanceSettings>(&json).map_err(|e| e.to_string())
+            })
+            .map(|settings| AppearanceSettings {
+                opacity: clamp_opacity(settings.opacity),
+                fade: OverlayFade {
+                    fade_in_ms: settings.fade.fade_in_ms.min(MAX_FADE_MS),
+                    fade_out_ms: settings.fade.fade_out_ms.min(MAX_FADE_MS),
+                },
+            })
+            .unwrap_or_default();
+        Self {
+            settings: Mutex::new(settings),
+        }
+    }
+
+    pub fn opacity(&self) -> f64 {
+        self.settings().opacity
+    }
+
+    pub fn fade(&self) -> OverlayFade {
+        self.settings().fade
+    }
+
+    fn settings(&self) -> AppearanceSettings {
+        *self.settings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Apply `change` and persist the result. The in-memory value is only
+    /// updated once the file is written, so the two never disagree.
+    fn update(
+        &self,
+        app: &AppHandle,
+        change: impl FnOnce(&mut AppearanceSettings),
+    ) -> Result<(), String> {
+        let mut settings = self.settings.lock().unwrap_or_else(|e| e.into_inner());
+        let mut updated = *settings;
+        change(&mut updated);
+
+        let path = settings_path(app)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+        }
+        let json = serde_json::to_string(&updated)
+            .map_err(|e| format!("serialize overlay appearance: {e}"))?;
+        std::fs::write(&path, json).map_err(|e| format!("write {}: {e}", path.display()))?;
+        *settings = updated;
+        Ok(())
+    }
+}
+
+/// `value` clamped to `[0, 1]`. A NaN in a hand-edited file reads as opaque.
+fn clamp_opacity(value: f64) -> f64 {
+    if value.is_nan() {
+        return DEFAULT_OPACITY;
+    }
+    value.clamp(0.0, 1.0)
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .map_err(|e| format!("resolve app config directory: {e}"))
+}
+
+/// Opacity of the overlay's background, 0 (invisible) to 1 (opaque), for
+/// the overlay to apply when it loads.
+#[tauri::command]
+#[specta::specta]
+pub fn get_overlay_opacity(appearance: State<'_, OverlayAppearance>) -> f64 {
+    appearance.opacity()
+}
+
+/// Set the overlay background's opacity, clamped to `[0, 1]`, and persist
+/// it across restarts. An open overlay is updated at once through
+/// `overlay-opacity`. Returns the value applied after clamping.
+#[tauri::command]
+#[specta::specta]
+pub fn set_overlay_opacity(
+    value: f64,
+    app: AppHandle,
+    appearance: State<'_, OverlayAppearance>,
+) -> Result<f64, String> {
+    if value.is_nan() {
+        return Err("Overlay opacity must be a number, got NaN".to_string());
+    }
+    let opacity = clamp_opacity(value);
+
+    appearance.update(&app, |settings| settings.opacity = opacity)?;
+    info!("Overlay opacity set to {opacity}");
+
+    // Not open yet is fine: the overlay reads the value when it loads.
+    let _ = app.emit_to(WINDOW_LABEL, OVERLAY_OPACITY_EVENT, opacity);
+    Ok(opacity)
+}
+
+/// How long the overlay fades in when shown and out when hidden.
+#[tauri::command]
+#[specta::specta]
+pub fn get_overlay_fade(appearance: State<'_, OverlayAppearance>) -> OverlayFade {
+    appearance.fade()
+}
+
+/// Set the overlay's fade durations in milliseconds, 0 to disable either,
+/// and persist them across restarts. Each is at most 2000 ms. An open
+/// overlay is updated at once through `overlay-fade`.
+#[tauri::command]
+#[specta::specta]
+pub fn set_overlay_fade_ms(
+    fade_in: u32,
+    fade_out: u32,
+    app: AppHandle,
+    appearance: State<'_, OverlayAppearance>,
+) -> Result<OverlayFade, String> {
+    let fade = OverlayFade {
+        fade_in_ms: check_fade("fadeIn", fade_in)?,
+        fade_out_ms: check_fade("fadeOut", fade_out)?,
+    };
+
+    appearance.update(&app, |settings| settings.fade = fade)?;
+    info!("Overlay fade set to {fade_in} ms in, {fade_out} ms out");
+
+    let _ = app.emit_to(WINDOW_LABEL, OVERLAY_FADE_EVENT, fade);
+    Ok(fade)
+}
+
+fn check_fade(field: &str, ms: u32) -> Result<u32, String> {
+    if ms > MAX_FADE_MS {
+        return Err(format!(
+            "{field}: fade must be at most {MAX_FADE_MS} ms, got {ms}"
+        ));
+    }
+    Ok(ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opacity_is_clamped_into_range() {
+        assert_eq!(clamp_opacity(0.4), 0.4);
+        assert_eq!(clamp_opacity(-0.5), 0.0);
+        assert_eq!(clamp_opacity(7.0), 1.0);
+        assert_eq!(clamp_opacity(f64::INFINITY), 1.0);
+        assert_eq!(clamp_opacity(f64::NAN), DEFAULT_OPACITY);
+    }
+
+    #[test]
+    fn settings_written_before_fade_existed_still_load() {
+        let settings: AppearanceSettings = serde_json::from_str(r#"{"opacity":0.5}"#).unwrap();
+        assert_eq!(settings.opacity, 0.5);
+        assert_eq!(settings.fade, OverlayFade::default());
+
+        assert_eq!(check_fade("fadeOut", 2_000), Ok(2_000));
+        assert!(check_fade("fadeOut", 2_001)
+            .unwrap_err()
+            .starts_with("fadeOut:"));
+    }
+}
@@ -0,0 +1,350 @@
+//! Where the recording overlay goes: bottom-center of the work area of the
+//! monitor the cursor is on.
+//!
+//! The frontend used to place it on the monitor holding the main window,
+//! which on a multi-monitor setup is usually not the screen the user is
+//! dictating on (the main window is often minimized or parked elsewhere).
+//! The cursor is a much better proxy for "where I'm looking". When the
+//! cursor position or its monitor cannot be determined (Wayland does not
+//! expose a global cursor position), this falls back to the primary
+//! monitor, so single-monitor setups behave exactly as before.
+//!
+//! The work area excludes the taskbar and dock, so the overlay never sits
+//! under them.
+//!
+//! Positions are physical (desktop) pixels. Each monitor divides its own
+//! coordinates by its own scale factor, so on a mixed-DPI setup logical
+//! coordinates of different monitors overlap and cannot say which screen a
+//! point is on. Only the overlay's size and margin are logical, scaled by the
+//! monitor it lands on.
+//!
+//! A user who drags the overlay somewhere else can pin it there with
+//! `set_overlay_custom_position`. The spot is kept in
+//! `<appConfigDir>/overlay_position.json`, like the analytics flag, so it
+//! survives a restart. It is clamped into the work area of the monitor it is
+//! on every time it is read, and a spot on a monitor that is no longer
+//! connected is clamped onto the cursor's monitor instead, so the overlay can
+//! never end up off-screen.
+
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, State};
+
+// Must stay in sync with the JS window manager's `WINDOW_LABEL`.
+const WINDOW_LABEL: &str = "recording-overlay";
+const SETTINGS_FILE: &str = "overlay_position.json";
+
+// Must stay in sync with the overlay's size and margin in
+// `src/lib/recording-overlay/`. Logical pixels.
+const OVERLAY_WIDTH: f64 = 184.0;
+const OVERLAY_HEIGHT: f64 = 40.0;
+/// Distance from the bottom of the work area, in logical pixels.
+const OVERLAY_BOTTOM_MARGIN: f64 = 72.0;
+
+/// Top-left corner for the overlay, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct OverlayPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// A monitor's work area in physical pixels, plus its scale factor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct WorkArea {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    scale_factor: f64,
+}
+
+impl WorkArea {
+    fn contains(&self, position: OverlayPosition) -> bool {
+        contains(
+            (self.x, self.y, self.width, self.height),
+            f64::from(position.x),
+            f64::from(position.y),
+        )
+    }
+}
+
+impl From<&Monitor> for WorkArea {
+    fn from(monitor: &Monitor) -> Self {
+        let area = monitor.work_area();
+        Self {
+            x: area.position.x,
+            y: area.position.y,
+            width: area.size.width,
+            height: area.size.height,
+            scale_factor: monitor.scale_factor(),
+        }
+    }
+}
+
+/// Whether the physical point lies on `monitor` (its full bounds, not just
+/// the work area, so a cursor over the taskbar still counts).
+fn is_mouse_within_monitor(point: PhysicalPosition<f64>, monitor: &Monitor) -> bool {
+    let position = monitor.position();
+    let size = monitor.size();
+    contains(
+        (position.x, position.y, size.width, size.height),
+        point.x,
+        point.y,
+    )
+}
+
+/// Half-open containment, so a point on the seam between two side-by-side
+/// monitors belongs to exactly one of them.
+fn contains((x, y, width, height): (i32, i32, u32, u32), px: f64, py: f64) -> bool {
+    let (left, top) = (f64::from(x), f64::from(y));
+    px >= left && px < left + f64::from(width) && py >= top && py < top + f64::from(height)
+}
+
+/// The `custom` spot clamped so the whole overlay fits in `area`, or
+/// without one, the overlay centered horizontally at the bottom of `area`.
+/// The overlay's logical size and margin are scaled to the monitor's pixels.
+fn calculate_overlay_position(area: WorkArea, custom: Option<OverlayPosition>) -> OverlayPosition {
+    let scale = area.scale_factor;
+    let (x, y) = (f64::from(area.x), f64::from(area.y));
+    let (width, height) = (f64::from(area.width), f64::from(area.height));
+    let (overlay_width, overlay_height) = (OVERLAY_WIDTH * scale, OVERLAY_HEIGHT * scale);
+    let (left, top) = match custom {
+        // `max` after `min` keeps the overlay's top-left corner on screen
+        // even on a work area smaller than the overlay.
+        Some(custom) => (
+            f64::from(custom.x).min(x + width - overlay_width).max(x),
+            f64::from(custom.y).min(y + height - overlay_height).max(y),
+        ),
+        None => (
+            x + (width - overlay_width) / 2.0,
+            y + height - overlay_height - OVERLAY_BOTTOM_MARGIN * scale,
+        ),
+    };
+    OverlayPosition {
+        x: left.round() as i32,
+        y: top.round() as i32,
+    }
+}
+
+/// The monitor under the cursor, else the primary monitor.
+fn get_monitor_with_cursor(app: &AppHandle) -> Option<Monitor> {
+    let under_cursor = app.cursor_position().ok().and_then(|cursor| {
+        app.available_monitors()
+            .ok()?
+            .into_iter()
+            .find(|monitor| is_mouse_within_monitor(cursor, monitor))
+    });
+    if under_cursor.is_none() {
+        debug!("Cursor monitor unknown, placing the overlay on the primary monitor");
+    }
+    under_cursor.or_else(|| app.primary_monitor().ok().flatten())
+}
+
+/// Where to show the overlay, honoring a custom spot if one is set.
+fn resolve_position(app: &AppHandle, custom: Option<OverlayPosition>) -> Option<OverlayPosition> {
+    let on_its_monitor = custom.and_then(|custom| {
+        app.available_monitors()
+            .ok()?
+            .iter()
+            .map(WorkArea::from)
+            .find(|area| area.contains(custom))
+    });
+    let area = on_its_monitor.or_else(|| get_monitor_with_cursor(app).map(|m| (&m).into()))?;
+    Some(calculate_overlay_position(area, custom))
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OverlaySettings {
+    custom: Option<OverlayPosition>,
+}
+
+/// Managed app state holding the pinned overlay spot, if any.
+pub struct OverlayPlacement {
+    custom: Mutex<Option<OverlayPosition>>,
+}
+
+impl OverlayPlacement {
+    /// Read the persisted spot. A missing or unreadable file means automatic
+    /// placement.
+    pub fn load(app: &AppHandle) -> Self {
+        let custom = settings_path(app)
+            .and_then(|path| std::fs::read_to_string(path).map_err(|e| e.to_string()))
+            .and_then(|json| {
+                serde_json::from_str::<OverlaySettings>(&json).map_err(|e| e.to_string())
+            })
+            .map(|settings| settings.custom)
+            .unwrap_or_default();
+        Self {
+            custom: Mutex::new(custom),
+        }
+    }
+
+    fn custom(&self) -> Option<OverlayPosition> {
+        *self.custom.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .map_err(|e| format!("resolve app config directory: {e}"))
+}
+
+fn save_custom(
+    app: &AppHandle,
+    placement: &OverlayPlacement,
+    custom: Option<OverlayPosition>,
+) -> Result<(), String> {
+    let path = settings_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+    }
+    let json = serde_json::to_string(&OverlaySettings { custom })
+        .map_err(|e| format!("serialize overlay settings: {e}"))?;
+    std::fs::write(&path, json).map_err(|e| format!("write {}: {e}", path.display()))?;
+    *placement.custom.lock().unwrap_or_else(|e| e.into_inner()) = custom;
+    Ok(())
+}
+
+/// Where to put the recording overlay before showing it: the pinned spot
+/// from `set_overlay_custom_position`, clamped onto the screen, or else
+/// bottom-center of the work area of the monitor under the cursor (or the
+/// primary monitor). Also what the frontend reads on startup to restore a
+/// pinned overlay. `null` when no monitor is known at all.
+#[tauri::command]
+#[specta::specta]
+pub fn get_overlay_position(
+    app: AppHandle,
+    placement: State<'_, OverlayPlacement>,
+) -> Option<OverlayPosition> {
+    resolve_position(&app, placement.custom())
+}
+
+/// Pin the overlay's top-left corner at `(x, y)` physical pixels, e.g. the
+/// window's `outerPosition()` where the user dropped it after a drag, and
+/// move it there now if it exists. Persisted across restarts.
+#[tauri::command]
+#[specta::specta]
+pub fn set_overlay_custom_position(
+    x: i32,
+    y: i32,
+    app: AppHandle,
+    placement: State<'_, OverlayPlacement>,
+) -> Result<(), String> {
+    let custom = OverlayPosition { x, y };
+    save_custom(&app, &placement, Some(custom))?;
+    info!("Overlay pinned at ({x}, {y})");
+
+    if let (Some(window), Some(position)) = (
+        app.get_webview_window(WINDOW_LABEL),
+        resolve_position(&app, Some(custom)),
+    ) {
+        window
+            .set_position(PhysicalPosition::new(position.x, position.y))
+            .map_err(|e| format!("Failed to move overlay: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Forget the pinned spot and go back to automatic placement.
+#[tauri::command]
+#[specta::specta]
+pub fn reset_overlay_position(
+    app: AppHandle,
+    placement: State<'_, OverlayPlacement>,
+) -> Result<(), String> {
+    save_custom(&app, &placement, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seam_between_monitors_belongs_to_the_right_hand_one() {
+        let left = (0, 0, 1920, 1080);
+        let right = (1920, 0, 2560, 1440);
+
+        assert!(contains(left, 1919.5, 500.0));
+        assert!(!contains(left, 1920.0, 500.0));
+        assert!(contains(right, 1920.0, 500.0));
+        assert!(!contains(right, 1920.0, -1.0));
+    }
+
+    #[test]
+    fn overlay_is_centered_at_the_bottom_of_a_scaled_work_area() {
+        // A 2x monitor to the right of a 1920-wide one, with a 50 px
+        // (physical) taskbar at the bottom.
+        let area = WorkArea {
+            x: 1920,
+            y: 0,
+            width: 2560,
+            height: 1390,
+            scale_factor: 2.0,
+        };
+
+        let position = calculate_overlay_position(area, None);
+
+        // Physical coordinates, with the logical size and margin doubled:
+        // the overlay stays on the right-hand monitor.
+        assert_eq!(
+            position,
+            OverlayPosition {
+                x: 1920 + (2560 - 2 * 184) / 2,
+                y: 1390 - 2 * 40 - 2 * 72,
+            }
+        );
+        assert!(area.contains(position));
+    }
+
+    #[test]
+    fn mixed_dpi_monitors_do_not_overlap() {
+        // A 1x 1920-wide monitor and a 2x monitor to its right. Divided by
+        // their own scales, the second would start at x = 960, inside the
+        // first; in physical pixels each point is on exactly one of them.
+        let left = WorkArea {
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1040,
+            scale_factor: 1.0,
+        };
+        let right = WorkArea {
+            x: 1920,
+            y: 0,
+            width: 2560,
+            height: 1390,
+            scale_factor: 2.0,
+        };
+        let on_right = OverlayPosition { x: 2000, y: 100 };
+
+        assert!(!left.contains(on_right));
+        assert!(right.contains(on_right));
+        assert_eq!(calculate_overlay_position(right, Some(on_right)), on_right);
+    }
+
+    #[test]
+    fn custom_position_is_clamped_into_the_work_area() {
+        let area = WorkArea {
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1040,
+            scale_factor: 1.0,
+        };
+        let inside = OverlayPosition { x: 40, y: 30 };
+
+        assert_eq!(calculate_overlay_position(area, Some(inside)), inside);
+        // Left behind on a monitor that was unplugged, to the right.
+        assert_eq!(
+            calculate_overlay_position(area, Some(OverlayPosition { x: 2500, y: -80 })),
+            OverlayPosition {
+                x: 1920 - 184,
+                y: 0,
+            }
+        );
+    }
+}
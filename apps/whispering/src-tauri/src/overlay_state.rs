@@ -0,0 +1,89 @@
+//! What the recording overlay is showing, kept in Rust so it outlives a
+//! webview reload.
+//!
+//! The main window decides what the overlay displays and pushes it over
+//! `recording-overlay:status`; Rust never sees that traffic. So when the
+//! main window reloads (a dev hot-reload, or recovery from a crashed
+//! webview) its `latestStatus` is gone and the overlay flashes back to a
+//! default until the recorder next changes state. The main window records
+//! each status here with `set_overlay_state` and reads it back with
+//! `get_overlay_state` on startup. Where the overlay sits is already owned
+//! by Rust (`overlay_position`), so only the displayed status is kept.
+//!
+//! Held in memory only: after a restart no recording is running, so the
+//! right state is hidden.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::State;
+
+/// What the overlay displays. Mirrors `RecordingOverlayStatus` in
+/// `src/lib/recording-overlay/events.ts`, so the wire shape is the same
+/// `{ mode, state }` object the main window already emits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum OverlayState {
+    Manual { state: ManualOverlayState },
+    Vad { state: VadOverlayState },
+}
+
+/// A manual recording only shows the overlay while it records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ManualOverlayState {
+    Recording,
+}
+
+/// The non-idle voice-activity states; an idle VAD hides the overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VadOverlayState {
+    Listening,
+    SpeechDetected,
+}
+
+/// Managed app state: the last status the overlay was given, `None` while
+/// it is hidden.
+#[derive(Default)]
+pub struct OverlayStatus(Mutex<Option<OverlayState>>);
+
+/// The status the overlay was last given, or `None` when it is hidden. A
+/// reloaded main window restores its overlay from this.
+#[tauri::command]
+#[specta::specta]
+pub fn get_overlay_state(status: State<'_, OverlayStatus>) -> Option<OverlayState> {
+    *status.0.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Record what the overlay now displays, or `None` when it was hidden. Call
+/// alongside each status sent to the overlay so `get_overlay_state` stays
+/// current.
+#[tauri::command]
+#[specta::specta]
+pub fn set_overlay_state(state: Option<OverlayState>, status: State<'_, OverlayStatus>) {
+    *status.0.lock().unwrap_or_else(|e| e.into_inner()) = state;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_has_the_frontend_status_shape() {
+        let vad = OverlayState::Vad {
+            state: VadOverlayState::SpeechDetected,
+        };
+        let json = serde_json::to_string(&vad).unwrap();
+        assert_eq!(json, r#"{"mode":"vad","state":"SPEECH_DETECTED"}"#);
+
+        let manual: OverlayState =
+            serde_json::from_str(r#"{"mode":"manual","state":"RECORDING"}"#).unwrap();
+        assert_eq!(
+            manual,
+            OverlayState::Manual {
+                state: ManualOverlayState::Recording
+            }
+        );
+        assert!(serde_json::from_str::<OverlayState>(r#"{"mode":"vad","state":"IDLE"}"#).is_err());
+    }
+}
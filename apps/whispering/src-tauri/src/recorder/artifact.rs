@@ -1,7 +1,10 @@
-//! Durable recording artifact: a 16 kHz mono IEEE-float WAV file written to
-//! `<appDataDir>/recordings/{id}.wav`. This path matches the JS file-system
-//! blob store (`PATHS.DB.RECORDING_AUDIO`) so history playback, deletion, and
-//! the `convertFileSrc` URL flow work without an extra copy.
+//! Durable recording artifact: an IEEE-float WAV file (16 kHz mono, or the
+//! device's own rate and channels for a `use_native_format` session) written
+//! to `<appDataDir>/recordings/{id}.wav`, or `{id}.{label}.wav` with a
+//! `filename_template` (see `naming.rs`). This path matches the JS file-system
+//! blob store (`PATHS.DB.RECORDING_AUDIO`), which finds files by the `{id}.`
+//! prefix, so history playback, deletion, and the `convertFileSrc` URL flow
+//! work without an extra copy.
 //!
 //! JS never sees raw PCM samples on the wire. The IPC shape is the small
 //! `RecordingArtifact` JSON handle returned by `stop_recording`; later
@@ -23,12 +26,12 @@ use std::path::{Path, PathBuf};
 use serde::Serialize;
 use tauri::{AppHandle, Manager};
 
-use crate::audio::decode_to_pcm16k_mono;
+use crate::audio::{decode_channel_to_pcm16k, decode_to_pcm16k_mono_with, Downmix};
 
-/// Target rate for every cpal-written artifact. Matches the recorder's
-/// finalize contract and the rate all local transcription engines want.
+/// Rate of cpal-written artifacts unless the session asked for the device's
+/// native format. Matches the recorder's finalize contract and the rate all
+/// local transcription engines want.
 const ARTIFACT_RATE: u32 = 16_000;
-const ARTIFACT_CHANNELS: u16 = 1;
 
 /// File extension Rust writes for cpal artifacts. Other producers
 /// (navigator MediaRecorder via the JS blob store) may write other
@@ -40,6 +43,9 @@ const ARTIFACT_MIME: &str = "audio/wav";
 /// the JS side so the blob store keeps finding files by id prefix.
 const RECORDINGS_DIR_NAME: &str = "recordings";
 
+/// File `check_recordings_dir_writable` creates and immediately removes.
+const WRITE_PROBE_NAME: &str = ".write-probe";
+
 /// Serializable handle returned to the JS side. The id is the lookup key
 /// for every later operation; the rest is metadata the UI needs without
 /// having to read the file (duration for analytics, byteLength for artifact
@@ -63,6 +69,67 @@ pub struct RecordingArtifact {
     #[specta(type = specta_typescript::Number<u64>)]
     pub byte_length: u64,
     pub mime_type: String,
+    /// Where the file was written, for "show in folder". Later operations
+    /// still take the id.
+    pub path: String,
+}
+
+/// Magnitude from which a sample counts as clipped. Matches the offline
+/// `analyze_clipping` threshold.
+const CLIP_THRESHOLD: f32 = 0.99;
+
+/// One-shot quality summary of a finished recording, emitted on
+/// `recording-complete`. Levels are measured on the written samples, across
+/// all channels; dBFS fields are `None` for digital silence.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingStats {
+    pub recording_id: String,
+    pub duration_seconds: f64,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub peak_dbfs: Option<f32>,
+    /// RMS level over the whole recording.
+    pub avg_dbfs: Option<f32>,
+    /// Stream errors the audio driver reported while recording (overruns,
+    /// device hiccups). Each one may have lost a buffer of audio.
+    pub dropout_count: u32,
+    /// Frames dropped because the recorder fell too far behind the device,
+    /// each a gap in the written audio.
+    pub dropped_frames: u32,
+    pub file_size_bytes: u64,
+    pub was_clipped: bool,
+    /// Device capture rate measured against the wall clock, when it was off
+    /// the device's nominal rate enough to raise `recording-rate-drift`.
+    pub measured_sample_rate: Option<u32>,
+}
+
+impl RecordingStats {
+    pub fn new(
+        artifact: &RecordingArtifact,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+        dropout_count: u32,
+    ) -> Self {
+        let to_dbfs = |amplitude: f64| (amplitude > 0.0).then(|| (20.0 * amplitude.log10()) as f32);
+        let peak = samples.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+        let sum_sq: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
+        let rms = (sum_sq / samples.len().max(1) as f64).sqrt();
+        Self {
+            recording_id: artifact.id.clone(),
+            duration_seconds: artifact.duration_ms as f64 / 1000.0,
+            sample_rate,
+            channels,
+            peak_dbfs: to_dbfs(peak as f64),
+            avg_dbfs: to_dbfs(rms),
+            dropout_count,
+            dropped_frames: 0,
+            file_size_bytes: artifact.byte_length,
+            was_clipped: peak >= CLIP_THRESHOLD,
+            measured_sample_rate: None,
+        }
+    }
 }
 
 /// Validate that `id` is a single safe filename component: no separators,
@@ -101,12 +168,46 @@ pub(crate) fn recordings_dir(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(app_data.join(RECORDINGS_DIR_NAME))
 }
 
-/// Resolve the write path for a new cpal artifact. The cpal writer always
-/// produces `.wav`; reads use `find_recording_path` which accepts any
-/// extension (so navigator/file-upload blobs saved by JS still resolve).
-fn recording_path(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+/// Check that a recording can be written before a session opens: create
+/// the recordings folder if needed, then create and remove a probe file in
+/// it. Without this a read-only folder (a locked-down SD card, a full disk)
+/// only fails at `stop_recording`, after the user has already dictated.
+pub(crate) fn check_recordings_dir_writable(app: &AppHandle) -> Result<(), String> {
+    probe_writable(&recordings_dir(app)?)
+}
+
+fn probe_writable(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("create recordings dir {}: {e}", dir.display()))?;
+    if !dir.is_dir() {
+        return Err(format!(
+            "recordings dir {} is not a directory",
+            dir.display()
+        ));
+    }
+    let probe = dir.join(WRITE_PROBE_NAME);
+    OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(&probe)
+        .and_then(|mut file| file.write_all(b"probe"))
+        .map_err(|e| format!("recordings dir {} is not writable: {e}", dir.display()))?;
+    std::fs::remove_file(&probe).map_err(|e| format!("remove write probe {}: {e}", probe.display()))
+}
+
+/// Resolve the write path for a new cpal artifact, with the sanitized
+/// `label` from `naming::render_label` between the id and the extension. The
+/// cpal writer always produces `.wav`; reads use `find_recording_path` which
+/// accepts anything after the `{id}.` prefix (so navigator/file-upload blobs
+/// saved by JS, and labeled files, still resolve).
+fn recording_path(app: &AppHandle, id: &str, label: Option<&str>) -> Result<PathBuf, String> {
     validate_recording_id(id)?;
-    Ok(recordings_dir(app)?.join(format!("{id}.{ARTIFACT_EXT}")))
+    let name = match label {
+        Some(label) => format!("{id}.{label}.{ARTIFACT_EXT}"),
+        None => format!("{id}.{ARTIFACT_EXT}"),
+    };
+    Ok(recordings_dir(app)?.join(name))
 }
 
 /// Resolve the file path for an existing recording id, regardless of
@@ -136,7 +237,7 @@ fn is_recording_artifact_name(name: &str) -> bool {
     !name.ends_with(".md")
 }
 
-fn recording_id_from_artifact_filename(name: &str) -> Option<&str> {
+pub(super) fn recording_id_from_artifact_filename(name: &str) -> Option<&str> {
     if !is_recording_artifact_name(name) {
         return None;
     }
@@ -183,34 +284,51 @@ fn delete_recording_artifacts_matching(
     Ok(deleted)
 }
 
-/// Synthesize and write a mono 16 kHz IEEE-float WAV from in-memory PCM
-/// samples. Returns the artifact handle. The caller is responsible for
-/// passing samples that are already at `ARTIFACT_RATE` (the recorder
-/// consumer worker resamples at finalize, so this holds for cpal callers).
+/// Synthesize and write an IEEE-float WAV at `sample_rate` from in-memory
+/// PCM samples interleaved over `channels`, named `{id}.{label}.wav` when a
+/// label is given. Returns the artifact handle. The rate and channels are
+/// the recorder's `artifact_rate` and `artifact_channels`: 16 kHz mono, or
+/// the device's own for a native-format session. Readers decode any rate
+/// and channel count back to 16 kHz mono.
+///
+/// An earlier artifact for the same id under a different name is removed
+/// once the write succeeds, so the `{id}.` prefix lookup stays unambiguous.
 pub fn write_artifact(
     app: &AppHandle,
     id: &str,
+    label: Option<&str>,
     samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
 ) -> Result<RecordingArtifact, String> {
-    let path = recording_path(app, id)?;
+    let path = recording_path(app, id, label)?;
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)
             .map_err(|e| format!("create recordings dir {}: {e}", parent.display()))?;
     }
 
-    write_pcm_as_wav(&path, samples)?;
+    write_pcm_as_wav(&path, samples, sample_rate, channels)?;
 
     let byte_length = std::fs::metadata(&path)
         .map_err(|e| format!("stat artifact {}: {e}", path.display()))?
         .len();
 
-    let duration_ms = (samples.len() as f64 / ARTIFACT_RATE as f64 * 1000.0).round() as u64;
+    let frames = samples.len() / usize::from(channels.max(1));
+    let duration_ms = (frames as f64 / sample_rate as f64 * 1000.0).round() as u64;
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned());
+    delete_recording_artifacts_matching(app, |name| {
+        Some(name) != file_name.as_deref() && recording_id_from_artifact_filename(name) == Some(id)
+    })?;
 
     Ok(RecordingArtifact {
         id: id.to_string(),
         duration_ms,
         byte_length,
         mime_type: ARTIFACT_MIME.to_string(),
+        path: path.to_string_lossy().into_owned(),
     })
 }
 
@@ -219,10 +337,37 @@ pub fn write_artifact(
 /// Accepts any container Symphonia can decode (cpal-written WAV,
 /// navigator-saved webm/opus/mp4, etc.).
 pub fn read_artifact_samples(app: &AppHandle, id: &str) -> Result<Vec<f32>, String> {
+    read_artifact_samples_with(app, id, &Downmix::Average)
+}
+
+/// `read_artifact_samples`, folding a multichannel file to mono per
+/// `downmix`.
+pub fn read_artifact_samples_with(
+    app: &AppHandle,
+    id: &str,
+    downmix: &Downmix,
+) -> Result<Vec<f32>, String> {
     let path = find_recording_path(app, id)?;
     let bytes =
         std::fs::read(&path).map_err(|e| format!("read artifact {}: {e}", path.display()))?;
-    decode_to_pcm16k_mono(&bytes).map_err(|e| format!("decode artifact {}: {e}", path.display()))
+    decode_to_pcm16k_mono_with(&bytes, downmix)
+        .map_err(|e| format!("decode artifact {}: {e}", path.display()))
+}
+
+/// Like `read_artifact_samples`, but keeps a single channel instead of
+/// downmixing. Only meaningful for imported multichannel files and
+/// native-format recordings; other recorder output is mono, so only
+/// channel 0 exists there.
+pub fn read_artifact_channel_samples(
+    app: &AppHandle,
+    id: &str,
+    channel_index: u16,
+) -> Result<Vec<f32>, String> {
+    let path = find_recording_path(app, id)?;
+    let bytes =
+        std::fs::read(&path).map_err(|e| format!("read artifact {}: {e}", path.display()))?;
+    decode_channel_to_pcm16k(&bytes, channel_index)
+        .map_err(|e| format!("decode artifact {}: {e}", path.display()))
 }
 
 /// Delete recording artifacts by recording id.
@@ -255,65 +400,82 @@ pub(super) fn clear_artifacts(app: &AppHandle) -> Result<u32, String> {
     delete_recording_artifacts_matching(app, is_recording_artifact_name)
 }
 
-/// Write a mono 16 kHz IEEE-float WAV directly. The header is small and
+/// Write an IEEE-float WAV directly. The header is small and
 /// stable enough to hand-write here rather than route through `hound`,
 /// which has a less ergonomic API for the recovered-writer-then-sync_all
 /// flow we want. The file is fsynced before returning so the artifact
 /// handle never points at a partially flushed file.
-fn write_pcm_as_wav(path: &Path, samples: &[f32]) -> Result<(), String> {
-    let bits_per_sample: u16 = 32;
-    let bytes_per_sample: u32 = (bits_per_sample / 8) as u32;
-    let channels = ARTIFACT_CHANNELS;
-    let rate = ARTIFACT_RATE;
-    let data_size: u32 = (samples.len() as u32)
-        .checked_mul(bytes_per_sample)
-        .ok_or_else(|| "wav data size overflow".to_string())?;
-    let file_size: u32 = 36u32
-        .checked_add(data_size)
-        .ok_or_else(|| "wav file size overflow".to_string())?;
-
+///
+/// Every write error surfaces (a full disk shows up here as ENOSPC, at the
+/// latest from the flush or fsync). On failure the partial file is removed:
+/// its header claims the full length, so leaving it would present a
+/// truncated recording as a complete one.
+fn write_pcm_as_wav(path: &Path, samples: &[f32], rate: u32, channels: u16) -> Result<(), String> {
     let file = OpenOptions::new()
         .create(true)
         .truncate(true)
         .write(true)
         .open(path)
         .map_err(|e| format!("open artifact {}: {e}", path.display()))?;
+
+    let result = write_and_sync(file, path, samples, rate, channels);
+    if result.is_err() {
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
+
+fn write_and_sync(
+    file: std::fs::File,
+    path: &Path,
+    samples: &[f32],
+    rate: u32,
+    channels: u16,
+) -> Result<(), String> {
     let mut w = BufWriter::new(file);
+    write_wav(&mut w, samples, rate, channels).map_err(io_err(path))?;
+    let file = w
+        .into_inner()
+        .map_err(|e| format!("flush wav {}: {e}", path.display()))?;
+    file.sync_all()
+        .map_err(|e| format!("sync wav {}: {e}", path.display()))
+}
+
+/// Serialize `samples`, interleaved over `channels`, as an IEEE-float WAV
+/// at `rate` into `w`.
+fn write_wav(w: &mut impl Write, samples: &[f32], rate: u32, channels: u16) -> std::io::Result<()> {
+    let bits_per_sample: u16 = 32;
+    let bytes_per_sample: u32 = (bits_per_sample / 8) as u32;
+    let too_long = || std::io::Error::other("recording too long for a WAV file");
+    let data_size: u32 = u32::try_from(samples.len())
+        .ok()
+        .and_then(|n| n.checked_mul(bytes_per_sample))
+        .ok_or_else(too_long)?;
+    let file_size: u32 = 36u32.checked_add(data_size).ok_or_else(too_long)?;
 
     // RIFF header
-    w.write_all(b"RIFF").map_err(io_err(path))?;
-    w.write_all(&file_size.to_le_bytes())
-        .map_err(io_err(path))?;
-    w.write_all(b"WAVE").map_err(io_err(path))?;
+    w.write_all(b"RIFF")?;
+    w.write_all(&file_size.to_le_bytes())?;
+    w.write_all(b"WAVE")?;
     // fmt chunk
-    w.write_all(b"fmt ").map_err(io_err(path))?;
-    w.write_all(&16u32.to_le_bytes()).map_err(io_err(path))?;
+    w.write_all(b"fmt ")?;
+    w.write_all(&16u32.to_le_bytes())?;
     // Format tag 3 = IEEE float (per the WAVE spec, the original PCM tag 1
     // is for integer samples; tag 3 indicates IEEE 754 float samples).
-    w.write_all(&3u16.to_le_bytes()).map_err(io_err(path))?;
-    w.write_all(&channels.to_le_bytes()).map_err(io_err(path))?;
-    w.write_all(&rate.to_le_bytes()).map_err(io_err(path))?;
+    w.write_all(&3u16.to_le_bytes())?;
+    w.write_all(&channels.to_le_bytes())?;
+    w.write_all(&rate.to_le_bytes())?;
     let byte_rate: u32 = rate * channels as u32 * bytes_per_sample;
-    w.write_all(&byte_rate.to_le_bytes())
-        .map_err(io_err(path))?;
+    w.write_all(&byte_rate.to_le_bytes())?;
     let block_align: u16 = channels * bytes_per_sample as u16;
-    w.write_all(&block_align.to_le_bytes())
-        .map_err(io_err(path))?;
-    w.write_all(&bits_per_sample.to_le_bytes())
-        .map_err(io_err(path))?;
+    w.write_all(&block_align.to_le_bytes())?;
+    w.write_all(&bits_per_sample.to_le_bytes())?;
     // data chunk
-    w.write_all(b"data").map_err(io_err(path))?;
-    w.write_all(&data_size.to_le_bytes())
-        .map_err(io_err(path))?;
+    w.write_all(b"data")?;
+    w.write_all(&data_size.to_le_bytes())?;
     for &s in samples {
-        w.write_all(&s.to_le_bytes()).map_err(io_err(path))?;
+        w.write_all(&s.to_le_bytes())?;
     }
-
-    let file = w
-        .into_inner()
-        .map_err(|e| format!("flush wav {}: {e}", path.display()))?;
-    file.sync_all()
-        .map_err(|e| format!("sync wav {}: {e}", path.display()))?;
     Ok(())
 }
 
@@ -325,6 +487,46 @@ fn io_err(path: &Path) -> impl Fn(std::io::Error) -> String + '_ {
 mod tests {
     use super::*;
 
+    #[test]
+    fn stats_summarize_levels_and_clipping() {
+        let artifact = RecordingArtifact {
+            id: "rec".to_string(),
+            duration_ms: 1500,
+            byte_length: 96_044,
+            mime_type: ARTIFACT_MIME.to_string(),
+            path: "rec.wav".to_string(),
+        };
+
+        let stats = RecordingStats::new(&artifact, &[0.5, -0.5, 0.5, -0.5], ARTIFACT_RATE, 2, 2);
+        assert_eq!(stats.duration_seconds, 1.5);
+        assert_eq!(stats.channels, 2);
+        assert!((stats.peak_dbfs.unwrap() + 6.02).abs() < 0.01);
+        assert_eq!(stats.avg_dbfs, stats.peak_dbfs);
+        assert_eq!(stats.dropout_count, 2);
+        assert!(!stats.was_clipped);
+
+        assert!(RecordingStats::new(&artifact, &[0.2, 1.0], ARTIFACT_RATE, 1, 0).was_clipped);
+        let silent = RecordingStats::new(&artifact, &[0.0; 4], ARTIFACT_RATE, 1, 0);
+        assert_eq!((silent.peak_dbfs, silent.avg_dbfs), (None, None));
+    }
+
+    #[test]
+    fn probe_creates_the_dir_and_leaves_it_empty() {
+        let root = std::env::temp_dir().join(format!("whispering-probe-{}", std::process::id()));
+        let dir = root.join("recordings");
+        let result = probe_writable(&dir);
+        let left_behind = std::fs::read_dir(&dir).map(|entries| entries.count());
+
+        let file = root.join("file");
+        std::fs::write(&file, "not a dir").unwrap();
+        let on_file = probe_writable(&file);
+        std::fs::remove_dir_all(&root).ok();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(left_behind.unwrap(), 0);
+        assert!(on_file.is_err());
+    }
+
     #[test]
     fn validate_rejects_traversal() {
         assert!(validate_recording_id("../escape").is_err());
@@ -349,4 +551,61 @@ mod tests {
         assert_eq!(recording_id_from_artifact_filename("abc.webm"), Some("abc"));
         assert_eq!(recording_id_from_artifact_filename("abc.md"), None);
     }
+
+    /// Accepts `limit` bytes, then fails the way a full disk does.
+    struct FullDisk {
+        written: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Write for FullDisk {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let room = self.limit - self.written.len();
+            if room == 0 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::StorageFull,
+                    "No space left on device",
+                ));
+            }
+            let n = buf.len().min(room);
+            self.written.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn wav_write_failure_is_reported_not_swallowed() {
+        let samples = vec![0.25f32; 1000];
+        let mut full = FullDisk {
+            written: Vec::new(),
+            limit: 100,
+        };
+
+        let err = write_wav(&mut full, &samples, ARTIFACT_RATE, 1).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::StorageFull);
+
+        let mut roomy = FullDisk {
+            written: Vec::new(),
+            limit: usize::MAX,
+        };
+        write_wav(&mut roomy, &samples, ARTIFACT_RATE, 1).unwrap();
+        assert_eq!(roomy.written.len(), 44 + samples.len() * 4);
+    }
+
+    #[test]
+    fn wav_header_describes_interleaved_channels() {
+        let mut wav = Vec::new();
+        write_wav(&mut wav, &[0.25f32; 8], 48_000, 2).unwrap();
+        let u16_at = |at: usize| u16::from_le_bytes([wav[at], wav[at + 1]]);
+        let u32_at = |at: usize| u32::from_le_bytes(wav[at..at + 4].try_into().unwrap());
+        assert_eq!(u16_at(22), 2);
+        assert_eq!(u32_at(24), 48_000);
+        assert_eq!(u32_at(28), 48_000 * 2 * 4);
+        assert_eq!(u16_at(32), 8);
+        assert_eq!(u32_at(40), 8 * 4);
+    }
 }
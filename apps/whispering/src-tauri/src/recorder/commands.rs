@@ -1,14 +1,185 @@
+use crate::focus::remember_paste_target;
 use crate::recorder::artifact::{
-    clear_artifacts, delete_artifacts, write_artifact, RecordingArtifact,
+    check_recordings_dir_writable, clear_artifacts, delete_artifacts, write_artifact,
+    RecordingArtifact, RecordingStats,
 };
-use crate::recorder::recorder::{Recorder, Result};
+use crate::recorder::level::{
+    estimate_snr, CurrentLevel, LevelBallistics, LevelSensitivity, SnrMeasurement,
+    MAX_LEVEL_WINDOW_MS,
+};
+use crate::recorder::orphans::{find_orphans, OrphanedRecordings};
+use crate::recorder::recorder::{
+    Recorder, RecorderSnapshot, RecordingDevice, RecordingLatency, RecordingSessionOptions, Result,
+    StreamConfigInfo,
+};
+use crate::recorder::retention::{cleanup, RecordingCleanup};
+use crate::timings::{record_stage, PipelineStage};
 use log::{debug, info, warn};
 use serde::Serialize;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, State};
+use tokio::sync::Notify;
 
 const RECORDER_STATE_CHANGED: &str = "recorder:state-changed";
 
+/// Emitted when a stopped recording could not be written to disk (disk
+/// full, folder removed, permissions). The audio is lost; the event lets the
+/// UI say so instead of the recording silently never appearing.
+pub const RECORDING_WRITE_FAILED: &str = "recording-write-failed";
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RecordingWriteFailed {
+    recording_id: String,
+    error: String,
+}
+
+/// Emitted after a successful stop with a `RecordingStats` quality summary
+/// (duration, levels, dropouts, clipping), so the UI can show it without
+/// another round trip.
+pub const RECORDING_COMPLETE: &str = "recording-complete";
+
+/// Emitted once a second during a `start_recording` countdown, with the
+/// time left before capture starts, and with 0 as capture starts.
+pub const RECORDING_COUNTDOWN: &str = "recording-countdown";
+
+/// Longest accepted `start_delay_ms`.
+const MAX_START_DELAY_MS: u32 = 60_000;
+
+/// Emitted by `measure_snr` as each phase starts, so the UI can prompt
+/// "stay quiet" and then "now speak", and once more when it is done.
+pub const SNR_MEASUREMENT_PHASE: &str = "snr-measurement-phase";
+
+/// Accepted length of each `measure_snr` phase.
+const SNR_PHASE_RANGE_MS: std::ops::RangeInclusive<u32> = 500..=30_000;
+
+/// `measure_snr` reads the level of this much audio this often.
+const SNR_READ_INTERVAL_MS: u32 = 100;
+
+/// `get_current_level` window without an explicit one: the `mic-level`
+/// emit interval.
+const DEFAULT_LEVEL_WINDOW_MS: u32 = 50;
+
+/// Accepted `record_to_memory` limit. Two minutes of 16 kHz mono is under
+/// 8 MB of samples; longer dictation belongs in a file.
+const MEMORY_CAPTURE_RANGE_MS: std::ops::RangeInclusive<u32> = 500..=120_000;
+
+/// Prefix of `record_to_memory` session ids, which name no file.
+const MEMORY_SESSION_PREFIX: &str = "memory-";
+
+/// Managed flag set while an `init_recording_session` call is opening a
+/// session. Two windows (or a double-fired shortcut) calling init at once
+/// would otherwise queue on the recorder lock, and the second call's
+/// `close_session` would tear down the session the first just returned to
+/// its caller. The second call is rejected instead, so the first caller's
+/// session stays the open one.
+#[derive(Default)]
+pub struct SessionInitLock(AtomicBool);
+
+impl SessionInitLock {
+    /// Claim the init, or fail if another call holds it. Released when the
+    /// claim drops, including on an init error.
+    fn claim(&self) -> Result<SessionInitClaim<'_>> {
+        self.0
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .map(|_| SessionInitClaim(&self.0))
+            .map_err(|_| {
+                "SessionInitInProgress: another recording session is being initialized".to_string()
+            })
+    }
+}
+
+struct SessionInitClaim<'a>(&'a AtomicBool);
+
+impl Drop for SessionInitClaim<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// Managed slot for the `record_to_memory` capture in progress, if any, so
+/// `stop_memory_recording` and `cancel_recording` can end it, and
+/// `stop_recording` does not write a file behind its back. Holds the
+/// session id and the signal the capture waits on.
+#[derive(Default)]
+pub struct MemoryCapture(Mutex<Option<(String, Arc<Notify>)>>);
+
+impl MemoryCapture {
+    fn begin(&self, recording_id: &str) -> Arc<Notify> {
+        let stop = Arc::new(Notify::new());
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) =
+            Some((recording_id.to_string(), stop.clone()));
+        stop
+    }
+
+    fn is_capturing(&self, recording_id: &str) -> bool {
+        matches!(
+            &*self.0.lock().unwrap_or_else(|e| e.into_inner()),
+            Some((id, _)) if id == recording_id
+        )
+    }
+
+    /// Wake the capture in progress, if any. False when there is none.
+    fn stop_any(&self) -> bool {
+        match &*self.0.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some((_, stop)) => {
+                stop.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Wake the capture of `recording_id`. False when that session is not a
+    /// memory capture.
+    fn stop(&self, recording_id: &str) -> bool {
+        match &*self.0.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some((id, stop)) if id == recording_id => {
+                stop.notify_one();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn end(&self) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// 16 kHz mono samples from `record_to_memory`, ready for
+/// `transcribe_samples_detailed`.
+#[derive(Serialize, Clone, Debug, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryRecording {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RecordingCountdown {
+    recording_id: String,
+    remaining_ms: u32,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+enum SnrPhase {
+    Silence,
+    Speech,
+    Done,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SnrMeasurementPhase {
+    phase: SnrPhase,
+    duration_ms: u32,
+}
+
 #[derive(Serialize, Clone, Copy, Debug)]
 #[serde(rename_all = "UPPERCASE")]
 enum RecordingState {
@@ -25,6 +196,9 @@ fn emit_recording_state(app: &AppHandle, state: RecordingState) {
     }
 }
 
+/// Device names in driver order, which can change between calls.
+/// Deprecated: prefer `list_recording_devices`, which orders the list stably
+/// and marks the system default.
 #[tauri::command]
 #[specta::specta]
 pub async fn enumerate_recording_devices(
@@ -37,18 +211,54 @@ pub async fn enumerate_recording_devices(
     recorder.enumerate_devices()
 }
 
+/// Recording devices with the system default first and marked, then the
+/// rest alphabetically, so a settings dropdown keeps its order across
+/// refreshes. Pass a device's `id`, not its name, to `init_recording_session`:
+/// two devices can share a name.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_recording_devices(
+    recorder: State<'_, Mutex<Recorder>>,
+) -> Result<Vec<RecordingDevice>> {
+    debug!("Listing recording devices");
+    let recorder = recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {e}"))?;
+    recorder.list_devices()
+}
+
+/// Open the input stream for a new session. `device_identifier` is a device
+/// `id` from `list_recording_devices` (for a device with a unique name, the
+/// name itself), or the empty string for the system default (the legacy
+/// `"default"` literal is still accepted). On Windows a `"[Loopback] {output}"`
+/// id from `list_recording_devices` records what that output plays, e.g. the
+/// other side of a meeting; other platforms return an error explaining their
+/// own route (a virtual device on macOS, a monitor source on Linux).
+/// `options` may be omitted; see `RecordingSessionOptions` for the knobs and
+/// their tradeoffs. An out-of-range `sample_rate` or option, or a
+/// `filename_template` with an unknown placeholder, is rejected here with the
+/// field named, before the open session is closed, and so is a recordings
+/// folder that cannot be written to. A call made while another init is
+/// still running fails with a `SessionInitInProgress` error and leaves that
+/// init's session alone.
 #[tauri::command]
 #[specta::specta]
 pub async fn init_recording_session(
     device_identifier: String,
     recording_id: String,
     sample_rate: Option<u32>,
+    options: Option<RecordingSessionOptions>,
     recorder: State<'_, Mutex<Recorder>>,
+    init_lock: State<'_, SessionInitLock>,
     app_handle: AppHandle,
 ) -> Result<()> {
+    let _claim = init_lock.claim().inspect_err(|_| {
+        warn!("Rejected init for {recording_id}: another session init is in progress");
+    })?;
     info!(
-        "Initializing recording session: device={device_identifier}, id={recording_id}, sample_rate={sample_rate:?}",
+        "Initializing recording session: device={device_identifier}, id={recording_id}, sample_rate={sample_rate:?}, options={options:?}",
     );
+    check_recordings_dir_writable(&app_handle)?;
 
     {
         let mut recorder = recorder
@@ -58,6 +268,7 @@ pub async fn init_recording_session(
             device_identifier,
             recording_id,
             sample_rate,
+            options.unwrap_or_default(),
             app_handle.clone(),
         )?;
     }
@@ -68,53 +279,230 @@ pub async fn init_recording_session(
     Ok(())
 }
 
+/// Start capturing into the open session. With `start_delay_ms`, first
+/// count down for that long (at most 60 s), emitting `RECORDING_COUNTDOWN`
+/// each second so the UI can show "3, 2, 1". Audio during the countdown is
+/// not kept. `cancel_recording` during the countdown closes the session, so
+/// capture never starts, no file is written, and this returns an error.
 #[tauri::command]
 #[specta::specta]
 pub async fn start_recording(
+    start_delay_ms: Option<u32>,
     recorder: State<'_, Mutex<Recorder>>,
     app_handle: AppHandle,
 ) -> Result<()> {
-    info!("Starting recording");
+    info!("Starting recording: start_delay_ms={start_delay_ms:?}");
+    if let Some(delay_ms) = start_delay_ms.filter(|&ms| ms > 0) {
+        if delay_ms > MAX_START_DELAY_MS {
+            return Err(format!(
+                "Start delay must be at most {MAX_START_DELAY_MS} ms, got {delay_ms}"
+            ));
+        }
+        count_down(&recorder, &app_handle, delay_ms).await?;
+    }
     {
         let mut recorder = recorder
             .lock()
             .map_err(|e| format!("Failed to lock recorder: {e}"))?;
         recorder.start_recording()?;
     }
+    // The user is still in the app they are dictating into; remember it so
+    // `write_text` can wait for it to be frontmost again.
+    remember_paste_target(&app_handle);
     emit_recording_state(&app_handle, RecordingState::Recording);
     Ok(())
 }
 
+/// Wait out `delay_ms`, emitting the time left each second. Fails when the
+/// session the countdown started for is gone (cancelled, closed, or
+/// replaced) at any tick.
+async fn count_down(
+    recorder: &Mutex<Recorder>,
+    app_handle: &AppHandle,
+    delay_ms: u32,
+) -> Result<()> {
+    let session_id = |recorder: &Mutex<Recorder>| -> Result<Option<String>> {
+        Ok(recorder
+            .lock()
+            .map_err(|e| format!("Failed to lock recorder: {e}"))?
+            .session_id())
+    };
+    let recording_id =
+        session_id(recorder)?.ok_or_else(|| "No recording session initialized".to_string())?;
+
+    let mut remaining_ms = delay_ms;
+    loop {
+        if session_id(recorder)?.as_deref() != Some(recording_id.as_str()) {
+            info!("Recording {recording_id} cancelled during countdown");
+            return Err("Recording was cancelled during the countdown".to_string());
+        }
+        let payload = RecordingCountdown {
+            recording_id: recording_id.clone(),
+            remaining_ms,
+        };
+        if let Err(e) = app_handle.emit(RECORDING_COUNTDOWN, payload) {
+            warn!("Failed to emit {RECORDING_COUNTDOWN}: {e}");
+        }
+        if remaining_ms == 0 {
+            return Ok(());
+        }
+        // Land on whole seconds after the first tick: 2500 -> 2000 -> 1000.
+        let step_ms = match remaining_ms % 1000 {
+            0 => 1000,
+            partial => partial,
+        };
+        tokio::time::sleep(Duration::from_millis(step_ms.into())).await;
+        remaining_ms -= step_ms;
+    }
+}
+
 /// Stop the recorder, write the canonical WAV artifact to
 /// `<appDataDir>/recordings/{id}.wav`, return the small JSON handle.
 ///
 /// JS never sees raw PCM samples on the wire: later operations look the
 /// file up by id (`transcribe_recording`, `encode_recording_for_upload`,
 /// and `delete_recording_artifacts`).
+///
+/// A `record_to_memory` capture is left running: end it with
+/// `stop_memory_recording`, which has no file to return.
 #[tauri::command]
 #[specta::specta]
 pub async fn stop_recording(
     recorder: State<'_, Mutex<Recorder>>,
+    memory: State<'_, MemoryCapture>,
     app_handle: AppHandle,
 ) -> Result<RecordingArtifact> {
     info!("Stopping recording");
-    let (recording_id, samples) = {
+    let stop_requested = Instant::now();
+    let (
+        recording_id,
+        label,
+        samples,
+        sample_rate,
+        channels,
+        dropout_count,
+        dropped_frames,
+        measured_rate,
+    ) = {
         let mut recorder = recorder
             .lock()
             .map_err(|e| format!("Failed to lock recorder: {e}"))?;
         let id = recorder
             .session_id()
             .ok_or_else(|| "no active recording session at stop".to_string())?;
+        if memory.is_capturing(&id) {
+            return Err(format!(
+                "Recording {id} is a memory capture; stop it with stop_memory_recording"
+            ));
+        }
         let samples = recorder.stop_recording()?;
-        (id, samples)
+        (
+            id,
+            recorder.artifact_label(),
+            samples,
+            recorder.artifact_rate(),
+            recorder.artifact_channels(),
+            recorder.dropout_count(),
+            recorder.dropped_frames(),
+            recorder.measured_rate(),
+        )
     };
 
-    let artifact = write_artifact(&app_handle, &recording_id, &samples)?;
+    // The recorder has already stopped, so JS must see IDLE even when the
+    // write fails.
+    let artifact = persist_recording(
+        &app_handle,
+        &recording_id,
+        label.as_deref(),
+        &samples,
+        sample_rate,
+        channels,
+    );
     emit_recording_state(&app_handle, RecordingState::Idle);
+    let artifact = artifact?;
+    record_stage(
+        &app_handle,
+        PipelineStage::RecordToStop,
+        stop_requested.elapsed(),
+    );
     info!(
         "Recording stopped: id={}, duration_ms={}, bytes={}",
         artifact.id, artifact.duration_ms, artifact.byte_length,
     );
+    if dropped_frames > 0 {
+        warn!("Recording {recording_id} dropped {dropped_frames} frames on a full sample queue");
+    }
+    let stats = RecordingStats {
+        dropped_frames,
+        measured_sample_rate: measured_rate,
+        ..RecordingStats::new(&artifact, &samples, sample_rate, channels, dropout_count)
+    };
+    if let Err(e) = app_handle.emit(RECORDING_COMPLETE, stats) {
+        warn!("Failed to emit {RECORDING_COMPLETE}: {e}");
+    }
+    Ok(artifact)
+}
+
+/// `write_artifact`, reporting a failure on `RECORDING_WRITE_FAILED` as well
+/// as in the returned error.
+fn persist_recording(
+    app_handle: &AppHandle,
+    recording_id: &str,
+    label: Option<&str>,
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+) -> Result<RecordingArtifact> {
+    write_artifact(
+        app_handle,
+        recording_id,
+        label,
+        samples,
+        sample_rate,
+        channels,
+    )
+    .inspect_err(|error| {
+        warn!("Failed to write recording {recording_id}: {error}");
+        let payload = RecordingWriteFailed {
+            recording_id: recording_id.to_string(),
+            error: error.clone(),
+        };
+        if let Err(e) = app_handle.emit(RECORDING_WRITE_FAILED, payload) {
+            warn!("Failed to emit {RECORDING_WRITE_FAILED}: {e}");
+        }
+    })
+}
+
+/// Stop and persist the session's recording (if any, including one
+/// auto-stopped but not yet saved), then close the session so the input
+/// device is released. Used when the app is about to go away underneath the
+/// recorder, e.g. before an update swaps the binary. Returns the artifact
+/// that was written, or `None` when there was no recording.
+pub(crate) fn finalize_and_close(
+    recorder: &Mutex<Recorder>,
+    app_handle: &AppHandle,
+) -> Result<Option<RecordingArtifact>> {
+    let mut recorder = recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {e}"))?;
+    let artifact = match recorder.take_recording()? {
+        Some((recording_id, samples)) => {
+            let label = recorder.artifact_label();
+            Some(persist_recording(
+                app_handle,
+                &recording_id,
+                label.as_deref(),
+                &samples,
+                recorder.artifact_rate(),
+                recorder.artifact_channels(),
+            )?)
+        }
+        None => None,
+    };
+    recorder.close_session()?;
+    drop(recorder);
+
+    emit_recording_state(app_handle, RecordingState::Idle);
     Ok(artifact)
 }
 
@@ -122,6 +510,7 @@ pub async fn stop_recording(
 #[specta::specta]
 pub async fn cancel_recording(
     recorder: State<'_, Mutex<Recorder>>,
+    memory: State<'_, MemoryCapture>,
     app_handle: AppHandle,
 ) -> Result<()> {
     info!("Cancelling recording");
@@ -129,12 +518,124 @@ pub async fn cancel_recording(
         let mut recorder = recorder
             .lock()
             .map_err(|e| format!("Failed to lock recorder: {e}"))?;
+        let session = recorder.session_id();
         recorder.cancel_recording()?;
+        // A memory capture wakes to find its session gone and fails.
+        if let Some(id) = session {
+            memory.stop(&id);
+        }
     }
     emit_recording_state(&app_handle, RecordingState::Idle);
     Ok(())
 }
 
+/// End the `record_to_memory` capture in progress, whose call then returns
+/// the samples. Fails when no memory capture is running.
+#[tauri::command]
+#[specta::specta]
+pub async fn stop_memory_recording(memory: State<'_, MemoryCapture>) -> Result<()> {
+    info!("Stopping memory recording");
+    if memory.stop_any() {
+        Ok(())
+    } else {
+        Err("No memory recording in progress".to_string())
+    }
+}
+
+/// Record from `device_identifier` into memory and return the samples, with
+/// no file written or read, for the lowest-latency "hold, speak, release"
+/// dictation: pass the result straight to `transcribe_samples_detailed`.
+///
+/// Opens its own session (closing any open one that is not recording),
+/// captures until `stop_memory_recording` is called or `max_duration_ms`
+/// (500-120000) passes, then closes the session again. `sample_rate` is the
+/// preferred device rate, as for `init_recording_session`; the samples come
+/// back at 16 kHz mono either way. `cancel_recording` discards the capture
+/// and fails this call.
+#[tauri::command]
+#[specta::specta]
+pub async fn record_to_memory(
+    device_identifier: String,
+    max_duration_ms: u32,
+    sample_rate: Option<u32>,
+    recorder: State<'_, Mutex<Recorder>>,
+    init_lock: State<'_, SessionInitLock>,
+    memory: State<'_, MemoryCapture>,
+    app_handle: AppHandle,
+) -> Result<MemoryRecording> {
+    if !MEMORY_CAPTURE_RANGE_MS.contains(&max_duration_ms) {
+        return Err(format!(
+            "Memory capture limit must be between {} and {} ms, got {max_duration_ms}",
+            MEMORY_CAPTURE_RANGE_MS.start(),
+            MEMORY_CAPTURE_RANGE_MS.end()
+        ));
+    }
+    let recording_id = format!(
+        "{MEMORY_SESSION_PREFIX}{}",
+        chrono::Local::now().timestamp_millis()
+    );
+    info!(
+        "Recording to memory: device={device_identifier}, id={recording_id}, max_duration_ms={max_duration_ms}",
+    );
+
+    let stop = {
+        let _claim = init_lock.claim()?;
+        let mut recorder = recorder
+            .lock()
+            .map_err(|e| format!("Failed to lock recorder: {e}"))?;
+        if recorder.has_recording() {
+            return Err("Cannot record to memory while a recording is in progress".to_string());
+        }
+        recorder.init_session(
+            device_identifier,
+            recording_id.clone(),
+            sample_rate,
+            RecordingSessionOptions::default(),
+            app_handle.clone(),
+        )?;
+        // Registered before capture starts, so an immediate stop is not lost.
+        let stop = memory.begin(&recording_id);
+        if let Err(e) = recorder.start_recording() {
+            memory.end();
+            let _ = recorder.close_session();
+            return Err(e);
+        }
+        stop
+    };
+    emit_recording_state(&app_handle, RecordingState::Recording);
+
+    let stopped = tokio::time::timeout(
+        Duration::from_millis(u64::from(max_duration_ms)),
+        stop.notified(),
+    )
+    .await
+    .is_ok();
+    memory.end();
+
+    let samples = {
+        let mut recorder = recorder
+            .lock()
+            .map_err(|e| format!("Failed to lock recorder: {e}"))?;
+        if recorder.session_id().as_deref() != Some(recording_id.as_str()) {
+            info!("Memory recording {recording_id} was cancelled");
+            return Err("Recording was cancelled".to_string());
+        }
+        let samples = recorder.stop_recording();
+        let _ = recorder.close_session();
+        samples
+    };
+    emit_recording_state(&app_handle, RecordingState::Idle);
+    let samples = samples?;
+    info!(
+        "Memory recording {recording_id} done: samples={}, stopped_early={stopped}",
+        samples.len()
+    );
+    Ok(MemoryRecording {
+        samples,
+        sample_rate: 16_000,
+    })
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn close_recording_session(
@@ -164,6 +665,51 @@ pub async fn get_current_recording_id(
     Ok(recorder.get_current_recording_id())
 }
 
+/// Authoritative recorder state in one call, for a webview that reloaded
+/// (hot reload, crash recovery) while the Rust session lived on. The UI
+/// restores its view from this instead of assuming idle. There is no pause
+/// in the recorder, so "not recording" with an open session means armed,
+/// unless `auto_stopped` says a recording is still waiting to be saved.
+#[tauri::command]
+#[specta::specta]
+pub async fn sync_recorder_state(recorder: State<'_, Mutex<Recorder>>) -> Result<RecorderSnapshot> {
+    debug!("Syncing recorder state");
+    let recorder = recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {e}"))?;
+    Ok(recorder.snapshot())
+}
+
+/// What cpal negotiated for the open session, or `None` when no session is
+/// open. The requested sample rate and the default buffer size are only
+/// hints, so this is the source of truth for the format actually captured.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_stream_config(
+    recorder: State<'_, Mutex<Recorder>>,
+) -> Result<Option<StreamConfigInfo>> {
+    debug!("Getting stream config");
+    let recorder = recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {e}"))?;
+    Ok(recorder.stream_config())
+}
+
+/// Input latency of the open session, or `None` when no session is open.
+/// Combine with `bufferFrames` in `RecordingSessionOptions` to trade CPU for
+/// a shorter delay between speaking and seeing levels or captions.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_recording_latency(
+    recorder: State<'_, Mutex<Recorder>>,
+) -> Result<Option<RecordingLatency>> {
+    debug!("Getting recording latency");
+    let recorder = recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {e}"))?;
+    Ok(recorder.latency())
+}
+
 /// Delete recording artifacts by id.
 ///
 /// This is intentionally id-based instead of path-based. The recorder
@@ -195,3 +741,358 @@ pub async fn clear_recording_artifacts(app_handle: AppHandle) -> Result<u32> {
         .await
         .map_err(|e| format!("Task join error: {e}"))?
 }
+
+/// Recordings that no markdown note under `notes_folder` (searched
+/// recursively) mentions by id, with the space deleting them would free.
+/// Nothing is deleted here: pass the ids to `delete_recording_artifacts`.
+/// Fails if any note cannot be read, so an unreadable note never makes its
+/// recording look orphaned.
+#[tauri::command]
+#[specta::specta]
+pub async fn find_orphaned_recordings(
+    notes_folder: String,
+    app_handle: AppHandle,
+) -> Result<OrphanedRecordings> {
+    tokio::task::spawn_blocking(move || {
+        find_orphans(&app_handle, std::path::Path::new(&notes_folder))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?
+}
+
+/// Delete old recordings: keep the `keep_last` most recent, plus any newer
+/// than `keep_newer_than_days` when given, and delete the artifacts of the
+/// rest. Markdown sidecars stay. Returns the deleted ids, so their database
+/// rows can be removed too, and the bytes freed.
+#[tauri::command]
+#[specta::specta]
+pub async fn cleanup_recordings(
+    keep_last: u32,
+    keep_newer_than_days: Option<u32>,
+    app_handle: AppHandle,
+) -> Result<RecordingCleanup> {
+    info!("Cleaning up recordings: keep_last={keep_last}, keep_newer_than_days={keep_newer_than_days:?}");
+    let removed =
+        tokio::task::spawn_blocking(move || cleanup(&app_handle, keep_last, keep_newer_than_days))
+            .await
+            .map_err(|e| format!("Task join error: {e}"))??;
+    info!(
+        "Removed {} recording(s), freeing {} bytes",
+        removed.recording_ids.len(),
+        removed.freed_bytes
+    );
+    Ok(removed)
+}
+
+/// Calibrate the live `mic-level` meter: the RMS is multiplied by
+/// `multiplier` (0.1-100) and capped at `max` (0.01-1.0) before it is
+/// emitted. Raise the multiplier for a quiet mic; lower it, or the cap, for
+/// one that keeps the bars pinned. Takes effect immediately, including
+/// mid-recording, and never changes the recorded audio.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_level_sensitivity(
+    multiplier: f32,
+    max: f32,
+    recorder: State<'_, Mutex<Recorder>>,
+) -> Result<()> {
+    let sensitivity = LevelSensitivity::new(multiplier, max)?;
+    recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {e}"))?
+        .set_level_sensitivity(sensitivity);
+    info!("Level sensitivity set: {sensitivity:?}");
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_level_sensitivity(
+    recorder: State<'_, Mutex<Recorder>>,
+) -> Result<LevelSensitivity> {
+    let recorder = recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {e}"))?;
+    Ok(recorder.level_sensitivity())
+}
+
+/// Start or stop emitting `mic-level` to the overlay from the open session
+/// while it is not recording, for a "ready to record" overlay that pulses
+/// with the user's voice. Nothing is recorded. Starting a recording turns it
+/// off (the recording emits levels itself, so they are never sent twice), as
+/// does closing the session; call again to resume. Fails without an open
+/// session.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_idle_level_monitoring(
+    enabled: bool,
+    recorder: State<'_, Mutex<Recorder>>,
+) -> Result<()> {
+    recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {e}"))?
+        .set_idle_levels(enabled)?;
+    debug!(
+        "Idle level monitoring {}",
+        if enabled { "on" } else { "off" }
+    );
+    Ok(())
+}
+
+/// RMS and peak dBFS of the last `window_ms` of input (default 50, the
+/// `mic-level` interval; at most 1000), for simple meters that poll instead
+/// of subscribing to the event. Raw levels, before the meter's sensitivity
+/// and ballistics. `null` unless recording or idle level monitoring is on.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_current_level(
+    window_ms: Option<u32>,
+    recorder: State<'_, Mutex<Recorder>>,
+) -> Result<Option<CurrentLevel>> {
+    let window_ms = window_ms.unwrap_or(DEFAULT_LEVEL_WINDOW_MS);
+    if !(1..=MAX_LEVEL_WINDOW_MS).contains(&window_ms) {
+        return Err(format!(
+            "Level window must be between 1 and {MAX_LEVEL_WINDOW_MS} ms, got {window_ms}"
+        ));
+    }
+    let recorder = recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {e}"))?;
+    Ok(recorder.current_level(window_ms))
+}
+
+/// Estimate the signal-to-noise ratio of the open session's mic in this
+/// room. Emits `SNR_MEASUREMENT_PHASE` with `silence`, measures the noise
+/// floor for `silence_duration_ms`, then emits `speech` and measures the
+/// user's speaking level for `speech_duration_ms`, and finally emits `done`.
+/// Each phase is 500-30000 ms. Levels come from the same metering as
+/// `get_current_level`, which is turned on for the measurement (it also
+/// drives the overlay's `mic-level`) and put back as it was.
+///
+/// `device_name` must be the open session's device, or empty for whichever
+/// device it opened. Fails without an open session or while recording.
+#[tauri::command]
+#[specta::specta]
+pub async fn measure_snr(
+    device_name: String,
+    speech_duration_ms: u32,
+    silence_duration_ms: u32,
+    recorder: State<'_, Mutex<Recorder>>,
+    app_handle: AppHandle,
+) -> Result<SnrMeasurement> {
+    for duration_ms in [silence_duration_ms, speech_duration_ms] {
+        if !SNR_PHASE_RANGE_MS.contains(&duration_ms) {
+            return Err(format!(
+                "Each SNR phase must be between {} and {} ms, got {duration_ms}",
+                SNR_PHASE_RANGE_MS.start(),
+                SNR_PHASE_RANGE_MS.end()
+            ));
+        }
+    }
+
+    let was_metering = {
+        let recorder = recorder
+            .lock()
+            .map_err(|e| format!("Failed to lock recorder: {e}"))?;
+        let snapshot = recorder.snapshot();
+        if !snapshot.session_open {
+            return Err("No recording session initialized".to_string());
+        }
+        if snapshot.is_recording {
+            return Err("Cannot measure SNR while recording".to_string());
+        }
+        let session_device = snapshot.device_name.unwrap_or_default();
+        if !device_name.is_empty() && device_name != session_device {
+            return Err(format!(
+                "The open session records from '{session_device}', not '{device_name}'"
+            ));
+        }
+        let was_metering = recorder.idle_levels_enabled();
+        recorder.set_idle_levels(true)?;
+        was_metering
+    };
+
+    let measured = async {
+        let silence = read_snr_phase(
+            &recorder,
+            &app_handle,
+            SnrPhase::Silence,
+            silence_duration_ms,
+        )
+        .await?;
+        let speech =
+            read_snr_phase(&recorder, &app_handle, SnrPhase::Speech, speech_duration_ms).await?;
+        Ok::<_, String>(estimate_snr(&silence, &speech))
+    }
+    .await;
+
+    if !was_metering {
+        if let Ok(recorder) = recorder.lock() {
+            // Fails only if the session was closed meanwhile, which ended
+            // the metering anyway.
+            let _ = recorder.set_idle_levels(false);
+        }
+    }
+    emit_snr_phase(&app_handle, SnrPhase::Done, 0);
+
+    let measurement = measured?;
+    info!("Measured SNR: {measurement:?}");
+    Ok(measurement)
+}
+
+/// RMS readings over one `measure_snr` phase. Fails when nothing was
+/// metered, i.e. the session was closed mid-measurement.
+async fn read_snr_phase(
+    recorder: &Mutex<Recorder>,
+    app_handle: &AppHandle,
+    phase: SnrPhase,
+    duration_ms: u32,
+) -> Result<Vec<Option<f32>>> {
+    emit_snr_phase(app_handle, phase, duration_ms);
+    let mut readings = Vec::new();
+    for _ in 0..duration_ms / SNR_READ_INTERVAL_MS {
+        tokio::time::sleep(Duration::from_millis(u64::from(SNR_READ_INTERVAL_MS))).await;
+        let level = recorder
+            .lock()
+            .map_err(|e| format!("Failed to lock recorder: {e}"))?
+            .current_level(SNR_READ_INTERVAL_MS);
+        readings.extend(level.map(|level| level.rms_dbfs));
+    }
+    if readings.is_empty() {
+        return Err("No input was metered; was the session closed?".to_string());
+    }
+    Ok(readings)
+}
+
+fn emit_snr_phase(app_handle: &AppHandle, phase: SnrPhase, duration_ms: u32) {
+    let payload = SnrMeasurementPhase { phase, duration_ms };
+    if let Err(e) = app_handle.emit(SNR_MEASUREMENT_PHASE, payload) {
+        warn!("Failed to emit {SNR_MEASUREMENT_PHASE}: {e}");
+    }
+}
+
+/// Smooth the live `mic-level` meter like an analog VU meter: it rises with
+/// the `attackMs` time constant and falls with `releaseMs` (each 1-5000 ms).
+/// Pass `null` for both to go back to the raw, unsmoothed level (the
+/// default). Takes effect immediately, including mid-recording.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_level_ballistics(
+    attack_ms: Option<u32>,
+    release_ms: Option<u32>,
+    recorder: State<'_, Mutex<Recorder>>,
+) -> Result<()> {
+    let ballistics = match (attack_ms, release_ms) {
+        (Some(attack_ms), Some(release_ms)) => Some(LevelBallistics::new(attack_ms, release_ms)?),
+        (None, None) => None,
+        _ => return Err("Set both attack and release, or neither".to_string()),
+    };
+    recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {e}"))?
+        .set_level_ballistics(ballistics);
+    info!("Level ballistics set: {ballistics:?}");
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_level_ballistics(
+    recorder: State<'_, Mutex<Recorder>>,
+) -> Result<Option<LevelBallistics>> {
+    let recorder = recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {e}"))?;
+    Ok(recorder.level_ballistics())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    #[test]
+    fn concurrent_session_inits_let_exactly_one_through() {
+        let lock = Arc::new(SessionInitLock::default());
+        let open_sessions = Arc::new(Mutex::new(Vec::new()));
+        let attempted = Arc::new(Barrier::new(2));
+
+        let results: Vec<Result<()>> = ["first", "second"]
+            .into_iter()
+            .map(|id| {
+                let (lock, open_sessions, attempted) =
+                    (lock.clone(), open_sessions.clone(), attempted.clone());
+                thread::spawn(move || {
+                    let claim = lock.claim();
+                    // The winner keeps its claim until both threads have
+                    // tried, so the other one always finds it held.
+                    attempted.wait();
+                    let _claim = claim?;
+                    // Stand-in for init_session: close whatever is open, then
+                    // open this call's session.
+                    let mut sessions = open_sessions.lock().unwrap();
+                    sessions.clear();
+                    sessions.push(id);
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+
+        let rejected: Vec<_> = results.iter().filter_map(|r| r.as_ref().err()).collect();
+        assert_eq!(rejected.len(), 1);
+        assert!(rejected[0].starts_with("SessionInitInProgress"));
+        assert_eq!(open_sessions.lock().unwrap().len(), 1);
+
+        // Released once the winning init finished, so the next init runs.
+        assert!(lock.claim().is_ok());
+    }
+
+    #[test]
+    fn memory_capture_stop_wakes_only_its_own_session() {
+        let memory = MemoryCapture::default();
+        // Stopped before the capture starts waiting, as a quick release does.
+        let stop = memory.begin("memory-1");
+        assert!(!memory.stop("other"));
+        assert!(memory.stop("memory-1"));
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let woke = runtime.block_on(async {
+            tokio::time::timeout(Duration::from_millis(100), stop.notified()).await
+        });
+        assert!(woke.is_ok());
+
+        memory.end();
+        assert!(!memory.stop("memory-1"));
+    }
+
+    #[test]
+    fn stop_memory_recording_wakes_the_capture_in_progress() {
+        let memory = MemoryCapture::default();
+        assert!(!memory.stop_any());
+
+        let stop = memory.begin("memory-1");
+        assert!(memory.is_capturing("memory-1"));
+        assert!(!memory.is_capturing("other"));
+        assert!(memory.stop_any());
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let woke = runtime.block_on(async {
+            tokio::time::timeout(Duration::from_millis(100), stop.notified()).await
+        });
+        assert!(woke.is_ok());
+
+        memory.end();
+        assert!(!memory.is_capturing("memory-1"));
+        assert!(!memory.stop_any());
+    }
+}
@@ -0,0 +1,409 @@
+//! Calibration and ballistics for the live `mic-level` meter.
+//!
+//! The consumer worker emits the RMS of recent audio; the overlay applies its
+//! own perceptual gain on top. That gain suits a typical headset mic, but a
+//! quiet lavalier barely moves the bars and a hot USB mic pins them. A
+//! `LevelSensitivity` scales the RMS before it is emitted and caps the
+//! result, so the meter can be calibrated per mic at runtime.
+//!
+//! Optional `LevelBallistics` then smooth the value like an analog VU meter:
+//! it rises with the `attack` time constant and falls with the (typically
+//! longer) `release` one, so the bars stop flickering between syllables.
+//! Without ballistics the calibrated RMS is emitted as is.
+//!
+//! None of this touches the recorded samples, only the metered value.
+//!
+//! `RecentAudio` is the pull-based side: the last second of metered input,
+//! from which `get_current_level` measures RMS and peak on demand for UIs
+//! that poll instead of subscribing to `mic-level`. It reports raw dBFS,
+//! before sensitivity and ballistics.
+//!
+//! `estimate_snr` turns a run of those readings, taken while the user stays
+//! silent and then while they speak, into a signal-to-noise estimate for
+//! `measure_snr`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const MULTIPLIER_RANGE: std::ops::RangeInclusive<f32> = 0.1..=100.0;
+const MAX_RANGE: std::ops::RangeInclusive<f32> = 0.01..=1.0;
+const TIME_CONSTANT_RANGE_MS: std::ops::RangeInclusive<u32> = 1..=5000;
+/// Longest window `get_current_level` can measure, and so how much input
+/// `RecentAudio` keeps.
+pub(crate) const MAX_LEVEL_WINDOW_MS: u32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelSensitivity {
+    /// Gain applied to the RMS before emitting (0.1-100).
+    pub multiplier: f32,
+    /// Ceiling on the emitted value (0.01-1.0).
+    pub max: f32,
+}
+
+impl Default for LevelSensitivity {
+    /// Unity gain and a full-scale ceiling: the raw RMS, as before this
+    /// setting existed.
+    fn default() -> Self {
+        Self {
+            multiplier: 1.0,
+            max: 1.0,
+        }
+    }
+}
+
+impl LevelSensitivity {
+    pub fn new(multiplier: f32, max: f32) -> Result<Self, String> {
+        if !MULTIPLIER_RANGE.contains(&multiplier) {
+            return Err(format!(
+                "Level multiplier must be between {} and {}, got {multiplier}",
+                MULTIPLIER_RANGE.start(),
+                MULTIPLIER_RANGE.end()
+            ));
+        }
+        if !MAX_RANGE.contains(&max) {
+            return Err(format!(
+                "Level max must be between {} and {}, got {max}",
+                MAX_RANGE.start(),
+                MAX_RANGE.end()
+            ));
+        }
+        Ok(Self { multiplier, max })
+    }
+
+    /// The metered value for a raw RMS amplitude.
+    pub fn apply(self, rms: f32) -> f32 {
+        (rms * self.multiplier).min(self.max)
+    }
+}
+
+/// Exponential smoothing time constants for a VU-style meter. Analog VU
+/// meters use roughly 300 ms both ways; a peak-program feel is closer to
+/// 10 ms attack and 500+ ms release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelBallistics {
+    /// Time constant while the level rises (1-5000 ms).
+    pub attack_ms: u32,
+    /// Time constant while the level falls (1-5000 ms).
+    pub release_ms: u32,
+}
+
+impl LevelBallistics {
+    pub fn new(attack_ms: u32, release_ms: u32) -> Result<Self, String> {
+        for (name, value) in [("attack", attack_ms), ("release", release_ms)] {
+            if !TIME_CONSTANT_RANGE_MS.contains(&value) {
+                return Err(format!(
+                    "Level {name} must be between {} and {} ms, got {value}",
+                    TIME_CONSTANT_RANGE_MS.start(),
+                    TIME_CONSTANT_RANGE_MS.end()
+                ));
+            }
+        }
+        Ok(Self {
+            attack_ms,
+            release_ms,
+        })
+    }
+}
+
+/// Meter settings shared between the command layer and the consumer worker.
+/// Stored in atomics (f32 as bits; 0 ms for "no ballistics") so the worker
+/// reads them without a lock on every emit, and an update lands
+/// mid-recording.
+#[derive(Debug, Clone)]
+pub(crate) struct SharedMeterSettings {
+    multiplier: Arc<AtomicU32>,
+    max: Arc<AtomicU32>,
+    attack_ms: Arc<AtomicU32>,
+    release_ms: Arc<AtomicU32>,
+}
+
+impl Default for SharedMeterSettings {
+    fn default() -> Self {
+        let defaults = LevelSensitivity::default();
+        Self {
+            multiplier: Arc::new(AtomicU32::new(defaults.multiplier.to_bits())),
+            max: Arc::new(AtomicU32::new(defaults.max.to_bits())),
+            attack_ms: Arc::new(AtomicU32::new(0)),
+            release_ms: Arc::new(AtomicU32::new(0)),
+        }
+    }
+}
+
+impl SharedMeterSettings {
+    pub(crate) fn sensitivity(&self) -> LevelSensitivity {
+        LevelSensitivity {
+            multiplier: f32::from_bits(self.multiplier.load(Ordering::Relaxed)),
+            max: f32::from_bits(self.max.load(Ordering::Relaxed)),
+        }
+    }
+
+    pub(crate) fn set_sensitivity(&self, sensitivity: LevelSensitivity) {
+        self.multiplier
+            .store(sensitivity.multiplier.to_bits(), Ordering::Relaxed);
+        self.max.store(sensitivity.max.to_bits(), Ordering::Relaxed);
+    }
+
+    pub(crate) fn ballistics(&self) -> Option<LevelBallistics> {
+        let attack_ms = self.attack_ms.load(Ordering::Relaxed);
+        let release_ms = self.release_ms.load(Ordering::Relaxed);
+        (attack_ms > 0 && release_ms > 0).then_some(LevelBallistics {
+            attack_ms,
+            release_ms,
+        })
+    }
+
+    pub(crate) fn set_ballistics(&self, ballistics: Option<LevelBallistics>) {
+        let (attack_ms, release_ms) = ballistics.map_or((0, 0), |b| (b.attack_ms, b.release_ms));
+        self.attack_ms.store(attack_ms, Ordering::Relaxed);
+        self.release_ms.store(release_ms, Ordering::Relaxed);
+    }
+}
+
+/// Per-session meter state: turns each raw RMS into the value to emit.
+pub(crate) struct LevelMeter {
+    settings: SharedMeterSettings,
+    smoothed: f32,
+}
+
+impl LevelMeter {
+    pub(crate) fn new(settings: SharedMeterSettings) -> Self {
+        Self {
+            settings,
+            smoothed: 0.0,
+        }
+    }
+
+    /// Start the next recording from a resting needle.
+    pub(crate) fn reset(&mut self) {
+        self.smoothed = 0.0;
+    }
+
+    /// Calibrate `rms`, then smooth it over the `elapsed` time since the
+    /// previous reading when ballistics are on.
+    pub(crate) fn next(&mut self, rms: f32, elapsed: Duration) -> f32 {
+        let level = self.settings.sensitivity().apply(rms);
+        self.smoothed = match self.settings.ballistics() {
+            Some(ballistics) => smooth(self.smoothed, level, elapsed, ballistics),
+            None => level,
+        };
+        self.smoothed
+    }
+}
+
+/// Level of the most recent input, in dBFS. `None` levels are digital
+/// silence.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentLevel {
+    pub rms_dbfs: Option<f32>,
+    pub peak_dbfs: Option<f32>,
+    /// Audio actually measured. Shorter than asked for right after metering
+    /// starts.
+    pub window_ms: u32,
+}
+
+/// The last `MAX_LEVEL_WINDOW_MS` of metered mono input at the device rate.
+/// The consumer worker pushes every metered buffer and clears it whenever
+/// metering stops, so a level read from it is never stale.
+#[derive(Debug, Clone)]
+pub(crate) struct RecentAudio {
+    samples: Arc<Mutex<VecDeque<f32>>>,
+    sample_rate: u32,
+}
+
+impl RecentAudio {
+    pub(crate) fn new(sample_rate: u32) -> Self {
+        Self {
+            samples: Arc::new(Mutex::new(VecDeque::new())),
+            sample_rate,
+        }
+    }
+
+    fn frames(&self, ms: u32) -> usize {
+        (u64::from(self.sample_rate) * u64::from(ms) / 1000) as usize
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<f32>> {
+        self.samples.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn push(&self, chunk: &[f32]) {
+        let capacity = self.frames(MAX_LEVEL_WINDOW_MS);
+        let mut samples = self.lock();
+        samples.extend(chunk);
+        let excess = samples.len().saturating_sub(capacity);
+        samples.drain(..excess);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// RMS and peak of the last `window_ms`, or `None` with nothing metered.
+    pub(crate) fn level(&self, window_ms: u32) -> Option<CurrentLevel> {
+        let samples = self.lock();
+        let take = self.frames(window_ms).min(samples.len());
+        if take == 0 {
+            return None;
+        }
+        let (mut sum_sq, mut peak) = (0f64, 0f32);
+        for &sample in samples.range(samples.len() - take..) {
+            sum_sq += f64::from(sample) * f64::from(sample);
+            peak = peak.max(sample.abs());
+        }
+        let to_dbfs = |amplitude: f64| (amplitude > 0.0).then(|| (20.0 * amplitude.log10()) as f32);
+        Some(CurrentLevel {
+            rms_dbfs: to_dbfs((sum_sq / take as f64).sqrt()),
+            peak_dbfs: to_dbfs(f64::from(peak)),
+            window_ms: (take as u64 * 1000 / u64::from(self.sample_rate)) as u32,
+        })
+    }
+}
+
+/// A room check from `measure_snr`, in RMS dBFS. `None` levels are digital
+/// silence: a muted or disconnected mic.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SnrMeasurement {
+    /// Level while the user was silent: room noise plus the mic's own hiss.
+    pub noise_floor_dbfs: Option<f32>,
+    /// Level of the louder half of the speaking phase, so the pauses
+    /// between words do not pull it toward the noise floor.
+    pub speech_level_dbfs: Option<f32>,
+    /// Speech level minus noise floor. Below about 15 dB transcription
+    /// suffers from room noise; `None` when either level is missing.
+    pub snr_db: Option<f32>,
+}
+
+/// Combine RMS readings (dBFS, `None` for digital silence) from the silent
+/// and speaking phases. Levels are averaged as power, not in dB, so one loud
+/// window counts for what it is.
+pub(crate) fn estimate_snr(silence: &[Option<f32>], speech: &[Option<f32>]) -> SnrMeasurement {
+    let power = |dbfs: Option<f32>| dbfs.map_or(0.0, |db| 10f64.powf(f64::from(db) / 10.0));
+    let mean_dbfs = |powers: &[f64]| {
+        let mean = powers.iter().sum::<f64>() / powers.len().max(1) as f64;
+        (mean > 0.0).then(|| (10.0 * mean.log10()) as f32)
+    };
+
+    let noise: Vec<f64> = silence.iter().map(|&db| power(db)).collect();
+    let mut voiced: Vec<f64> = speech.iter().map(|&db| power(db)).collect();
+    voiced.sort_by(|a, b| b.total_cmp(a));
+    voiced.truncate(voiced.len().div_ceil(2));
+
+    let noise_floor_dbfs = mean_dbfs(&noise);
+    let speech_level_dbfs = mean_dbfs(&voiced);
+    SnrMeasurement {
+        noise_floor_dbfs,
+        speech_level_dbfs,
+        snr_db: noise_floor_dbfs
+            .zip(speech_level_dbfs)
+            .map(|(noise, speech)| speech - noise),
+    }
+}
+
+/// One step of a one-pole follower: move from `current` toward `target` by
+/// the fraction a time constant of `attack`/`release` covers in `elapsed`.
+fn smooth(current: f32, target: f32, elapsed: Duration, ballistics: LevelBallistics) -> f32 {
+    let tau_ms = if target > current {
+        ballistics.attack_ms
+    } else {
+        ballistics.release_ms
+    };
+    let alpha = 1.0 - (-(elapsed.as_secs_f32() * 1000.0) / tau_ms as f32).exp();
+    current + (target - current) * alpha
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_then_caps_and_rejects_out_of_range_values() {
+        let sensitivity = LevelSensitivity::new(8.0, 0.5).unwrap();
+        assert_eq!(sensitivity.apply(0.05), 0.4);
+        assert_eq!(sensitivity.apply(0.2), 0.5);
+        assert_eq!(LevelSensitivity::default().apply(0.05), 0.05);
+
+        assert!(LevelSensitivity::new(0.0, 1.0).is_err());
+        assert!(LevelSensitivity::new(8.0, 1.5).is_err());
+        assert!(LevelSensitivity::new(f32::NAN, 1.0).is_err());
+
+        let shared = SharedMeterSettings::default();
+        shared.set_sensitivity(sensitivity);
+        assert_eq!(shared.sensitivity(), sensitivity);
+    }
+
+    #[test]
+    fn ballistics_rise_fast_and_fall_slow() {
+        let settings = SharedMeterSettings::default();
+        let mut meter = LevelMeter::new(settings.clone());
+        let tick = Duration::from_millis(50);
+
+        // Raw by default.
+        assert_eq!(meter.next(0.5, tick), 0.5);
+        assert_eq!(meter.next(0.0, tick), 0.0);
+
+        settings.set_ballistics(Some(LevelBallistics::new(10, 500).unwrap()));
+        let risen = meter.next(0.5, tick);
+        assert!(
+            risen > 0.49,
+            "attack should nearly reach the target: {risen}"
+        );
+        let fallen = meter.next(0.0, tick);
+        assert!(
+            (0.4..0.49).contains(&fallen),
+            "release should barely move in one tick: {fallen}"
+        );
+
+        assert!(LevelBallistics::new(0, 300).is_err());
+        settings.set_ballistics(None);
+        assert_eq!(settings.ballistics(), None);
+    }
+
+    #[test]
+    fn recent_audio_measures_only_the_requested_tail() {
+        let recent = RecentAudio::new(1000);
+        assert_eq!(recent.level(50), None);
+
+        recent.push(&[0.5; 1500]);
+        recent.push(&[0.0, 0.1, -0.1, 0.0]);
+
+        let tail = recent.level(4).unwrap();
+        assert_eq!(tail.window_ms, 4);
+        assert!((tail.peak_dbfs.unwrap() + 20.0).abs() < 1e-4);
+        assert!((tail.rms_dbfs.unwrap() + 23.01).abs() < 0.01);
+        // Capped at the last second, however much was pushed.
+        assert_eq!(recent.level(5000).unwrap().window_ms, MAX_LEVEL_WINDOW_MS);
+
+        recent.clear();
+        assert_eq!(recent.level(50), None);
+    }
+
+    #[test]
+    fn snr_uses_the_voiced_half_of_speech() {
+        // Pauses between words sit at the noise floor and must not count.
+        let silence = [Some(-60.0); 10];
+        let speech = [
+            Some(-20.0),
+            Some(-60.0),
+            Some(-20.0),
+            Some(-60.0),
+            Some(-20.0),
+            Some(-20.0),
+        ];
+
+        let snr = estimate_snr(&silence, &speech);
+        assert!((snr.noise_floor_dbfs.unwrap() + 60.0).abs() < 1e-3);
+        assert!((snr.speech_level_dbfs.unwrap() + 20.0).abs() < 1e-3);
+        assert!((snr.snr_db.unwrap() - 40.0).abs() < 1e-3);
+
+        let muted = estimate_snr(&[None; 4], &speech);
+        assert_eq!(muted.noise_floor_dbfs, None);
+        assert_eq!(muted.snr_db, None);
+    }
+}
@@ -1,11 +1,26 @@
 pub mod artifact;
 pub mod commands;
+pub mod level;
+pub mod naming;
+pub mod orphans;
 pub mod recorder;
+pub mod retention;
 
-pub use artifact::{read_artifact_samples, write_artifact, RecordingArtifact};
+pub use artifact::{
+    read_artifact_channel_samples, read_artifact_samples, read_artifact_samples_with,
+    write_artifact, RecordingArtifact, RecordingStats,
+};
 pub use commands::{
-    cancel_recording, clear_recording_artifacts, close_recording_session,
-    delete_recording_artifacts, enumerate_recording_devices, get_current_recording_id,
-    init_recording_session, start_recording, stop_recording,
+    cancel_recording, cleanup_recordings, clear_recording_artifacts, close_recording_session,
+    delete_recording_artifacts, enumerate_recording_devices, find_orphaned_recordings,
+    get_current_recording_id, get_level_ballistics, get_level_sensitivity, get_recording_latency,
+    get_stream_config, init_recording_session, list_recording_devices, set_level_ballistics,
+    set_level_sensitivity, start_recording, stop_recording, sync_recorder_state,
+};
+pub use level::{LevelBallistics, LevelSensitivity};
+pub use orphans::OrphanedRecordings;
+pub use recorder::{
+    Recorder, RecorderSnapshot, RecordingDevice, RecordingLatency, RecordingSessionOptions,
+    StreamConfigInfo,
 };
-pub use recorder::Recorder;
+pub use retention::RecordingCleanup;
@@ -0,0 +1,111 @@
+//! Readable recording file names from a template such as
+//! `{date}_{time}_{device}`.
+//!
+//! The recording id always stays at the front of the name, because both
+//! this module's readers and the JS blob store find a recording by the
+//! `{id}.` prefix. The rendered template is a label between the id and the
+//! extension: `{id}.2024-06-01_0930_Blue-Yeti.wav`. Since ids are unique,
+//! two recordings can never collide on a name, whatever the template says.
+//!
+//! Placeholders:
+//! - `{id}`: the recording id
+//! - `{date}`: local start date, `2024-06-01`
+//! - `{time}`: local start time, `0930`
+//! - `{device}`: name of the device the session opened
+//!
+//! The rendered label is sanitized into a single safe filename component:
+//! path separators, characters Windows forbids, and whitespace become `-`.
+
+use chrono::NaiveDateTime;
+
+/// Longest label kept; a long device name should not push the file name
+/// past filesystem limits.
+const MAX_LABEL_CHARS: usize = 100;
+
+/// Render `template` for one recording. Fails on an unknown placeholder or
+/// an unclosed `{`, so a typo surfaces at `init_recording_session` instead of
+/// silently ending up in file names. `None` when the label sanitizes to
+/// nothing, which means a plain `{id}.wav`.
+pub fn render_label(
+    template: &str,
+    id: &str,
+    started: NaiveDateTime,
+    device: &str,
+) -> Result<Option<String>, String> {
+    let mut label = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        label.push_str(&rest[..open]);
+        let close = rest[open..]
+            .find('}')
+            .ok_or_else(|| format!("Unclosed '{{' in filename template '{template}'"))?;
+        let value = match &rest[open + 1..open + close] {
+            "id" => id.to_string(),
+            "date" => started.format("%Y-%m-%d").to_string(),
+            "time" => started.format("%H%M").to_string(),
+            "device" => device.to_string(),
+            other => {
+                return Err(format!(
+                    "Unknown placeholder '{{{other}}}' in filename template; use {{id}}, {{date}}, {{time}}, or {{device}}"
+                ))
+            }
+        };
+        label.push_str(&value);
+        rest = &rest[open + close + 1..];
+    }
+    label.push_str(rest);
+
+    let label = sanitize(&label);
+    Ok((!label.is_empty()).then_some(label))
+}
+
+/// One filename component: unsafe characters and whitespace become `-`,
+/// runs of `-` collapse, and leading or trailing `-` and `.` go.
+fn sanitize(label: &str) -> String {
+    let mut out = String::with_capacity(label.len());
+    for c in label.chars() {
+        let unsafe_char = c.is_whitespace()
+            || c.is_control()
+            || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|');
+        let c = if unsafe_char { '-' } else { c };
+        if !(c == '-' && out.ends_with('-')) {
+            out.push(c);
+        }
+    }
+    let out: String = out
+        .trim_matches(|c| c == '-' || c == '.')
+        .chars()
+        .take(MAX_LABEL_CHARS)
+        .collect();
+    out.trim_end_matches(['-', '.']).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn june_first() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 6, 1)
+            .unwrap()
+            .and_hms_opt(9, 30, 12)
+            .unwrap()
+    }
+
+    #[test]
+    fn renders_placeholders_into_a_safe_label() {
+        let label = render_label("{date}_{time}_{device}", "rec1", june_first(), "Blue Yeti");
+        assert_eq!(label.unwrap().as_deref(), Some("2024-06-01_0930_Blue-Yeti"));
+
+        let label = render_label("{id} ../{device}", "rec1", june_first(), "Mic: \"USB\" / 2");
+        assert_eq!(label.unwrap().as_deref(), Some("rec1-..-Mic-USB-2"));
+
+        assert_eq!(render_label(" / ", "rec1", june_first(), ""), Ok(None));
+    }
+
+    #[test]
+    fn rejects_unknown_and_unclosed_placeholders() {
+        assert!(render_label("{dat}", "rec1", june_first(), "").is_err());
+        assert!(render_label("{date", "rec1", june_first(), "").is_err());
+    }
+}
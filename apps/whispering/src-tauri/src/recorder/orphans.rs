@@ -0,0 +1,147 @@
+//! Find recordings that no note refers to any more.
+//!
+//! In a notes-plus-audio workflow a note links its recording by id (in the
+//! body or the frontmatter). Deleting the note leaves the audio behind. This
+//! scans every markdown file under the notes folder for each artifact's id
+//! and reports the ids that appear nowhere, with the disk space they use.
+//! Matching is by substring, so a near-miss can only keep a recording, never
+//! mark a referenced one as orphaned.
+//!
+//! Deletion stays id-based: the caller hands the ids back to
+//! `delete_recording_artifacts`, so this module never deletes anything and
+//! the frontend still never gets a path-based delete. Any read error aborts
+//! the scan rather than skipping the file, because an unread note could be
+//! the one that references a recording.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::artifact::{recording_id_from_artifact_filename, recordings_dir};
+
+#[derive(Debug, Clone, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedRecordings {
+    /// Ids no note mentions, sorted.
+    pub recording_ids: Vec<String>,
+    /// Bytes freed by deleting every listed recording.
+    #[specta(type = specta_typescript::Number<u64>)]
+    pub reclaimable_bytes: u64,
+}
+
+/// Recordings in the app's recordings folder that no markdown file under
+/// `notes_dir` references.
+pub(super) fn find_orphans(
+    app: &AppHandle,
+    notes_dir: &Path,
+) -> Result<OrphanedRecordings, String> {
+    let artifacts = artifact_sizes(&recordings_dir(app)?)?;
+    unreferenced(artifacts, notes_dir)
+}
+
+/// Total artifact size per recording id. An id can own several files (a
+/// WAV plus a re-encoded copy); markdown sidecars are not artifacts.
+fn artifact_sizes(dir: &Path) -> Result<BTreeMap<String, u64>, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(format!("read recordings dir {}: {e}", dir.display())),
+    };
+    let mut sizes = BTreeMap::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("read recordings dir {}: {e}", dir.display()))?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let Some(id) = recording_id_from_artifact_filename(&name) else {
+            continue;
+        };
+        let meta = entry
+            .metadata()
+            .map_err(|e| format!("stat artifact {}: {e}", entry.path().display()))?;
+        if meta.is_file() {
+            *sizes.entry(id.to_string()).or_insert(0) += meta.len();
+        }
+    }
+    Ok(sizes)
+}
+
+/// Drop every id found in a markdown file under `notes_dir` from
+/// `candidates`. A file named after an id (`{id}.md`, the recording's own
+/// metadata sidecar) describes that recording rather than referring to it,
+/// so it does not count as a reference to its own id.
+fn unreferenced(
+    mut candidates: BTreeMap<String, u64>,
+    notes_dir: &Path,
+) -> Result<OrphanedRecordings, String> {
+    let mut stack = vec![notes_dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        if candidates.is_empty() {
+            break;
+        }
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| format!("read notes dir {}: {e}", dir.display()))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("read notes dir {}: {e}", dir.display()))?;
+            let path = entry.path();
+            let file_type = entry
+                .file_type()
+                .map_err(|e| format!("stat {}: {e}", path.display()))?;
+            if file_type.is_dir() {
+                stack.push(path);
+                continue;
+            }
+            if path.extension().and_then(|e| e.to_str()) != Some("md") {
+                continue;
+            }
+            let own_id = path.file_stem().and_then(|s| s.to_str());
+            let bytes =
+                std::fs::read(&path).map_err(|e| format!("read note {}: {e}", path.display()))?;
+            let contents = String::from_utf8_lossy(&bytes);
+            candidates
+                .retain(|id, _| Some(id.as_str()) == own_id || !contents.contains(id.as_str()));
+        }
+    }
+
+    Ok(OrphanedRecordings {
+        reclaimable_bytes: candidates.values().sum(),
+        recording_ids: candidates.into_keys().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_only_recordings_no_note_mentions() {
+        let root = std::env::temp_dir().join(format!("whispering-orphans-{}", std::process::id()));
+        let recordings = root.join("recordings");
+        let notes = root.join("notes");
+        std::fs::create_dir_all(&recordings).unwrap();
+        std::fs::create_dir_all(notes.join("2024")).unwrap();
+
+        std::fs::write(recordings.join("kept1.wav"), [0u8; 10]).unwrap();
+        std::fs::write(recordings.join("kept2.wav"), [0u8; 20]).unwrap();
+        std::fs::write(recordings.join("gone.wav"), [0u8; 30]).unwrap();
+        std::fs::write(recordings.join("gone.ogg"), [0u8; 5]).unwrap();
+        std::fs::write(recordings.join("gone.md"), "id: gone").unwrap();
+
+        std::fs::write(notes.join("a.md"), "---\nrecording: kept1\n---\nHello").unwrap();
+        std::fs::write(notes.join("2024").join("b.md"), "See [audio](kept2.wav)").unwrap();
+        std::fs::write(notes.join("gone.md"), "recording: gone").unwrap();
+        std::fs::write(notes.join("c.txt"), "gone").unwrap();
+
+        let orphans = unreferenced(artifact_sizes(&recordings).unwrap(), &notes).unwrap();
+        std::fs::remove_dir_all(&root).ok();
+
+        assert_eq!(
+            orphans,
+            OrphanedRecordings {
+                recording_ids: vec!["gone".to_string()],
+                reclaimable_bytes: 35,
+            }
+        );
+    }
+}
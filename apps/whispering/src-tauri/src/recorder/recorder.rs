@@ -5,28 +5,40 @@
 //! ┌────────────────────┐  mpsc  ┌─────────────────────┐
 //! │ build_input_stream │ ─────▶ │ run_consumer        │
 //! │  - downmix to mono │ chunks │  - accumulate Vec   │
-//! │  - sample_tx.send  │        │  - resample (final) │
+//! │  - try_send chunk  │        │  - resample (final) │
 //! └────────────────────┘        │  - pad short clips  │
 //!                               │  - emit artifact    │
 //!                               └─────────────────────┘
 //! ```
 //!
-//! The cpal callback never blocks: it downmixes to mono and ships
-//! samples through an mpsc channel. The consumer worker accumulates,
-//! resamples to 16 kHz at finalize, pads sub-1s clips, and hands the
-//! resulting `Vec<f32>` (mono 16 kHz PCM) back to the command layer,
+//! The cpal callback never blocks: it downmixes to mono (or, with
+//! `use_native_format`, keeps the device's channels) and ships
+//! samples through a bounded mpsc channel, dropping (and counting) a chunk
+//! rather than waiting when the consumer is that far behind. The consumer
+//! worker accumulates,
+//! resamples to 16 kHz at finalize (or keeps the device rate, with
+//! `use_native_format`), pads sub-1s clips, and hands the resulting
+//! `Vec<f32>` (mono PCM, or interleaved over the device's channels) back
+//! to the command layer,
 //! which writes the durable WAV artifact and emits the small handle JS
 //! sees over IPC.
 
+use chrono::Local;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, SampleFormat, Stream};
-use log::{debug, error, info};
-use std::sync::atomic::{AtomicBool, Ordering};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::{mpsc, Arc};
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 
+use super::level::{
+    CurrentLevel, LevelBallistics, LevelMeter, LevelSensitivity, RecentAudio, SharedMeterSettings,
+};
+use super::naming::render_label;
 use crate::audio::resample_mono;
 
 /// Simple result type using String for errors. Errors cross the IPC
@@ -39,6 +51,25 @@ pub type Result<T> = std::result::Result<T, String>;
 /// second resample step inside `audio::encode_pcm_to_opus_ogg`.
 const TARGET_RATE: u32 = 16_000;
 
+/// Preferred sample rates `init_session` accepts. Below 8 kHz speech loses
+/// the consonants transcription depends on; 384 kHz is the highest rate any
+/// audio interface offers.
+const SAMPLE_RATE_RANGE: std::ops::RangeInclusive<u32> = 8_000..=384_000;
+
+/// `buffer_frames` `init_session` accepts: a single frame per callback would
+/// wake the consumer at the sample rate, and past 64k frames (over a second
+/// at 48 kHz) the meter and auto-stop stop tracking the input.
+const BUFFER_FRAMES_RANGE: std::ops::RangeInclusive<u32> = 16..=65_536;
+
+/// Chunks the callback can queue ahead of the consumer: about ten seconds at
+/// a typical 10 ms buffer, far longer than any finalize or emit takes, while
+/// keeping a stalled consumer from growing memory without bound.
+const SAMPLE_QUEUE_CHUNKS: usize = 1_024;
+
+/// `input_gain` `init_session` accepts, about -20 to +24 dB. Past that a
+/// quiet mic's noise floor is boosted into the speech range anyway.
+const INPUT_GAIN_RANGE: std::ops::RangeInclusive<f32> = 0.1..=16.0;
+
 /// Overlay window label and event name for live mic levels. The recording
 /// overlay (a separate webview) renders these into its meter bars. Kept in
 /// sync with the JS window manager's `WINDOW_LABEL` and the `mic-level`
@@ -46,17 +77,113 @@ const TARGET_RATE: u32 = 16_000;
 const OVERLAY_WINDOW_LABEL: &str = "recording-overlay";
 const MIC_LEVEL_EVENT: &str = "mic-level";
 
+/// App-wide copy of the metered level, for simple indicators (a pulsing dot)
+/// outside the overlay. Same value and cadence as `MIC_LEVEL_EVENT`; only
+/// emitted when the session opts in via `emit_level_to_app`, so sessions
+/// without a subscriber do not broadcast 20 events a second to every window.
+const AUDIO_LEVEL_SINGLE_EVENT: &str = "audio-level-single";
+
 /// Minimum gap between mic-level emits. ~20 Hz is smooth for a meter and keeps
 /// the targeted Tauri event off the IPC hot path (per Tauri's guidance to
 /// throttle high-frequency events). Levels between emits are averaged, not
 /// dropped, so a brief loud transient still registers.
 const MIC_LEVEL_EMIT_INTERVAL: Duration = Duration::from_millis(50);
 
-/// Sub-1s recordings are padded to this many samples (at 16 kHz, so
-/// 1.25 s). Suppresses Whisper hallucination on near-silent short
-/// clips. Empty recordings (no samples ever delivered) are left empty.
+/// Sub-1s recordings are padded to this many frames (at 16 kHz, so
+/// 1.25 s; scaled for other artifact rates). Suppresses Whisper
+/// hallucination on near-silent short clips. Empty recordings (no samples
+/// ever delivered) are left empty.
 const SHORT_RECORDING_PAD_SAMPLES: usize = 20_000;
 
+/// Length of the linear ramp `fade_edges` applies at each end of a
+/// recording: long enough to remove the click of a cut mid-waveform, far
+/// shorter than any syllable.
+const EDGE_FADE: Duration = Duration::from_millis(5);
+
+/// Event emitted (app-wide) when the capture clock is measurably off its
+/// nominal rate. Carries `RateDriftPayload`.
+const RATE_DRIFT_EVENT: &str = "recording-rate-drift";
+
+/// Drift is only judged once this much wall-clock audio has arrived: over a
+/// few seconds, callback scheduling jitter dwarfs any real clock error.
+const DRIFT_MIN_ELAPSED: Duration = Duration::from_secs(30);
+
+/// How often the measured rate is recomputed after `DRIFT_MIN_ELAPSED`.
+const DRIFT_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Relative deviation past which the capture rate counts as drifting. 0.5%
+/// is 18 s of timestamp error over an hour, well above scheduling noise.
+const DRIFT_THRESHOLD: f64 = 0.005;
+
+/// Event emitted (app-wide) when `auto_stop_silence_ms` of continuous
+/// silence ends capture. Carries `AutoStoppedPayload`.
+const AUTO_STOPPED_EVENT: &str = "auto-stopped";
+
+/// RMS below which a level window counts as silence for auto-stop, about
+/// -40 dBFS: room tone and fan noise sit under it, quiet speech does not.
+const AUTO_STOP_SILENCE_RMS: f32 = 0.01;
+
+/// Event emitted (app-wide) when the input clips while metered, at most
+/// once per `CLIPPING_CHECK_INTERVAL`. Carries `ClippingPayload`.
+const CLIPPING_EVENT: &str = "clipping-detected";
+
+/// Magnitude from which a raw input sample counts as clipped. Matches the
+/// threshold of the `recording-complete` summary and `analyze_clipping`.
+const CLIP_THRESHOLD: f32 = 0.99;
+
+/// Window over which clipped samples are counted before `clipping-detected`
+/// is emitted. Long enough that a clipping voice does not flood the event,
+/// short enough that a gain hint shows up while the user is still speaking.
+const CLIPPING_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ClippingPayload {
+    /// Share of raw input samples (across all channels, 0.0-1.0) at or past
+    /// `CLIP_THRESHOLD` over the last check interval.
+    clipped_fraction: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AutoStoppedPayload {
+    silence_ms: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RateDriftPayload {
+    nominal_rate: u32,
+    measured_rate: f64,
+}
+
+/// Clipped input samples, counted by the stream callback and drained by the
+/// consumer. Counted on the raw interleaved samples: once a clipped channel
+/// is averaged with a quiet one it no longer reads as full scale. Relaxed
+/// atomic adds are wait-free, so the callback can afford them.
+#[derive(Debug, Clone, Default)]
+struct ClipCounter {
+    clipped: Arc<AtomicU64>,
+    total: Arc<AtomicU64>,
+}
+
+impl ClipCounter {
+    fn record(&self, samples: impl ExactSizeIterator<Item = f32>) {
+        let total = samples.len() as u64;
+        let clipped = samples.filter(|s| s.abs() >= CLIP_THRESHOLD).count() as u64;
+        self.clipped.fetch_add(clipped, Ordering::Relaxed);
+        self.total.fetch_add(total, Ordering::Relaxed);
+    }
+
+    /// Share of the samples recorded since the last call that clipped, or
+    /// `None` when none did.
+    fn take_fraction(&self) -> Option<f32> {
+        let clipped = self.clipped.swap(0, Ordering::Relaxed);
+        let total = self.total.swap(0, Ordering::Relaxed);
+        (clipped > 0).then(|| clipped as f32 / total.max(clipped) as f32)
+    }
+}
+
 /// Worker-thread command channel.
 #[derive(Debug)]
 enum RecorderCmd {
@@ -66,15 +193,188 @@ enum RecorderCmd {
     Shutdown,
 }
 
+/// Optional knobs for `init_session`. Every field defaults to "let the
+/// driver decide", so `None` from the frontend keeps the old behavior.
+#[derive(Debug, Clone, Default, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RecordingSessionOptions {
+    /// Frames per callback buffer. Smaller buffers make the level meter and
+    /// live captions react sooner, at the cost of more callback wakeups (CPU)
+    /// and a higher risk of overruns on a loaded machine. 16-65536; ignored,
+    /// with a warning, when the device does not support the requested size.
+    pub buffer_frames: Option<u32>,
+    /// Also emit each metered level app-wide on `audio-level-single`, not
+    /// just to the recording overlay.
+    pub emit_level_to_app: bool,
+    /// Stop capturing after this much continuous silence, for hands-free
+    /// dictation. Capture halts and `auto-stopped` is emitted; the audio so
+    /// far is kept (`RecorderSnapshot::auto_stopped`), and `stop_recording`
+    /// still writes it out as usual.
+    /// Silence counts from the start of the recording, so saying nothing at
+    /// all also stops it. `None` disables auto-stop.
+    pub auto_stop_silence_ms: Option<u32>,
+    /// Name the recording file from a template such as
+    /// `{date}_{time}_{device}`, so the recordings folder is browsable. The
+    /// id stays at the front (`{id}.2024-06-01_0930_Blue-Yeti.wav`); see
+    /// `naming.rs` for the placeholders. `None` keeps `{id}.wav`.
+    pub filename_template: Option<String>,
+    /// Multiply the input by this before it is recorded, metered, or checked
+    /// for clipping, for a mic that records too quietly. Boosted samples past
+    /// full scale are clamped. 0.1-16; `None` leaves the input as is.
+    pub input_gain: Option<f32>,
+    /// Capture in the device's highest-quality input config (its highest
+    /// supported rate, at its native channel count, in its most precise
+    /// sample format) and keep that rate and those channels in the
+    /// recording file instead of resampling to 16 kHz mono. Transcription
+    /// downmixes and resamples its own copy when it reads the file. Cannot
+    /// be combined with a preferred sample rate.
+    pub use_native_format: bool,
+    /// Ramp the first and last few milliseconds of the recording in and
+    /// out, so a start or stop that cuts the waveform does not click (or
+    /// show up as a transient at the edges of the transcript).
+    pub fade_edges: bool,
+    /// When the capture clock is measured to drift, resample the recording
+    /// from the measured rate so its duration matches the wall clock. Off by
+    /// default: frames lost to overruns or a stalled scheduler also read as
+    /// a slow clock, and resampling would then pitch-shift good audio. The
+    /// drift event and `measured_sample_rate` are reported either way.
+    pub correct_rate_drift: bool,
+}
+
+/// What cpal actually negotiated for the live stream, as opposed to what the
+/// frontend asked for. `buffer_frames` is read from the callback itself
+/// (frames per delivered buffer), so it is `None` until the first buffer
+/// arrives and reflects the driver's real choice under `BufferSize::Default`.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamConfigInfo {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// `"f32"`, `"i16"`, or `"u16"`.
+    pub sample_format: String,
+    pub buffer_frames: Option<u32>,
+}
+
+/// Everything a freshly loaded webview needs to rebuild its recorder view
+/// in one call, read from the Rust side that survived the reload.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RecorderSnapshot {
+    /// A session is open (input stream running), recording or not.
+    pub session_open: bool,
+    pub is_recording: bool,
+    /// Capture was ended by `auto_stop_silence_ms` and the recording is
+    /// still held, waiting for `stop_recording` to write it out. Such a
+    /// session is not armed: initializing a new one would discard the audio.
+    pub auto_stopped: bool,
+    /// Id of the open session, whether or not it is recording yet.
+    pub recording_id: Option<String>,
+    /// Time since `start_recording`; `None` when not recording.
+    pub elapsed_ms: Option<u32>,
+    /// Name of the device the session opened.
+    pub device_name: Option<String>,
+    pub stream_config: Option<StreamConfigInfo>,
+}
+
+/// Input latency of the live stream, for tuning how quickly levels and
+/// captions follow speech. `estimated_ms` is the driver-reported latency
+/// (capture to callback) when the backend provides capture timestamps, and
+/// one buffer period otherwise; `note` says which.
+#[derive(Debug, Clone, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingLatency {
+    pub sample_rate: u32,
+    /// Frames per delivered buffer; `None` until the first buffer arrives.
+    pub buffer_frames: Option<u32>,
+    /// Smallest and largest buffer the device accepts, when it says.
+    pub min_buffer_frames: Option<u32>,
+    pub max_buffer_frames: Option<u32>,
+    /// One buffer period: `buffer_frames / sample_rate`.
+    pub buffer_latency_ms: Option<f32>,
+    /// Time from capture to callback reported by the driver, for the most
+    /// recent buffer. `None` when the backend does not timestamp captures.
+    pub driver_latency_ms: Option<f32>,
+    pub estimated_ms: Option<f32>,
+    /// Set when `estimated_ms` is an approximation or unavailable.
+    pub note: Option<String>,
+}
+
+/// An input device as listed by `Recorder::list_devices`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingDevice {
+    /// What `init_session` takes to open this device. The device's name,
+    /// or for the second and later of several devices sharing a name, the
+    /// name plus its position among them (`USB Audio Device#2`); see
+    /// `device_ids`.
+    pub id: String,
+    pub name: String,
+    /// The device the OS currently routes default input to. Of several
+    /// devices sharing the default's name, only the first is marked: the
+    /// OS reports the default by name alone.
+    pub is_default: bool,
+}
+
+/// Negotiated parameters of the active stream plus the callback-updated
+/// buffer size. Held by `Recorder` for the lifetime of a session.
+struct ActiveStreamConfig {
+    sample_rate: u32,
+    channels: u16,
+    sample_format: SampleFormat,
+    callback_frames: Arc<AtomicU32>,
+    /// Capture-to-callback delay of the latest buffer in microseconds; 0
+    /// when the backend reports no capture timestamp.
+    driver_latency_us: Arc<AtomicU32>,
+    /// Device's supported buffer range, when it reports one.
+    buffer_range: Option<(u32, u32)>,
+    /// Stream errors reported by the driver since the last `start_recording`.
+    stream_errors: Arc<AtomicU32>,
+    /// Capture rate measured by the drift monitor for the current or last
+    /// recording, in Hz; 0 while no drift has been detected.
+    measured_rate: Arc<AtomicU32>,
+    /// Frames the callback dropped on a full sample queue since the last
+    /// `start_recording`.
+    dropped_frames: Arc<AtomicU32>,
+}
+
 /// CPAL-backed audio recorder. Owns the consumer worker, the command
 /// channel, and the cpal stream's join handle for the active session.
 pub struct Recorder {
     cmd_tx: Option<mpsc::Sender<RecorderCmd>>,
     worker_handle: Option<JoinHandle<()>>,
     is_recording: Arc<AtomicBool>,
+    /// Set by the worker when auto-stop ends capture but keeps the audio,
+    /// until `stop_recording` takes it. Fresh per session like
+    /// `is_recording`.
+    auto_stopped: Arc<AtomicBool>,
+    /// Meter the open session's input while not recording, so the overlay
+    /// can pulse with the user's voice before they start. Fresh per session
+    /// like `is_recording`, and cleared by the worker when recording starts.
+    idle_levels: Arc<AtomicBool>,
+    /// Recent metered input of the open session, for `current_level`.
+    recent_audio: Option<RecentAudio>,
     /// Id passed in at `init_session`. Surfaced by `get_current_recording_id`
     /// so a reloaded webview can reattach to the still-live Rust session.
     current_recording_id: Option<String>,
+    /// Name of the device the open session captures from.
+    device_name: Option<String>,
+    /// When the current recording started; `None` when not recording.
+    recording_started: Option<Instant>,
+    /// `RecordingSessionOptions::filename_template` of the open session.
+    filename_template: Option<String>,
+    /// The rendered template for the current or last recording of the
+    /// session, fixed when it starts so the file is named after its start.
+    artifact_label: Option<String>,
+    /// Rate of the PCM `stop_recording` returns: 16 kHz, or the device rate
+    /// for a `use_native_format` session.
+    artifact_rate: u32,
+    /// Channels of the PCM `stop_recording` returns, interleaved: 1, or the
+    /// device's channel count for a `use_native_format` session.
+    artifact_channels: u16,
+    stream_config: Option<ActiveStreamConfig>,
+    /// Meter calibration and ballistics. Outlive sessions, and the live
+    /// worker reads them on every emit, so a change applies mid-recording.
+    meter_settings: SharedMeterSettings,
 }
 
 impl Recorder {
@@ -83,22 +383,59 @@ impl Recorder {
             cmd_tx: None,
             worker_handle: None,
             is_recording: Arc::new(AtomicBool::new(false)),
+            auto_stopped: Arc::new(AtomicBool::new(false)),
+            idle_levels: Arc::new(AtomicBool::new(false)),
+            recent_audio: None,
             current_recording_id: None,
+            device_name: None,
+            recording_started: None,
+            filename_template: None,
+            artifact_label: None,
+            artifact_rate: TARGET_RATE,
+            artifact_channels: 1,
+            stream_config: None,
+            meter_settings: SharedMeterSettings::default(),
         }
     }
 
-    /// List available recording devices by name.
+    /// List available recording devices by name. On Windows this includes
+    /// every output device as `"[Loopback] {name}"`, which records what that
+    /// device plays.
     pub fn enumerate_devices(&self) -> Result<Vec<String>> {
         let host = cpal::default_host();
-        let devices = host
+        #[allow(unused_mut)]
+        let mut devices: Vec<String> = host
             .input_devices()
             .map_err(|e| format!("Failed to get input devices: {e}"))?
             .filter_map(|device| device.name().ok())
             .collect();
 
+        #[cfg(target_os = "windows")]
+        match host.output_devices() {
+            Ok(outputs) => devices.extend(
+                outputs
+                    .filter_map(|device| device.name().ok())
+                    .map(|name| format!("{LOOPBACK_PREFIX}{name}")),
+            ),
+            Err(e) => warn!("Failed to list output devices for loopback: {e}"),
+        }
+
         Ok(devices)
     }
 
+    /// List recording devices in a stable order: the system default first,
+    /// then the rest alphabetically. Driver order can change between calls,
+    /// which made a dropdown built from `enumerate_devices` reshuffle.
+    /// Devices sharing a name are all listed, with distinct ids.
+    pub fn list_devices(&self) -> Result<Vec<RecordingDevice>> {
+        let host = cpal::default_host();
+        let default_name = host.default_input_device().and_then(|d| d.name().ok());
+        Ok(sort_devices(
+            self.enumerate_devices()?,
+            default_name.as_deref(),
+        ))
+    }
+
     /// Initialize a recording session and spawn the consumer worker.
     ///
     /// The cpal stream comes up immediately (mic permission prompt fires
@@ -110,30 +447,84 @@ impl Recorder {
         device_name: String,
         recording_id: String,
         preferred_sample_rate: Option<u32>,
+        options: RecordingSessionOptions,
         app_handle: AppHandle,
     ) -> Result<()> {
+        // Reject a bad request before the open session is torn down for it.
+        validate_recording_format(&recording_id, preferred_sample_rate, &options)?;
+
         // Clean up any existing session before standing up a new one.
         self.close_session()?;
 
         let host = cpal::default_host();
         let device = find_device(&host, &device_name)?;
-        let config = get_optimal_config(&device, preferred_sample_rate)?;
+        let opened_device_name = device.name().ok();
+        let config = if matches!(
+            DeviceSelector::parse(&device_name),
+            DeviceSelector::Loopback(_)
+        ) {
+            // Shared-mode loopback only delivers the output's mix format, so
+            // the preferred rate cannot apply; the audio is resampled later.
+            device
+                .default_output_config()
+                .map_err(|e| format!("Failed to get loopback config: {e}"))?
+        } else if options.use_native_format {
+            get_native_config(&device)?
+        } else {
+            get_optimal_config(&device, preferred_sample_rate)?
+        };
         let sample_format = config.sample_format();
         let device_rate = config.sample_rate().0;
         let device_channels = config.channels();
+        let (artifact_rate, artifact_channels) = if options.use_native_format {
+            (device_rate, device_channels)
+        } else {
+            (TARGET_RATE, 1)
+        };
+        let keep_channels = artifact_channels > 1;
 
         let stream_config = cpal::StreamConfig {
             channels: device_channels,
             sample_rate: cpal::SampleRate(device_rate),
-            buffer_size: cpal::BufferSize::Default,
+            buffer_size: resolve_buffer_size(options.buffer_frames, config.buffer_size()),
         };
 
         // Fresh atomic each session so a stale clone from the previous
         // worker can never flip a new stream's gate.
         self.is_recording = Arc::new(AtomicBool::new(false));
         let is_recording = self.is_recording.clone();
+        self.auto_stopped = Arc::new(AtomicBool::new(false));
+        let auto_stopped = self.auto_stopped.clone();
+        self.idle_levels = Arc::new(AtomicBool::new(false));
+        let idle_levels = self.idle_levels.clone();
+        let callback_frames = Arc::new(AtomicU32::new(0));
+        let stream_callback_frames = callback_frames.clone();
+        let driver_latency_us = Arc::new(AtomicU32::new(0));
+        let stream_driver_latency_us = driver_latency_us.clone();
+        let buffer_range = match config.buffer_size() {
+            cpal::SupportedBufferSize::Range { min, max } => Some((*min, *max)),
+            cpal::SupportedBufferSize::Unknown => None,
+        };
+        let stream_errors = Arc::new(AtomicU32::new(0));
+        let stream_error_count = stream_errors.clone();
+        let measured_rate = Arc::new(AtomicU32::new(0));
+        let consumer_measured_rate = measured_rate.clone();
+        let dropped_frames = Arc::new(AtomicU32::new(0));
+        let stream_dropped_frames = dropped_frames.clone();
+        let clips = ClipCounter::default();
+        let stream_clips = clips.clone();
+        let meter = LevelMeter::new(self.meter_settings.clone());
+        let recent_audio = RecentAudio::new(device_rate);
+        let consumer_recent_audio = recent_audio.clone();
+        let emit_level_to_app = options.emit_level_to_app;
+        let input_gain = options.input_gain.unwrap_or(1.0);
+        let fade_edges = options.fade_edges;
+        let correct_rate_drift = options.correct_rate_drift;
+        let auto_stop_after = options
+            .auto_stop_silence_ms
+            .map(|ms| Duration::from_millis(u64::from(ms)));
 
-        let (sample_tx, sample_rx) = mpsc::channel::<Vec<f32>>();
+        let (sample_tx, sample_rx) = mpsc::sync_channel::<Vec<f32>>(SAMPLE_QUEUE_CHUNKS);
         let (cmd_tx, cmd_rx) = mpsc::channel::<RecorderCmd>();
 
         let worker_handle = thread::spawn(move || {
@@ -145,7 +536,14 @@ impl Recorder {
                 &stream_config,
                 sample_format,
                 device_channels,
+                keep_channels,
                 sample_tx,
+                stream_dropped_frames,
+                stream_callback_frames,
+                stream_driver_latency_us,
+                stream_error_count,
+                stream_clips,
+                input_gain,
             ) {
                 Ok(s) => s,
                 Err(e) => {
@@ -160,13 +558,47 @@ impl Recorder {
             }
 
             info!("Audio stream started successfully");
-            run_consumer(sample_rx, cmd_rx, device_rate, is_recording, app_handle);
+            run_consumer(
+                sample_rx,
+                cmd_rx,
+                device_rate,
+                artifact_rate,
+                artifact_channels,
+                is_recording,
+                auto_stopped,
+                idle_levels,
+                meter,
+                consumer_recent_audio,
+                emit_level_to_app,
+                auto_stop_after,
+                fade_edges,
+                correct_rate_drift,
+                consumer_measured_rate,
+                clips,
+                app_handle,
+            );
             drop(stream);
         });
 
         self.cmd_tx = Some(cmd_tx);
         self.worker_handle = Some(worker_handle);
+        self.recent_audio = Some(recent_audio);
         self.current_recording_id = Some(recording_id);
+        self.device_name = opened_device_name;
+        self.filename_template = options.filename_template;
+        self.artifact_rate = artifact_rate;
+        self.artifact_channels = artifact_channels;
+        self.stream_config = Some(ActiveStreamConfig {
+            sample_rate: device_rate,
+            channels: device_channels,
+            sample_format,
+            callback_frames,
+            driver_latency_us,
+            buffer_range,
+            stream_errors,
+            measured_rate,
+            dropped_frames,
+        });
 
         info!(
             "Recording session initialized: {} Hz, {} channels",
@@ -182,16 +614,35 @@ impl Recorder {
             .cmd_tx
             .as_ref()
             .ok_or_else(|| "No recording session initialized".to_string())?;
+        if let Some(config) = &self.stream_config {
+            config.stream_errors.store(0, Ordering::Relaxed);
+            config.measured_rate.store(0, Ordering::Relaxed);
+            config.dropped_frames.store(0, Ordering::Relaxed);
+        }
+        // Rendered before capture starts so a bad template cannot leave a
+        // recording running behind an error.
+        let label = match (&self.filename_template, &self.current_recording_id) {
+            (Some(template), Some(id)) => render_label(
+                template,
+                id,
+                Local::now().naive_local(),
+                self.device_name.as_deref().unwrap_or_default(),
+            )?,
+            _ => None,
+        };
         let (reply_tx, reply_rx) = mpsc::channel();
         tx.send(RecorderCmd::Start(reply_tx))
             .map_err(|e| format!("Failed to send start command: {e}"))?;
         reply_rx
             .recv()
             .map_err(|e| format!("Failed to receive start confirmation: {e}"))?;
+        self.recording_started = Some(Instant::now());
+        self.artifact_label = label;
         Ok(())
     }
 
-    /// Stop recording and consume the worker's mono 16 kHz PCM.
+    /// Stop recording and consume the worker's PCM, at `artifact_rate` and
+    /// interleaved over `artifact_channels`.
     pub fn stop_recording(&mut self) -> Result<Vec<f32>> {
         let tx = self
             .cmd_tx
@@ -200,6 +651,7 @@ impl Recorder {
         let (reply_tx, reply_rx) = mpsc::channel();
         tx.send(RecorderCmd::Stop(reply_tx))
             .map_err(|e| format!("Failed to send stop command: {e}"))?;
+        self.recording_started = None;
         reply_rx
             .recv()
             .map_err(|e| format!("Worker dropped stop reply: {e}"))?
@@ -224,20 +676,155 @@ impl Recorder {
         if let Some(handle) = self.worker_handle.take() {
             let _ = handle.join();
         }
+        self.recent_audio = None;
         self.current_recording_id = None;
+        self.device_name = None;
+        self.recording_started = None;
+        self.filename_template = None;
+        self.artifact_label = None;
+        self.artifact_rate = TARGET_RATE;
+        self.artifact_channels = 1;
+        self.stream_config = None;
         debug!("Recording session closed");
         Ok(())
     }
 
     /// Recording id of the active session, if any. Surfaced for the JS
-    /// reload-reattach path: only returns while the recorder is actively
-    /// capturing, so a stopped-but-not-closed session does not look live.
+    /// reload-reattach path: only returns while the session holds a
+    /// recording (capturing, or auto-stopped with the audio kept), so a
+    /// stopped-but-not-closed session does not look live.
     pub fn get_current_recording_id(&self) -> Option<String> {
-        if self.is_recording.load(Ordering::Acquire) {
-            self.current_recording_id.clone()
-        } else {
-            None
+        self.current_recording_id
+            .clone()
+            .filter(|_| self.has_recording())
+    }
+
+    /// Whether the session holds a recording `stop_recording` would write:
+    /// still capturing, or auto-stopped with the audio kept.
+    pub fn has_recording(&self) -> bool {
+        self.is_recording.load(Ordering::Acquire) || self.auto_stopped.load(Ordering::Acquire)
+    }
+
+    /// Stop the session's recording, if it holds one, and return it with
+    /// its id. `None` when there is nothing to save.
+    pub fn take_recording(&mut self) -> Result<Option<(String, Vec<f32>)>> {
+        match self.session_id().filter(|_| self.has_recording()) {
+            Some(id) => Ok(Some((id, self.stop_recording()?))),
+            None => Ok(None),
+        }
+    }
+
+    /// Negotiated config of the live stream, or `None` with no session.
+    pub fn stream_config(&self) -> Option<StreamConfigInfo> {
+        self.stream_config.as_ref().map(|config| {
+            let frames = config.callback_frames.load(Ordering::Relaxed);
+            StreamConfigInfo {
+                sample_rate: config.sample_rate,
+                channels: config.channels,
+                sample_format: format!("{:?}", config.sample_format).to_lowercase(),
+                buffer_frames: (frames > 0).then_some(frames),
+            }
+        })
+    }
+
+    /// Authoritative recorder state, for a reloaded frontend to resync from.
+    pub fn snapshot(&self) -> RecorderSnapshot {
+        let is_recording = self.is_recording.load(Ordering::Acquire);
+        RecorderSnapshot {
+            session_open: self.cmd_tx.is_some(),
+            is_recording,
+            auto_stopped: self.auto_stopped.load(Ordering::Acquire),
+            recording_id: self.current_recording_id.clone(),
+            elapsed_ms: self
+                .recording_started
+                .filter(|_| is_recording)
+                .map(|started| started.elapsed().as_millis() as u32),
+            device_name: self.device_name.clone(),
+            stream_config: self.stream_config(),
+        }
+    }
+
+    /// Input latency of the live stream, or `None` with no session.
+    pub fn latency(&self) -> Option<RecordingLatency> {
+        self.stream_config.as_ref().map(|config| {
+            let frames = config.callback_frames.load(Ordering::Relaxed);
+            let driver_us = config.driver_latency_us.load(Ordering::Relaxed);
+            estimate_latency(
+                config.sample_rate,
+                (frames > 0).then_some(frames),
+                config.buffer_range,
+                (driver_us > 0).then_some(driver_us),
+            )
+        })
+    }
+
+    /// Turn metering of the open session's input on or off while it is not
+    /// recording. Starting a recording turns it off (the recording meters
+    /// the input itself), and closing the session ends it.
+    pub fn set_idle_levels(&self, enabled: bool) -> Result<()> {
+        if self.cmd_tx.is_none() {
+            return Err("No recording session initialized".to_string());
         }
+        self.idle_levels.store(enabled, Ordering::Release);
+        Ok(())
+    }
+
+    /// Whether idle metering is on for the open session.
+    pub fn idle_levels_enabled(&self) -> bool {
+        self.idle_levels.load(Ordering::Acquire)
+    }
+
+    /// RMS and peak of the last `window_ms` of input, while recording or idle
+    /// metering is on. `None` otherwise, and before the first buffer.
+    pub fn current_level(&self, window_ms: u32) -> Option<CurrentLevel> {
+        let metering =
+            self.is_recording.load(Ordering::Acquire) || self.idle_levels.load(Ordering::Acquire);
+        self.recent_audio
+            .as_ref()
+            .filter(|_| metering)
+            .and_then(|recent| recent.level(window_ms))
+    }
+
+    /// Stream errors (overruns, device hiccups) the driver reported since the
+    /// current or last recording started. Zero with no session.
+    pub fn dropout_count(&self) -> u32 {
+        self.stream_config
+            .as_ref()
+            .map_or(0, |config| config.stream_errors.load(Ordering::Relaxed))
+    }
+
+    /// Frames lost to a full sample queue (the consumer falling more than
+    /// `SAMPLE_QUEUE_CHUNKS` behind) since the current or last recording
+    /// started. Zero with no session.
+    pub fn dropped_frames(&self) -> u32 {
+        self.stream_config
+            .as_ref()
+            .map_or(0, |config| config.dropped_frames.load(Ordering::Relaxed))
+    }
+
+    /// Capture rate the drift monitor measured for the current or last
+    /// recording, when it was off the nominal rate past the threshold.
+    pub fn measured_rate(&self) -> Option<u32> {
+        self.stream_config
+            .as_ref()
+            .map(|config| config.measured_rate.load(Ordering::Relaxed))
+            .filter(|&rate| rate > 0)
+    }
+
+    pub fn level_sensitivity(&self) -> LevelSensitivity {
+        self.meter_settings.sensitivity()
+    }
+
+    pub fn set_level_sensitivity(&self, sensitivity: LevelSensitivity) {
+        self.meter_settings.set_sensitivity(sensitivity);
+    }
+
+    pub fn level_ballistics(&self) -> Option<LevelBallistics> {
+        self.meter_settings.ballistics()
+    }
+
+    pub fn set_level_ballistics(&self, ballistics: Option<LevelBallistics>) {
+        self.meter_settings.set_ballistics(ballistics);
     }
 
     /// Session id without the is_recording gate. Used by `stop_recording`
@@ -246,6 +833,23 @@ impl Recorder {
     pub fn session_id(&self) -> Option<String> {
         self.current_recording_id.clone()
     }
+
+    /// File name label for the recording, from the session's
+    /// `filename_template`. `None` without a template.
+    pub fn artifact_label(&self) -> Option<String> {
+        self.artifact_label.clone()
+    }
+
+    /// Sample rate of the PCM `stop_recording` returns for the open session.
+    pub fn artifact_rate(&self) -> u32 {
+        self.artifact_rate
+    }
+
+    /// Channel count of the PCM `stop_recording` returns for the open
+    /// session.
+    pub fn artifact_channels(&self) -> u16 {
+        self.artifact_channels
+    }
 }
 
 impl Drop for Recorder {
@@ -254,16 +858,32 @@ impl Drop for Recorder {
     }
 }
 
-/// Consumer worker entrypoint. Accumulates mono samples, resamples to
-/// 16 kHz at finalize, pads short clips, emits the artifact. While recording,
-/// also emits a throttled RMS level to the overlay window so its meter can
-/// reflect live mic activity (the JS side never sees the PCM, so the level has
-/// to originate here).
+/// Consumer worker entrypoint. Accumulates samples (interleaved over
+/// `channels`), resamples to `artifact_rate` at finalize, pads short clips,
+/// emits the artifact. While recording, also emits a throttled RMS level to
+/// the overlay window so its meter can reflect live mic activity (the JS
+/// side never sees the PCM, so the level has to originate here). The same
+/// level windows drive auto-stop, and the clipped samples the callback
+/// counts are reported once a second. Between recordings the level is still
+/// metered while `idle_levels` is set, but nothing is buffered.
+#[allow(clippy::too_many_arguments)]
 fn run_consumer(
     sample_rx: mpsc::Receiver<Vec<f32>>,
     cmd_rx: mpsc::Receiver<RecorderCmd>,
     device_rate: u32,
+    artifact_rate: u32,
+    channels: u16,
     is_recording: Arc<AtomicBool>,
+    auto_stopped: Arc<AtomicBool>,
+    idle_levels: Arc<AtomicBool>,
+    mut meter: LevelMeter,
+    recent_audio: RecentAudio,
+    emit_level_to_app: bool,
+    auto_stop_after: Option<Duration>,
+    fade_edges: bool,
+    correct_rate_drift: bool,
+    measured_rate: Arc<AtomicU32>,
+    clips: ClipCounter,
     app_handle: AppHandle,
 ) {
     use std::sync::mpsc::RecvTimeoutError;
@@ -274,6 +894,9 @@ fn run_consumer(
     let mut level_sumsq = 0f64;
     let mut level_count = 0usize;
     let mut last_level_emit = Instant::now();
+    let mut drift = RateDriftMonitor::new(device_rate);
+    let mut silence = auto_stop_after.map(SilenceTimer::new);
+    let mut last_clip_check = Instant::now();
 
     loop {
         // Command channel has priority. Stop should respond fast even
@@ -283,26 +906,44 @@ fn run_consumer(
                 RecorderCmd::Start(reply) => {
                     recording = true;
                     is_recording.store(true, Ordering::Release);
+                    auto_stopped.store(false, Ordering::Release);
+                    idle_levels.store(false, Ordering::Release);
                     buffer.clear();
                     level_sumsq = 0.0;
                     level_count = 0;
                     last_level_emit = Instant::now();
+                    meter.reset();
+                    drift = RateDriftMonitor::new(device_rate);
+                    silence = auto_stop_after.map(SilenceTimer::new);
                     let _ = reply.send(());
                     continue;
                 }
                 RecorderCmd::Stop(reply) => {
                     is_recording.store(false, Ordering::Release);
-                    let result = finalize(std::mem::take(&mut buffer), device_rate);
+                    auto_stopped.store(false, Ordering::Release);
+                    let captured_rate = match drift.effective_rate() {
+                        Some(rate) if correct_rate_drift => rate,
+                        _ => device_rate,
+                    };
+                    let result = finalize(
+                        std::mem::take(&mut buffer),
+                        channels,
+                        captured_rate,
+                        artifact_rate,
+                        fade_edges,
+                    );
                     let _ = reply.send(result);
                     return;
                 }
                 RecorderCmd::Cancel(reply) => {
                     is_recording.store(false, Ordering::Release);
+                    auto_stopped.store(false, Ordering::Release);
                     let _ = reply.send(Ok(()));
                     return;
                 }
                 RecorderCmd::Shutdown => {
                     is_recording.store(false, Ordering::Release);
+                    auto_stopped.store(false, Ordering::Release);
                     return;
                 }
             }
@@ -310,23 +951,95 @@ fn run_consumer(
 
         match sample_rx.recv_timeout(Duration::from_millis(20)) {
             Ok(samples) => {
+                let metering = recording || idle_levels.load(Ordering::Acquire);
+                if !metering {
+                    // Start the next idle window fresh rather than from
+                    // whatever was left when metering stopped.
+                    level_sumsq = 0.0;
+                    level_count = 0;
+                    last_level_emit = Instant::now();
+                    recent_audio.clear();
+                    clips.take_fraction();
+                    last_clip_check = Instant::now();
+                    continue;
+                }
+                // Metering, auto-stop and drift work on frames; only the
+                // buffer keeps the channels of a native-format session.
+                let mono = match channels {
+                    1 => Cow::Borrowed(samples.as_slice()),
+                    n => Cow::Owned(downmix_f32(&samples, n.into())),
+                };
+                recent_audio.push(&mono);
+                for &sample in mono.iter() {
+                    level_sumsq += (sample as f64) * (sample as f64);
+                }
+                level_count += mono.len();
+
                 if recording {
-                    for &sample in &samples {
-                        level_sumsq += (sample as f64) * (sample as f64);
-                    }
-                    level_count += samples.len();
                     buffer.extend_from_slice(&samples);
 
-                    if last_level_emit.elapsed() >= MIC_LEVEL_EMIT_INTERVAL && level_count > 0 {
-                        let rms = (level_sumsq / level_count as f64).sqrt() as f32;
-                        // Targeted emit to the overlay only; no error if it is
-                        // not open (e.g. overlay disabled), and never fatal.
-                        let _ = app_handle.emit_to(OVERLAY_WINDOW_LABEL, MIC_LEVEL_EVENT, rms);
-                        level_sumsq = 0.0;
-                        level_count = 0;
-                        last_level_emit = Instant::now();
+                    let detected = drift.on_chunk(mono.len(), Instant::now());
+                    if let Some(rate) = drift.effective_rate() {
+                        measured_rate.store(rate, Ordering::Relaxed);
+                    }
+                    if let Some(measured_rate) = detected {
+                        warn!(
+                            "Capture rate drift: nominal {device_rate} Hz, measured {measured_rate:.1} Hz"
+                        );
+                        let _ = app_handle.emit(
+                            RATE_DRIFT_EVENT,
+                            RateDriftPayload {
+                                nominal_rate: device_rate,
+                                measured_rate,
+                            },
+                        );
                     }
                 }
+
+                if last_level_emit.elapsed() >= MIC_LEVEL_EMIT_INTERVAL && level_count > 0 {
+                    let rms = (level_sumsq / level_count as f64).sqrt() as f32;
+                    let level = meter.next(rms, last_level_emit.elapsed());
+                    // Targeted emit to the overlay only; no error if it is
+                    // not open (e.g. overlay disabled), and never fatal.
+                    let _ = app_handle.emit_to(OVERLAY_WINDOW_LABEL, MIC_LEVEL_EVENT, level);
+                    if emit_level_to_app {
+                        let _ = app_handle.emit(AUDIO_LEVEL_SINGLE_EVENT, level);
+                    }
+                    let window = Duration::from_secs_f64(level_count as f64 / device_rate as f64);
+                    if let Some(silent_for) = silence
+                        .as_mut()
+                        .filter(|_| recording)
+                        .and_then(|timer| timer.on_window(rms, window))
+                    {
+                        // Stop taking samples but keep the buffer: the
+                        // frontend follows up with `stop_recording`.
+                        info!(
+                            "Auto-stopping after {}ms of silence",
+                            silent_for.as_millis()
+                        );
+                        recording = false;
+                        hold_auto_stopped(&is_recording, &auto_stopped);
+                        let payload = AutoStoppedPayload {
+                            silence_ms: silent_for.as_millis() as u32,
+                        };
+                        if let Err(e) = app_handle.emit(AUTO_STOPPED_EVENT, payload) {
+                            warn!("Failed to emit {AUTO_STOPPED_EVENT}: {e}");
+                        }
+                    }
+                    level_sumsq = 0.0;
+                    level_count = 0;
+                    last_level_emit = Instant::now();
+                }
+
+                if last_clip_check.elapsed() >= CLIPPING_CHECK_INTERVAL {
+                    if let Some(clipped_fraction) = clips.take_fraction() {
+                        let payload = ClippingPayload { clipped_fraction };
+                        if let Err(e) = app_handle.emit(CLIPPING_EVENT, payload) {
+                            warn!("Failed to emit {CLIPPING_EVENT}: {e}");
+                        }
+                    }
+                    last_clip_check = Instant::now();
+                }
             }
             Err(RecvTimeoutError::Timeout) => continue,
             Err(RecvTimeoutError::Disconnected) => return,
@@ -334,44 +1047,438 @@ fn run_consumer(
     }
 }
 
-/// Resample to 16 kHz if needed, pad short clips, build the samples.
-fn finalize(buffer: Vec<f32>, device_rate: u32) -> Result<Vec<f32>> {
-    let samples = if device_rate == TARGET_RATE {
+/// Mark capture as auto-stopped with its audio kept. `auto_stopped` goes up
+/// before `is_recording` comes down, so a reader never sees a held
+/// recording as no recording at all.
+fn hold_auto_stopped(is_recording: &AtomicBool, auto_stopped: &AtomicBool) {
+    auto_stopped.store(true, Ordering::Release);
+    is_recording.store(false, Ordering::Release);
+}
+
+/// Consecutive silence across level windows, measured in audio time (frames
+/// over the device rate) so a stalled consumer catching up cannot fake a
+/// long pause.
+struct SilenceTimer {
+    limit: Duration,
+    silent_for: Duration,
+}
+
+impl SilenceTimer {
+    fn new(limit: Duration) -> Self {
+        Self {
+            limit,
+            silent_for: Duration::ZERO,
+        }
+    }
+
+    /// Feed one level window's raw RMS and length. Returns the silence so
+    /// far once it reaches the limit.
+    fn on_window(&mut self, rms: f32, window: Duration) -> Option<Duration> {
+        if rms < AUTO_STOP_SILENCE_RMS {
+            self.silent_for += window;
+        } else {
+            self.silent_for = Duration::ZERO;
+        }
+        (self.silent_for >= self.limit).then_some(self.silent_for)
+    }
+}
+
+/// Compares frames delivered against wall-clock time to estimate the real
+/// capture rate. Some hardware clocks run measurably fast or slow, so a file
+/// labelled 48 kHz holds slightly more or fewer frames per real second, and
+/// timestamps drift over a long recording. The clock starts at the first
+/// chunk after `Start` (that chunk's frames were captured before it, so they
+/// are not counted), which keeps stream start-up latency out of the estimate.
+struct RateDriftMonitor {
+    nominal_rate: u32,
+    first_chunk_at: Option<Instant>,
+    frames_since_first: u64,
+    last_check: Option<Instant>,
+    /// Last measured rate that exceeded `DRIFT_THRESHOLD`, if any.
+    drifting_rate: Option<f64>,
+}
+
+impl RateDriftMonitor {
+    fn new(nominal_rate: u32) -> Self {
+        Self {
+            nominal_rate,
+            first_chunk_at: None,
+            frames_since_first: 0,
+            last_check: None,
+            drifting_rate: None,
+        }
+    }
+
+    /// Record a delivered chunk. Returns the measured rate the first time it
+    /// crosses `DRIFT_THRESHOLD`; later checks only refresh the stored rate.
+    fn on_chunk(&mut self, frames: usize, now: Instant) -> Option<f64> {
+        let Some(first) = self.first_chunk_at else {
+            self.first_chunk_at = Some(now);
+            return None;
+        };
+        self.frames_since_first += frames as u64;
+
+        let elapsed = now.duration_since(first);
+        if elapsed < DRIFT_MIN_ELAPSED {
+            return None;
+        }
+        if self
+            .last_check
+            .is_some_and(|last| now.duration_since(last) < DRIFT_CHECK_INTERVAL)
+        {
+            return None;
+        }
+        self.last_check = Some(now);
+
+        let measured = self.frames_since_first as f64 / elapsed.as_secs_f64();
+        let deviation = (measured - self.nominal_rate as f64).abs() / self.nominal_rate as f64;
+        if deviation <= DRIFT_THRESHOLD {
+            return None;
+        }
+        let first_detection = self.drifting_rate.is_none();
+        self.drifting_rate = Some(measured);
+        first_detection.then_some(measured)
+    }
+
+    /// The measured rate, rounded, once drift was detected. With
+    /// `correct_rate_drift` the buffer is treated as this rate, so the
+    /// resampled artifact's duration (and every timestamp derived from it)
+    /// matches the wall clock. `None` means the nominal rate is trustworthy.
+    fn effective_rate(&self) -> Option<u32> {
+        self.drifting_rate.map(|rate| rate.round() as u32)
+    }
+}
+
+/// Resample from the rate the buffer was captured at to `artifact_rate` if
+/// needed, fade the edges if asked, pad short clips, build the samples.
+/// `buffer` and the result are interleaved over `channels`.
+fn finalize(
+    buffer: Vec<f32>,
+    channels: u16,
+    device_rate: u32,
+    artifact_rate: u32,
+    fade_edges: bool,
+) -> Result<Vec<f32>> {
+    let channels = usize::from(channels.max(1));
+    let samples = if device_rate == artifact_rate {
         buffer
     } else {
-        resample_mono(buffer, device_rate, TARGET_RATE)
-            .map_err(|e| format!("resample failed: {e}"))?
+        resample_interleaved(buffer, channels, device_rate, artifact_rate)?
     };
 
     let mut samples = samples;
-    let samples_per_second = TARGET_RATE as usize;
-    if !samples.is_empty()
-        && samples.len() < samples_per_second
-        && samples.len() < SHORT_RECORDING_PAD_SAMPLES
-    {
-        samples.resize(SHORT_RECORDING_PAD_SAMPLES, 0.0);
+    if fade_edges {
+        fade_edges_linear(&mut samples, artifact_rate, channels);
+    }
+    let frames = samples.len() / channels;
+    let frames_per_second = artifact_rate as usize;
+    let pad_frames = (SHORT_RECORDING_PAD_SAMPLES as u64 * u64::from(artifact_rate)
+        / u64::from(TARGET_RATE)) as usize;
+    if frames > 0 && frames < frames_per_second && frames < pad_frames {
+        samples.resize(pad_frames * channels, 0.0);
     }
 
     Ok(samples)
 }
 
-/// Find a recording device by name. Treats "default" case-insensitively.
+/// `resample_mono` over each channel of an interleaved buffer.
+fn resample_interleaved(
+    buffer: Vec<f32>,
+    channels: usize,
+    from_rate: u32,
+    to_rate: u32,
+) -> Result<Vec<f32>> {
+    let resample = |samples| {
+        resample_mono(samples, from_rate, to_rate).map_err(|e| format!("resample failed: {e}"))
+    };
+    if channels == 1 {
+        return resample(buffer);
+    }
+    let resampled = (0..channels)
+        .map(|channel| {
+            let samples = buffer.iter().skip(channel).step_by(channels).copied();
+            resample(samples.collect())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let frames = resampled.iter().map(Vec::len).min().unwrap_or(0);
+    Ok((0..frames)
+        .flat_map(|frame| resampled.iter().map(move |channel| channel[frame]))
+        .collect())
+}
+
+/// Legacy spelling of the default-device sentinel. Older frontends sent this
+/// literal, in any case ("Default" too); it is still honored, but only after
+/// an exact device-name match fails, so a device that is genuinely named
+/// "default" stays selectable.
+const LEGACY_DEFAULT_DEVICE: &str = "default";
+
+/// Prefix marking an output device to record through loopback, e.g.
+/// `"[Loopback] Speakers"`, so meeting audio coming out of the speakers can
+/// be transcribed.
+const LOOPBACK_PREFIX: &str = "[Loopback] ";
+
+/// Separates a device name from its position among devices of that name in
+/// a device id, e.g. `USB Audio Device#2`.
+const DUPLICATE_ID_SEPARATOR: char = '#';
+
+/// Which input device `init_session` should open. The wire value is a plain
+/// string so the IPC shape is unchanged: the empty string selects the system
+/// default, a `LOOPBACK_PREFIX` id selects an output device to capture
+/// through loopback, and anything else is a device id (see `device_ids`). Nothing is
+/// string-matched against a (possibly translated) "default" label, so a
+/// device called "Default" or "Default Microphone" is never mistaken for the
+/// sentinel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeviceSelector<'a> {
+    Default,
+    Named(&'a str),
+    /// Output device name, with `LOOPBACK_PREFIX` stripped.
+    Loopback(&'a str),
+}
+
+impl<'a> DeviceSelector<'a> {
+    fn parse(device_name: &'a str) -> Self {
+        if device_name.is_empty() {
+            DeviceSelector::Default
+        } else if let Some(output_name) = device_name.strip_prefix(LOOPBACK_PREFIX) {
+            DeviceSelector::Loopback(output_name)
+        } else {
+            DeviceSelector::Named(device_name)
+        }
+    }
+}
+
+/// The output device to capture through loopback. WASAPI records an output
+/// device's mix when an input stream is built on it (cpal sets
+/// `AUDCLNT_STREAMFLAGS_LOOPBACK`); no other cpal host can, so elsewhere
+/// this explains the platform's own route instead.
+#[cfg(target_os = "windows")]
+fn find_loopback_device(host: &cpal::Host, output_name: &str) -> Result<Device> {
+    let outputs = host
+        .output_devices()
+        .map_err(|e| format!("Failed to get output devices: {e}"))?;
+    find_by_id(outputs, output_name)
+        .ok_or_else(|| format!("Output device '{output_name}' not found for loopback"))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn find_loopback_device(_host: &cpal::Host, _output_name: &str) -> Result<Device> {
+    Err(if cfg!(target_os = "macos") {
+        "Loopback capture is only built in on Windows. On macOS, install a virtual audio device \
+         such as BlackHole, route the output to it, and record from it as an input."
+    } else {
+        "Loopback capture is only built in on Windows. On Linux, select the output's monitor \
+         source as the default input (pavucontrol, Recording tab) and record from the default device."
+    }
+    .to_string())
+}
+
+fn default_input_device(host: &cpal::Host) -> Result<Device> {
+    host.default_input_device()
+        .ok_or_else(|| "No default input device available".to_string())
+}
+
+/// Ids for devices with `names`, in driver order. A device's id is its name,
+/// except that the n-th device (n >= 2) sharing a name gets `name#n`, so two
+/// identical USB adapters can be told apart. The first keeps the plain name,
+/// so a name saved before duplicates were distinguished still selects the
+/// device it did. An id that would equal another device's actual name is
+/// skipped for the next number, keeping every id unique.
+///
+/// cpal 0.16 exposes no persistent device identifier, so the number is the
+/// device's position in driver order: stable while the same devices stay
+/// plugged into the same ports.
+fn device_ids(names: &[String]) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::with_capacity(names.len());
+    for name in names {
+        let mut id = name.clone();
+        let mut n = 1;
+        while ids.contains(&id) || (n > 1 && names.contains(&id)) {
+            n += 1;
+            id = format!("{name}{DUPLICATE_ID_SEPARATOR}{n}");
+        }
+        ids.push(id);
+    }
+    ids
+}
+
+/// The device among `devices` whose `device_ids` entry is `id`. Devices
+/// whose name cannot be read are skipped, as they are when listing.
+fn find_by_id(devices: impl Iterator<Item = Device>, id: &str) -> Option<Device> {
+    let mut named: Vec<(String, Device)> = devices
+        .filter_map(|device| Some((device.name().ok()?, device)))
+        .collect();
+    let names: Vec<String> = named.iter().map(|(name, _)| name.clone()).collect();
+    let index = device_ids(&names)
+        .iter()
+        .position(|candidate| candidate == id)?;
+    Some(named.swap_remove(index).1)
+}
+
+/// Assign ids, mark `default_name`, and order the list default-first, then by
+/// name ignoring case (ties broken by exact name). Devices sharing a name
+/// keep their driver order, so their ids read `name`, `name#2`, ...
+fn sort_devices(names: Vec<String>, default_name: Option<&str>) -> Vec<RecordingDevice> {
+    let mut devices: Vec<RecordingDevice> = device_ids(&names)
+        .into_iter()
+        .zip(names)
+        .map(|(id, name)| RecordingDevice {
+            is_default: Some(id.as_str()) == default_name,
+            id,
+            name,
+        })
+        .collect();
+    devices.sort_by(|a, b| {
+        b.is_default
+            .cmp(&a.is_default)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    devices
+}
+
+/// Find a recording device by selector. An id match always wins; the legacy
+/// `"default"` literal only falls back to the system default when no device
+/// carries that exact name.
 fn find_device(host: &cpal::Host, device_name: &str) -> Result<Device> {
-    if device_name.to_lowercase() == "default" {
-        return host
-            .default_input_device()
-            .ok_or_else(|| "No default input device available".to_string());
+    let id = match DeviceSelector::parse(device_name) {
+        DeviceSelector::Default => return default_input_device(host),
+        DeviceSelector::Named(id) => id,
+        DeviceSelector::Loopback(output_name) => return find_loopback_device(host, output_name),
+    };
+
+    let devices = host.input_devices().map_err(|e| e.to_string())?;
+    if let Some(device) = find_by_id(devices, id) {
+        return Ok(device);
     }
 
-    let devices: Vec<_> = host.input_devices().map_err(|e| e.to_string())?.collect();
-    for device in devices {
-        if let Ok(name) = device.name() {
-            if name == device_name {
-                return Ok(device);
-            }
+    if id.eq_ignore_ascii_case(LEGACY_DEFAULT_DEVICE) {
+        return default_input_device(host);
+    }
+    Err(format!("Device '{id}' not found"))
+}
+
+/// Combine the buffer period and the driver-reported latency into one
+/// estimate. The driver figure already includes the buffering, so it wins
+/// when present rather than being added to the period.
+fn estimate_latency(
+    sample_rate: u32,
+    buffer_frames: Option<u32>,
+    buffer_range: Option<(u32, u32)>,
+    driver_latency_us: Option<u32>,
+) -> RecordingLatency {
+    let buffer_latency_ms =
+        buffer_frames.map(|frames| frames as f32 * 1000.0 / sample_rate.max(1) as f32);
+    let driver_latency_ms = driver_latency_us.map(|us| us as f32 / 1000.0);
+    let (estimated_ms, note) = match (driver_latency_ms, buffer_latency_ms) {
+        (Some(driver), _) => (Some(driver), None),
+        (None, Some(buffer)) => (
+            Some(buffer),
+            Some(
+                "Estimated from the buffer size; the driver does not report capture timestamps, \
+                 so OS and hardware buffering are not included"
+                    .to_string(),
+            ),
+        ),
+        (None, None) => (
+            None,
+            Some("No audio buffer has arrived yet; query again once recording".to_string()),
+        ),
+    };
+    RecordingLatency {
+        sample_rate,
+        buffer_frames,
+        min_buffer_frames: buffer_range.map(|(min, _)| min),
+        max_buffer_frames: buffer_range.map(|(_, max)| max),
+        buffer_latency_ms,
+        driver_latency_ms,
+        estimated_ms,
+        note,
+    }
+}
+
+/// Check the requested session format before any device is touched, so a
+/// bad value fails here with the field named (`sampleRate: ...`) instead of
+/// being silently replaced by a driver default or failing at stop. The
+/// device may still pick another rate than `preferred_sample_rate`; only
+/// values no device could honor are rejected.
+pub(crate) fn validate_recording_format(
+    recording_id: &str,
+    preferred_sample_rate: Option<u32>,
+    options: &RecordingSessionOptions,
+) -> Result<()> {
+    if let Some(rate) = preferred_sample_rate.filter(|rate| !SAMPLE_RATE_RANGE.contains(rate)) {
+        return Err(format!(
+            "sampleRate: must be between {} and {} Hz, got {rate}",
+            SAMPLE_RATE_RANGE.start(),
+            SAMPLE_RATE_RANGE.end()
+        ));
+    }
+    if let Some(frames) = options
+        .buffer_frames
+        .filter(|frames| !BUFFER_FRAMES_RANGE.contains(frames))
+    {
+        return Err(format!(
+            "bufferFrames: must be between {} and {}, got {frames}",
+            BUFFER_FRAMES_RANGE.start(),
+            BUFFER_FRAMES_RANGE.end()
+        ));
+    }
+    if let Some(gain) = options
+        .input_gain
+        .filter(|gain| !INPUT_GAIN_RANGE.contains(gain))
+    {
+        return Err(format!(
+            "inputGain: must be between {} and {}, got {gain}",
+            INPUT_GAIN_RANGE.start(),
+            INPUT_GAIN_RANGE.end()
+        ));
+    }
+    if options.auto_stop_silence_ms == Some(0) {
+        return Err(
+            "autoStopSilenceMs: must be positive; leave it unset to disable auto-stop".to_string(),
+        );
+    }
+    if options.use_native_format && preferred_sample_rate.is_some() {
+        return Err(
+            "useNativeFormat: records at the device's own rate; leave sampleRate unset".to_string(),
+        );
+    }
+    if let Some(template) = &options.filename_template {
+        render_label(template, recording_id, Local::now().naive_local(), "")
+            .map_err(|e| format!("filenameTemplate: {e}"))?;
+    }
+    Ok(())
+}
+
+/// `BufferSize::Fixed(requested)` when the device advertises a range that
+/// contains it. Anything else falls back to the driver default: an
+/// unsupported fixed size makes `build_input_stream` fail outright, which is
+/// a worse outcome than a little extra latency.
+fn resolve_buffer_size(
+    requested: Option<u32>,
+    supported: &cpal::SupportedBufferSize,
+) -> cpal::BufferSize {
+    let Some(frames) = requested else {
+        return cpal::BufferSize::Default;
+    };
+    match supported {
+        cpal::SupportedBufferSize::Range { min, max } if (*min..=*max).contains(&frames) => {
+            cpal::BufferSize::Fixed(frames)
+        }
+        cpal::SupportedBufferSize::Range { min, max } => {
+            warn!(
+                "Requested buffer of {frames} frames is outside the supported range {min}..={max}; using the driver default"
+            );
+            cpal::BufferSize::Default
+        }
+        cpal::SupportedBufferSize::Unknown => {
+            warn!(
+                "Device does not report supported buffer sizes; ignoring requested {frames} frames"
+            );
+            cpal::BufferSize::Default
         }
     }
-    Err(format!("Device '{device_name}' not found"))
 }
 
 /// Get the best supported configuration for voice recording.
@@ -455,56 +1562,203 @@ fn get_optimal_config(
     best_config.ok_or_else(|| "Failed to find suitable audio configuration".to_string())
 }
 
+/// Get the device's highest-quality input configuration, for
+/// `use_native_format`. The native channel count is that of the device's
+/// default input config, the layout the OS runs it in.
+fn get_native_config(device: &Device) -> Result<cpal::SupportedStreamConfig> {
+    let native_channels = device
+        .default_input_config()
+        .ok()
+        .map(|config| config.channels());
+    let configs: Vec<_> = device
+        .supported_input_configs()
+        .map_err(|e| e.to_string())?
+        .collect();
+    native_config(native_channels, &configs).ok_or_else(|| {
+        "No configurations with supported sample formats (F32, I16, U16)".to_string()
+    })
+}
+
+/// The config with the highest rate (capped at `SAMPLE_RATE_RANGE`) at
+/// `native_channels`, in the most precise format the callback can take at
+/// that rate (F32 over I16 over U16). Falls back to any channel count when
+/// none is known or the device lists no config with it.
+fn native_config(
+    native_channels: Option<u16>,
+    configs: &[cpal::SupportedStreamConfigRange],
+) -> Option<cpal::SupportedStreamConfig> {
+    let format_rank = |format: SampleFormat| match format {
+        SampleFormat::F32 => Some(2),
+        SampleFormat::I16 => Some(1),
+        SampleFormat::U16 => Some(0),
+        _ => None,
+    };
+    configs
+        .iter()
+        .filter_map(|config| {
+            let rank = format_rank(config.sample_format())?;
+            let rate = config.max_sample_rate().0.min(*SAMPLE_RATE_RANGE.end());
+            let config = config.try_with_sample_rate(cpal::SampleRate(rate))?;
+            let native = Some(config.channels()) == native_channels;
+            Some((config, (native, rate, rank)))
+        })
+        .max_by_key(|(_, key)| *key)
+        .map(|(config, _)| config)
+}
+
 /// Build the cpal input stream. The callback's only job is to downmix to
-/// mono f32 and send the chunk down `sample_tx`; the consumer worker owns
-/// everything else.
+/// mono f32 (or convert to f32 and keep the channels, with
+/// `keep_channels`) and send the chunk down `sample_tx` (see
+/// `capture_chunk`); the consumer worker owns everything else.
+#[allow(clippy::too_many_arguments)]
 fn build_input_stream(
     device: &Device,
     config: &cpal::StreamConfig,
     sample_format: SampleFormat,
     channels: u16,
-    sample_tx: mpsc::Sender<Vec<f32>>,
+    keep_channels: bool,
+    sample_tx: mpsc::SyncSender<Vec<f32>>,
+    dropped_frames: Arc<AtomicU32>,
+    callback_frames: Arc<AtomicU32>,
+    driver_latency_us: Arc<AtomicU32>,
+    stream_errors: Arc<AtomicU32>,
+    clips: ClipCounter,
+    gain: f32,
 ) -> Result<Stream> {
-    let err_fn = |err| error!("Audio stream error: {err}");
+    let err_fn = move |err| {
+        stream_errors.fetch_add(1, Ordering::Relaxed);
+        error!("Audio stream error: {err}");
+    };
     let n_channels = channels as usize;
+    // A relaxed atomic store is wait-free, so recording the buffer size and
+    // latency is safe on the real-time callback thread. Backends without
+    // capture timestamps report capture == callback, which stores 0.
+    let record_buffer = move |len: usize, info: &cpal::InputCallbackInfo| {
+        callback_frames.store((len / n_channels.max(1)) as u32, Ordering::Relaxed);
+        let timestamp = info.timestamp();
+        let latency = timestamp
+            .callback
+            .duration_since(&timestamp.capture)
+            .unwrap_or_default();
+        driver_latency_us.store(
+            latency.as_micros().min(u32::MAX as u128) as u32,
+            Ordering::Relaxed,
+        );
+    };
 
     let stream = match sample_format {
-        SampleFormat::F32 => device
-            .build_input_stream(
-                config,
-                move |data: &[f32], _: &_| {
-                    let _ = sample_tx.send(downmix_f32(data, n_channels));
-                },
-                err_fn,
-                None,
-            )
-            .map_err(|e| format!("Failed to build F32 stream: {e}"))?,
-        SampleFormat::I16 => device
-            .build_input_stream(
-                config,
-                move |data: &[i16], _: &_| {
-                    let _ = sample_tx.send(downmix_i16(data, n_channels));
-                },
-                err_fn,
-                None,
-            )
-            .map_err(|e| format!("Failed to build I16 stream: {e}"))?,
-        SampleFormat::U16 => device
-            .build_input_stream(
-                config,
-                move |data: &[u16], _: &_| {
-                    let _ = sample_tx.send(downmix_u16(data, n_channels));
-                },
-                err_fn,
-                None,
-            )
-            .map_err(|e| format!("Failed to build U16 stream: {e}"))?,
+        SampleFormat::F32 => {
+            let mut capture = capture_chunk(
+                sample_tx,
+                dropped_frames,
+                clips,
+                gain,
+                n_channels,
+                keep_channels,
+                |s: f32| s,
+                downmix_f32,
+            );
+            device
+                .build_input_stream(
+                    config,
+                    move |data: &[f32], info: &cpal::InputCallbackInfo| {
+                        record_buffer(data.len(), info);
+                        capture(data);
+                    },
+                    err_fn,
+                    None,
+                )
+                .map_err(|e| format!("Failed to build F32 stream: {e}"))?
+        }
+        SampleFormat::I16 => {
+            let mut capture = capture_chunk(
+                sample_tx,
+                dropped_frames,
+                clips,
+                gain,
+                n_channels,
+                keep_channels,
+                i16_to_f32,
+                downmix_i16,
+            );
+            device
+                .build_input_stream(
+                    config,
+                    move |data: &[i16], info: &cpal::InputCallbackInfo| {
+                        record_buffer(data.len(), info);
+                        capture(data);
+                    },
+                    err_fn,
+                    None,
+                )
+                .map_err(|e| format!("Failed to build I16 stream: {e}"))?
+        }
+        SampleFormat::U16 => {
+            let mut capture = capture_chunk(
+                sample_tx,
+                dropped_frames,
+                clips,
+                gain,
+                n_channels,
+                keep_channels,
+                u16_to_f32,
+                downmix_u16,
+            );
+            device
+                .build_input_stream(
+                    config,
+                    move |data: &[u16], info: &cpal::InputCallbackInfo| {
+                        record_buffer(data.len(), info);
+                        capture(data);
+                    },
+                    err_fn,
+                    None,
+                )
+                .map_err(|e| format!("Failed to build U16 stream: {e}"))?
+        }
         _ => return Err(format!("Unsupported sample format: {sample_format:?}")),
     };
 
     Ok(stream)
 }
 
+/// The input callback's per-buffer work: count clipping, downmix (unless
+/// `keep_channels`), apply gain, and queue the chunk for the consumer.
+///
+/// It must never block: it takes no locks, and `try_send` returns at once
+/// even when the consumer is busy (finalizing, emitting levels) or stalled.
+/// Once `SAMPLE_QUEUE_CHUNKS` are waiting, further chunks are dropped and
+/// their frames added to `dropped_frames`, so the audio thread keeps its
+/// deadline and a stall costs audio it can report instead of unbounded
+/// memory. Keep it that way when adding per-chunk work here.
+#[allow(clippy::too_many_arguments)]
+fn capture_chunk<T: Copy>(
+    sample_tx: mpsc::SyncSender<Vec<f32>>,
+    dropped_frames: Arc<AtomicU32>,
+    clips: ClipCounter,
+    gain: f32,
+    n_channels: usize,
+    keep_channels: bool,
+    to_f32: fn(T) -> f32,
+    downmix: fn(&[T], usize) -> Vec<f32>,
+) -> impl FnMut(&[T]) {
+    move |data| {
+        clips.record(data.iter().map(|&s| to_f32(s) * gain));
+        let chunk = if keep_channels {
+            data.iter().map(|&s| to_f32(s)).collect()
+        } else {
+            downmix(data, n_channels)
+        };
+        let chunk = amplify(chunk, gain);
+        let frames = (data.len() / n_channels.max(1)) as u32;
+        // A disconnected receiver means the session is closing; nothing is
+        // recording, so nothing is lost.
+        if let Err(mpsc::TrySendError::Full(_)) = sample_tx.try_send(chunk) {
+            dropped_frames.fetch_add(frames, Ordering::Relaxed);
+        }
+    }
+}
+
 fn downmix_f32(interleaved: &[f32], channels: usize) -> Vec<f32> {
     if channels <= 1 {
         return interleaved.to_vec();
@@ -516,29 +1770,65 @@ fn downmix_f32(interleaved: &[f32], channels: usize) -> Vec<f32> {
 }
 
 fn downmix_i16(interleaved: &[i16], channels: usize) -> Vec<f32> {
-    let scale = 1.0 / i16::MAX as f32;
     if channels <= 1 {
-        return interleaved.iter().map(|&s| s as f32 * scale).collect();
+        return interleaved.iter().copied().map(i16_to_f32).collect();
     }
     interleaved
         .chunks_exact(channels)
-        .map(|frame| frame.iter().map(|&s| s as f32 * scale).sum::<f32>() / channels as f32)
+        .map(|frame| frame.iter().copied().map(i16_to_f32).sum::<f32>() / channels as f32)
         .collect()
 }
 
 fn downmix_u16(interleaved: &[u16], channels: usize) -> Vec<f32> {
-    // u16 PCM: midpoint is 32768. Normalize to [-1, 1] via (x / max) * 2 - 1.
-    let half = u16::MAX as f32 * 0.5;
-    let to_f32 = |s: u16| (s as f32 / half) - 1.0;
     if channels <= 1 {
-        return interleaved.iter().copied().map(to_f32).collect();
+        return interleaved.iter().copied().map(u16_to_f32).collect();
     }
     interleaved
         .chunks_exact(channels)
-        .map(|frame| frame.iter().copied().map(to_f32).sum::<f32>() / channels as f32)
+        .map(|frame| frame.iter().copied().map(u16_to_f32).sum::<f32>() / channels as f32)
         .collect()
 }
 
+/// Ramp the first and last `EDGE_FADE` of `samples`, interleaved over
+/// `channels`, linearly from and to silence. A clip shorter than two fades
+/// gets two half-length ramps.
+fn fade_edges_linear(samples: &mut [f32], rate: u32, channels: usize) {
+    let frames = samples.len() / channels;
+    let fade = ((EDGE_FADE.as_secs_f64() * f64::from(rate)) as usize).min(frames / 2);
+    for i in 0..fade {
+        let gain = i as f32 / fade as f32;
+        let head = i * channels;
+        let tail = (frames - 1 - i) * channels;
+        for sample in &mut samples[head..head + channels] {
+            *sample *= gain;
+        }
+        for sample in &mut samples[tail..tail + channels] {
+            *sample *= gain;
+        }
+    }
+}
+
+/// Apply the session's input gain to captured samples. Clamped to full
+/// scale, so a boosted peak clips flat instead of overflowing when the
+/// artifact is written as 16-bit PCM.
+fn amplify(mut samples: Vec<f32>, gain: f32) -> Vec<f32> {
+    if gain != 1.0 {
+        for sample in &mut samples {
+            *sample = (*sample * gain).clamp(-1.0, 1.0);
+        }
+    }
+    samples
+}
+
+fn i16_to_f32(sample: i16) -> f32 {
+    sample as f32 / i16::MAX as f32
+}
+
+/// u16 PCM: midpoint is 32768. Normalize to [-1, 1] via (x / max) * 2 - 1.
+fn u16_to_f32(sample: u16) -> f32 {
+    sample as f32 / (u16::MAX as f32 * 0.5) - 1.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -550,10 +1840,415 @@ mod tests {
         assert_eq!(mono, vec![0.0, 0.0]);
     }
 
+    #[test]
+    fn callback_path_never_blocks_on_a_stalled_consumer() {
+        const FRAMES: usize = 480;
+        const OVERFLOW: usize = 3;
+
+        // Nothing receives until every callback has run: a consumer stalled
+        // for the whole burst. A blocking send would hang here.
+        let (sample_tx, sample_rx) = mpsc::sync_channel(SAMPLE_QUEUE_CHUNKS);
+        let dropped_frames = Arc::new(AtomicU32::new(0));
+        let mut capture = capture_chunk(
+            sample_tx,
+            dropped_frames.clone(),
+            ClipCounter::default(),
+            1.0,
+            2,
+            false,
+            |s: f32| s,
+            downmix_f32,
+        );
+        let interleaved = vec![0.25_f32; FRAMES * 2];
+        for _ in 0..SAMPLE_QUEUE_CHUNKS + OVERFLOW {
+            capture(&interleaved);
+        }
+
+        assert_eq!(
+            dropped_frames.load(Ordering::Relaxed),
+            (OVERFLOW * FRAMES) as u32
+        );
+        let queued: usize = sample_rx.try_iter().map(|chunk| chunk.len()).sum();
+        assert_eq!(queued, SAMPLE_QUEUE_CHUNKS * FRAMES);
+    }
+
+    #[test]
+    fn auto_stopped_recording_is_still_taken_at_close() {
+        // Stand-in worker: records until silence auto-stops it, then hands
+        // the kept buffer to `Stop` as `run_consumer` does.
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let (auto_stop_tx, auto_stop_rx) = mpsc::channel();
+        let mut recorder = Recorder::new();
+        recorder.is_recording.store(true, Ordering::Release);
+        let (is_recording, auto_stopped) =
+            (recorder.is_recording.clone(), recorder.auto_stopped.clone());
+        let worker = thread::spawn(move || {
+            let buffer = vec![0.5_f32; 160];
+            auto_stop_rx.recv().unwrap();
+            hold_auto_stopped(&is_recording, &auto_stopped);
+            for cmd in cmd_rx {
+                match cmd {
+                    RecorderCmd::Stop(reply) => {
+                        auto_stopped.store(false, Ordering::Release);
+                        let _ = reply.send(Ok(buffer.clone()));
+                    }
+                    RecorderCmd::Shutdown => return,
+                    _ => {}
+                }
+            }
+        });
+        recorder.cmd_tx = Some(cmd_tx);
+        recorder.worker_handle = Some(worker);
+        recorder.current_recording_id = Some("rec-1".to_string());
+
+        auto_stop_tx.send(()).unwrap();
+        while recorder.snapshot().is_recording {
+            thread::yield_now();
+        }
+
+        let snapshot = recorder.snapshot();
+        assert!(snapshot.session_open && snapshot.auto_stopped);
+        assert_eq!(
+            recorder.get_current_recording_id().as_deref(),
+            Some("rec-1")
+        );
+
+        // What `finalize_and_close` does before writing the file.
+        let (id, samples) = recorder.take_recording().unwrap().unwrap();
+        assert_eq!(id, "rec-1");
+        assert_eq!(samples.len(), 160);
+        assert!(!recorder.has_recording());
+        assert!(recorder.take_recording().unwrap().is_none());
+        recorder.close_session().unwrap();
+    }
+
+    #[test]
+    fn native_capture_keeps_the_channels_interleaved() {
+        let (sample_tx, sample_rx) = mpsc::sync_channel(1);
+        let dropped_frames = Arc::new(AtomicU32::new(0));
+        let mut capture = capture_chunk(
+            sample_tx,
+            dropped_frames.clone(),
+            ClipCounter::default(),
+            2.0,
+            2,
+            true,
+            |s: f32| s,
+            downmix_f32,
+        );
+        capture(&[0.25, -0.25, 0.1, 0.0]);
+        capture(&[0.25, -0.25, 0.1, 0.0]);
+
+        assert_eq!(sample_rx.try_recv().unwrap(), vec![0.5, -0.5, 0.2, 0.0]);
+        // The second chunk found the queue full: two frames, not four
+        // samples, were lost.
+        assert_eq!(dropped_frames.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn finalize_resamples_and_pads_each_channel() {
+        // Left a constant, right silent, at 8 kHz to 16 kHz.
+        let stereo: Vec<f32> = (0..16_000).flat_map(|_| [0.5, 0.0]).collect();
+        let out = finalize(stereo, 2, 8_000, 16_000, false).unwrap();
+        assert_eq!(out.len() % 2, 0);
+        assert!((out.len() / 2).abs_diff(32_000) < 64);
+        let mid_frame = &out[out.len() / 4 * 2..][..2];
+        assert!((mid_frame[0] - 0.5).abs() < 0.01);
+        assert_eq!(mid_frame[1], 0.0);
+
+        // A short clip pads whole frames.
+        let short = finalize(vec![0.5; 200], 2, 16_000, 16_000, false).unwrap();
+        assert_eq!(short.len(), SHORT_RECORDING_PAD_SAMPLES * 2);
+    }
+
+    #[test]
+    fn recording_format_errors_name_the_field() {
+        let options = |buffer_frames, auto_stop_silence_ms| RecordingSessionOptions {
+            buffer_frames,
+            auto_stop_silence_ms,
+            ..Default::default()
+        };
+        let check = |rate, options: &RecordingSessionOptions| {
+            validate_recording_format("rec1", rate, options)
+        };
+
+        assert_eq!(check(None, &options(None, None)), Ok(()));
+        assert_eq!(check(Some(48_000), &options(Some(256), Some(2000))), Ok(()));
+        assert!(check(Some(0), &options(None, None))
+            .unwrap_err()
+            .starts_with("sampleRate:"));
+        assert!(check(None, &options(Some(0), None))
+            .unwrap_err()
+            .starts_with("bufferFrames:"));
+        assert!(check(None, &options(None, Some(0)))
+            .unwrap_err()
+            .starts_with("autoStopSilenceMs:"));
+        let gain = |input_gain| RecordingSessionOptions {
+            input_gain: Some(input_gain),
+            ..Default::default()
+        };
+        assert_eq!(check(None, &gain(2.5)), Ok(()));
+        assert!(check(None, &gain(0.0))
+            .unwrap_err()
+            .starts_with("inputGain:"));
+        assert!(check(None, &gain(f32::NAN))
+            .unwrap_err()
+            .starts_with("inputGain:"));
+
+        let template = RecordingSessionOptions {
+            filename_template: Some("{dat}".to_string()),
+            ..Default::default()
+        };
+        assert!(check(None, &template)
+            .unwrap_err()
+            .starts_with("filenameTemplate:"));
+
+        let native = RecordingSessionOptions {
+            use_native_format: true,
+            ..Default::default()
+        };
+        assert_eq!(check(None, &native), Ok(()));
+        assert!(check(Some(16_000), &native)
+            .unwrap_err()
+            .starts_with("useNativeFormat:"));
+    }
+
+    #[test]
+    fn native_config_takes_the_highest_rate_at_the_native_channel_count() {
+        let range = |channels, max_rate, format| {
+            cpal::SupportedStreamConfigRange::new(
+                channels,
+                cpal::SampleRate(8_000),
+                cpal::SampleRate(max_rate),
+                cpal::SupportedBufferSize::Unknown,
+                format,
+            )
+        };
+        let pick = |native_channels, configs: &[cpal::SupportedStreamConfigRange]| {
+            native_config(native_channels, configs).map(|config| {
+                (
+                    config.sample_rate().0,
+                    config.channels(),
+                    config.sample_format(),
+                )
+            })
+        };
+        let configs = [
+            range(2, 192_000, SampleFormat::F32),
+            range(2, 48_000, SampleFormat::I16),
+            range(1, 96_000, SampleFormat::U16),
+            range(1, 96_000, SampleFormat::I16),
+            range(1, 192_000, SampleFormat::I32),
+        ];
+
+        assert_eq!(
+            pick(Some(2), &configs),
+            Some((192_000, 2, SampleFormat::F32))
+        );
+        // The highest rate the callback can take at one channel, in the
+        // most precise of the formats offered there.
+        assert_eq!(
+            pick(Some(1), &configs),
+            Some((96_000, 1, SampleFormat::I16))
+        );
+        // No config at the native count, or no default to tell: any count.
+        assert_eq!(
+            pick(Some(4), &configs),
+            Some((192_000, 2, SampleFormat::F32))
+        );
+        assert_eq!(pick(None, &configs), Some((192_000, 2, SampleFormat::F32)));
+        // Rates past what a session accepts are capped.
+        assert_eq!(
+            pick(Some(1), &[range(1, 768_000, SampleFormat::F32)]),
+            Some((384_000, 1, SampleFormat::F32))
+        );
+        assert_eq!(pick(Some(1), &[range(1, 48_000, SampleFormat::I32)]), None);
+    }
+
+    #[test]
+    fn clipping_counts_raw_samples_of_any_channel() {
+        let clips = ClipCounter::default();
+        // Left channel pinned at full scale, right quiet: the downmix would
+        // read 0.5 and hide it.
+        let stereo = [i16::MAX, 100, i16::MIN, -100, 1000, 100, -1000, -100];
+        clips.record(stereo.iter().copied().map(i16_to_f32));
+
+        assert_eq!(clips.take_fraction(), Some(0.25));
+        assert_eq!(clips.take_fraction(), None);
+        clips.record([0.5_f32, -0.5].into_iter());
+        assert_eq!(clips.take_fraction(), None);
+    }
+
+    #[test]
+    fn gain_boosts_and_clamps_at_full_scale() {
+        assert_eq!(amplify(vec![0.1, -0.2, 0.6], 2.0), vec![0.2, -0.4, 1.0]);
+        assert_eq!(amplify(vec![-0.9], 4.0), vec![-1.0]);
+        assert_eq!(amplify(vec![0.3], 1.0), vec![0.3]);
+    }
+
     #[test]
     fn downmix_mono_is_identity() {
         let input = vec![0.1_f32, 0.2, 0.3];
         let mono = downmix_f32(&input, 1);
         assert_eq!(mono, input);
     }
+
+    #[test]
+    fn edge_fade_ramps_only_the_ends() {
+        // 5 ms at 1 kHz is a 5-sample ramp at each end.
+        let mut samples = vec![1.0_f32; 20];
+        fade_edges_linear(&mut samples, 1_000, 1);
+        assert_eq!(&samples[..5], &[0.0, 0.2, 0.4, 0.6, 0.8]);
+        assert!(samples[5..15].iter().all(|&s| s == 1.0));
+        assert_eq!(&samples[15..], &[0.8, 0.6, 0.4, 0.2, 0.0]);
+
+        // Too short for two full ramps: each end gets half the clip.
+        let mut short = vec![1.0_f32; 4];
+        fade_edges_linear(&mut short, 1_000, 1);
+        assert_eq!(short, [0.0, 0.5, 0.5, 0.0]);
+
+        // Interleaved channels ramp together, frame by frame.
+        let mut stereo = vec![1.0_f32; 8];
+        fade_edges_linear(&mut stereo, 1_000, 2);
+        assert_eq!(stereo, [0.0, 0.0, 0.5, 0.5, 0.5, 0.5, 0.0, 0.0]);
+
+        fade_edges_linear(&mut [], 1_000, 1);
+    }
+
+    /// Feed `seconds` of audio at `actual_rate` in 10 ms chunks against a
+    /// monitor that believes the device runs at `nominal_rate`.
+    fn run_drift_monitor(nominal_rate: u32, actual_rate: u32, seconds: u64) -> RateDriftMonitor {
+        let mut monitor = RateDriftMonitor::new(nominal_rate);
+        let start = Instant::now();
+        let chunk = (actual_rate / 100) as usize;
+        for tick in 0..=seconds * 100 {
+            monitor.on_chunk(chunk, start + Duration::from_millis(tick * 10));
+        }
+        monitor
+    }
+
+    #[test]
+    fn drift_monitor_ignores_an_accurate_clock() {
+        let monitor = run_drift_monitor(48_000, 48_000, 120);
+        assert_eq!(monitor.effective_rate(), None);
+    }
+
+    #[test]
+    fn drift_monitor_measures_a_fast_clock() {
+        // ~1% fast: 48_500 frames per wall-clock second.
+        let monitor = run_drift_monitor(48_000, 48_500, 120);
+        let rate = monitor.effective_rate().expect("drift detected");
+        assert!(rate.abs_diff(48_500) <= 5, "measured {rate}");
+    }
+
+    #[test]
+    fn silence_timer_fires_only_on_continuous_silence() {
+        let window = Duration::from_millis(50);
+        let mut timer = SilenceTimer::new(Duration::from_millis(200));
+
+        for _ in 0..3 {
+            assert_eq!(timer.on_window(0.001, window), None);
+        }
+        // Speech resets the count.
+        assert_eq!(timer.on_window(0.2, window), None);
+        for _ in 0..3 {
+            assert_eq!(timer.on_window(0.001, window), None);
+        }
+        assert_eq!(
+            timer.on_window(0.001, window),
+            Some(Duration::from_millis(200))
+        );
+    }
+
+    #[test]
+    fn devices_sort_default_first_then_by_name() {
+        let names = ["USB Mic", "built-in", "Webcam", "USB Mic", "AirPods"]
+            .map(String::from)
+            .to_vec();
+
+        let sorted = sort_devices(names, Some("USB Mic"));
+
+        let order: Vec<(&str, bool)> = sorted
+            .iter()
+            .map(|d| (d.id.as_str(), d.is_default))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("USB Mic", true),
+                ("AirPods", false),
+                ("built-in", false),
+                ("USB Mic#2", false),
+                ("Webcam", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_device_names_get_distinct_ids() {
+        let ids =
+            |names: &[&str]| device_ids(&names.iter().map(|n| n.to_string()).collect::<Vec<_>>());
+
+        assert_eq!(
+            ids(&["USB Audio", "Mic", "USB Audio", "USB Audio"]),
+            ["USB Audio", "Mic", "USB Audio#2", "USB Audio#3"]
+        );
+        // A device really named "X#2" keeps its name; the duplicate skips it.
+        assert_eq!(ids(&["X", "X", "X#2"]), ["X", "X#3", "X#2"]);
+    }
+
+    #[test]
+    fn latency_prefers_the_driver_figure_over_the_buffer_period() {
+        let from_buffer = estimate_latency(48_000, Some(480), Some((64, 4096)), None);
+        assert_eq!(from_buffer.buffer_latency_ms, Some(10.0));
+        assert_eq!(from_buffer.estimated_ms, Some(10.0));
+        assert!(from_buffer.note.is_some());
+        assert_eq!(from_buffer.min_buffer_frames, Some(64));
+
+        let from_driver = estimate_latency(48_000, Some(480), None, Some(23_500));
+        assert_eq!(from_driver.estimated_ms, Some(23.5));
+        assert_eq!(from_driver.note, None);
+
+        assert_eq!(
+            estimate_latency(48_000, None, None, None).estimated_ms,
+            None
+        );
+    }
+
+    #[test]
+    fn buffer_size_is_fixed_only_within_the_supported_range() {
+        let range = cpal::SupportedBufferSize::Range { min: 64, max: 4096 };
+        assert_eq!(resolve_buffer_size(None, &range), cpal::BufferSize::Default);
+        assert_eq!(
+            resolve_buffer_size(Some(256), &range),
+            cpal::BufferSize::Fixed(256)
+        );
+        assert_eq!(
+            resolve_buffer_size(Some(16), &range),
+            cpal::BufferSize::Default
+        );
+        assert_eq!(
+            resolve_buffer_size(Some(256), &cpal::SupportedBufferSize::Unknown),
+            cpal::BufferSize::Default
+        );
+    }
+
+    #[test]
+    fn device_selector_uses_empty_string_as_default_sentinel() {
+        assert_eq!(DeviceSelector::parse(""), DeviceSelector::Default);
+        assert_eq!(
+            DeviceSelector::parse("Default Microphone"),
+            DeviceSelector::Named("Default Microphone")
+        );
+        // The legacy literal is a name first; `find_device` only treats it as
+        // the default after no device with that exact name is found.
+        assert_eq!(
+            DeviceSelector::parse("default"),
+            DeviceSelector::Named("default")
+        );
+        assert_eq!(
+            DeviceSelector::parse("[Loopback] Speakers"),
+            DeviceSelector::Loopback("Speakers")
+        );
+    }
 }
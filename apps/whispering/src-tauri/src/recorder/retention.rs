@@ -0,0 +1,180 @@
+//! Delete old recordings past a retention policy, so the recordings folder
+//! does not grow without bound.
+//!
+//! The policy keeps the `keep_last` most recent recordings, plus any
+//! recording newer than `keep_newer_than_days`; everything else goes. A
+//! recording is all the artifacts sharing its id (a WAV plus a re-encoded
+//! copy), and its age is that of its newest file, so re-encoding an old
+//! recording counts as touching it. Markdown sidecars are left alone, as in
+//! `clear_recording_artifacts`.
+//!
+//! Like the rest of the artifact code this works on the app's recordings
+//! folder by id, never on a caller-supplied path. The removed ids are
+//! returned so the frontend can drop the matching database rows.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::artifact::{delete_artifacts, recording_id_from_artifact_filename, recordings_dir};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingCleanup {
+    /// Ids whose artifacts were deleted, oldest first.
+    pub recording_ids: Vec<String>,
+    #[specta(type = specta_typescript::Number<u64>)]
+    pub freed_bytes: u64,
+}
+
+/// One recording's artifacts: their total size and newest modification.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Recording {
+    bytes: u64,
+    modified: SystemTime,
+}
+
+/// Delete the recordings in the app's recordings folder that fall outside
+/// the policy.
+pub(super) fn cleanup(
+    app: &AppHandle,
+    keep_last: u32,
+    keep_newer_than_days: Option<u32>,
+) -> Result<RecordingCleanup, String> {
+    let recordings = scan(&recordings_dir(app)?)?;
+    let max_age = keep_newer_than_days.map(|days| DAY * days);
+    let expired = expired(recordings, keep_last, max_age, SystemTime::now());
+
+    let recording_ids: Vec<String> = expired.iter().map(|(id, _)| id.clone()).collect();
+    delete_artifacts(app, &recording_ids)?;
+    Ok(RecordingCleanup {
+        recording_ids,
+        freed_bytes: expired.iter().map(|(_, recording)| recording.bytes).sum(),
+    })
+}
+
+/// Artifacts in `dir` grouped by recording id.
+fn scan(dir: &Path) -> Result<BTreeMap<String, Recording>, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(format!("read recordings dir {}: {e}", dir.display())),
+    };
+    let mut recordings: BTreeMap<String, Recording> = BTreeMap::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("read recordings dir {}: {e}", dir.display()))?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let Some(id) = recording_id_from_artifact_filename(&name) else {
+            continue;
+        };
+        let meta = entry
+            .metadata()
+            .map_err(|e| format!("stat artifact {}: {e}", entry.path().display()))?;
+        if !meta.is_file() {
+            continue;
+        }
+        let modified = meta
+            .modified()
+            .map_err(|e| format!("stat artifact {}: {e}", entry.path().display()))?;
+        let recording = recordings
+            .entry(id.to_string())
+            .or_insert(Recording { bytes: 0, modified });
+        recording.bytes += meta.len();
+        recording.modified = recording.modified.max(modified);
+    }
+    Ok(recordings)
+}
+
+/// The recordings outside the `keep_last` newest and older than `max_age`,
+/// oldest first. Ties in age are broken by id so the result is
+/// deterministic.
+fn expired(
+    recordings: BTreeMap<String, Recording>,
+    keep_last: u32,
+    max_age: Option<Duration>,
+    now: SystemTime,
+) -> Vec<(String, Recording)> {
+    let mut newest_first: Vec<(String, Recording)> = recordings.into_iter().collect();
+    newest_first
+        .sort_by(|(a_id, a), (b_id, b)| b.modified.cmp(&a.modified).then_with(|| a_id.cmp(b_id)));
+
+    let mut expired: Vec<(String, Recording)> = newest_first
+        .into_iter()
+        .skip(keep_last as usize)
+        .filter(|(_, recording)| {
+            // A future timestamp (clock change) is age zero, so it is kept.
+            let age = now.duration_since(recording.modified).unwrap_or_default();
+            max_age.is_none_or(|max_age| age > max_age)
+        })
+        .collect();
+    expired.reverse();
+    expired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_newest_and_the_recent_and_expires_the_rest() {
+        let now = SystemTime::now();
+        let recording = |days_old: u32| Recording {
+            bytes: 10,
+            modified: now - DAY * days_old,
+        };
+        let recordings: BTreeMap<String, Recording> = [
+            ("a", recording(1)),
+            ("b", recording(3)),
+            ("c", recording(10)),
+            ("d", recording(20)),
+            ("e", recording(40)),
+        ]
+        .into_iter()
+        .map(|(id, r)| (id.to_string(), r))
+        .collect();
+        let ids = |expired: Vec<(String, Recording)>| -> Vec<String> {
+            expired.into_iter().map(|(id, _)| id).collect()
+        };
+
+        assert_eq!(
+            ids(expired(recordings.clone(), 2, None, now)),
+            ["e", "d", "c"]
+        );
+        // The two newest are kept regardless of age; of the rest, only the
+        // ones older than 15 days go.
+        assert_eq!(
+            ids(expired(recordings.clone(), 2, Some(DAY * 15), now)),
+            ["e", "d"]
+        );
+        assert_eq!(
+            ids(expired(recordings.clone(), 0, Some(DAY * 2), now)),
+            ["e", "d", "c", "b"]
+        );
+        assert!(expired(recordings, 5, None, now).is_empty());
+    }
+
+    #[test]
+    fn groups_artifacts_by_id_and_skips_sidecars() {
+        let dir = std::env::temp_dir().join(format!("whispering-retention-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("one.wav"), [0u8; 10]).unwrap();
+        std::fs::write(dir.join("one.ogg"), [0u8; 5]).unwrap();
+        std::fs::write(dir.join("one.md"), "id: one").unwrap();
+        std::fs::write(dir.join("two.2024-06-01_0930.wav"), [0u8; 7]).unwrap();
+
+        let recordings = scan(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        let sizes: Vec<(&str, u64)> = recordings
+            .iter()
+            .map(|(id, recording)| (id.as_str(), recording.bytes))
+            .collect();
+        assert_eq!(sizes, [("one", 15), ("two", 7)]);
+    }
+}
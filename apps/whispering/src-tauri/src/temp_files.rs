@@ -0,0 +1,254 @@
+//! Sweep temp files a crashed or killed process left behind.
+//!
+//! Audio conversion runs entirely in memory (see `audio`), so the only temp
+//! files the app creates are staging files for writes that must not leave a
+//! half-written result: `atomic_write` (markdown export, WAV header repair,
+//! transcript conversion) and the outputs of `split_stereo_to_mono`.
+//! `tempfile` deletes those on drop, but a process that dies between create
+//! and persist leaves them in place. They all carry `TEMP_FILE_PREFIX`, so
+//! they can be told apart from anything else in the directory and removed
+//! once they are old enough to be certainly abandoned.
+//!
+//! Staging files live next to their destination (a rename cannot cross
+//! filesystems), so the startup sweep has to visit those folders: the
+//! recordings folder, plus every folder a command has staged into, which
+//! `StagingDirs` remembers in `<appConfigDir>/temp_staging_dirs.json`.
+
+use log::{info, warn};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+
+use crate::recorder::artifact::recordings_dir;
+
+/// Name prefix of every temp file the app creates.
+pub const TEMP_FILE_PREFIX: &str = ".whispering-tmp-";
+
+/// Replace the file at `path` with `bytes` atomically: the bytes are
+/// written and synced to a `TEMP_FILE_PREFIX` file in the same directory,
+/// which is then renamed over `path`. A failure part-way leaves any existing
+/// file untouched, and the temp file is removed.
+pub fn atomic_write(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut temp = tempfile::Builder::new()
+        .prefix(TEMP_FILE_PREFIX)
+        .tempfile_in(dir)?;
+    temp.write_all(bytes)?;
+    temp.as_file().sync_all()?;
+    temp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Files younger than this may still belong to a write in progress.
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+const STAGING_DIRS_FILE: &str = "temp_staging_dirs.json";
+
+/// Most recently used staging folders to keep. Export folders change rarely;
+/// this only bounds the list for a user who picks a new one every time.
+const MAX_STAGING_DIRS: usize = 32;
+
+/// Managed app state: the folders commands have staged temp files in, most
+/// recent first, so the startup sweep can find what a crash left there.
+pub struct StagingDirs {
+    path: Option<PathBuf>,
+    dirs: Mutex<Vec<PathBuf>>,
+}
+
+impl StagingDirs {
+    /// Read the remembered folders; a missing or unreadable file starts an
+    /// empty list.
+    pub fn load(app: &AppHandle) -> Self {
+        let path = app
+            .path()
+            .app_config_dir()
+            .map(|dir| dir.join(STAGING_DIRS_FILE))
+            .ok();
+        let dirs = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            dirs: Mutex::new(dirs),
+        }
+    }
+
+    /// Remember `dir` as a folder temp files are staged in. Call before
+    /// staging, so a crash mid-write is still swept. Failing to save only
+    /// costs a sweep, so it is logged.
+    pub fn remember(&self, dir: &Path) {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let mut dirs = self.dirs.lock().unwrap_or_else(|e| e.into_inner());
+        if dirs.first() == Some(&dir) {
+            return;
+        }
+        dirs.retain(|known| *known != dir);
+        dirs.insert(0, dir);
+        dirs.truncate(MAX_STAGING_DIRS);
+        if let Err(e) = self.save(&dirs) {
+            warn!("Failed to remember temp staging folders: {e}");
+        }
+    }
+
+    /// `remember` the folder a file at `path` is staged in.
+    pub fn remember_parent(&self, path: &Path) {
+        match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => self.remember(dir),
+            _ => self.remember(Path::new(".")),
+        }
+    }
+
+    fn dirs(&self) -> Vec<PathBuf> {
+        self.dirs.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn save(&self, dirs: &[PathBuf]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Err("app config directory is unavailable".to_string());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+        }
+        let json = serde_json::to_string(dirs).map_err(|e| format!("serialize: {e}"))?;
+        atomic_write(path, json.as_bytes()).map_err(|e| format!("write {}: {e}", path.display()))
+    }
+}
+
+/// Remove files in `dir` named with `TEMP_FILE_PREFIX` and last modified more
+/// than `max_age` ago. Returns how many were removed. Files that cannot be
+/// inspected or removed are logged and skipped.
+pub fn cleanup_stale_temp_files(dir: &Path, max_age: Duration) -> std::io::Result<u32> {
+    let now = SystemTime::now();
+    let mut removed = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(TEMP_FILE_PREFIX)
+        {
+            continue;
+        }
+        let path = entry.path();
+        // `DirEntry::metadata` does not follow symlinks, so a link is never
+        // mistaken for (and never removes) its target.
+        let stale = entry.metadata().and_then(|meta| {
+            let modified = meta.modified()?;
+            Ok(meta.is_file() && now.duration_since(modified).unwrap_or_default() > max_age)
+        });
+        match stale {
+            Ok(true) => match fs::remove_file(&path) {
+                Ok(()) => removed += 1,
+                Err(e) => warn!("Failed to remove stale temp file {}: {e}", path.display()),
+            },
+            Ok(false) => {}
+            Err(e) => warn!("Failed to inspect temp file {}: {e}", path.display()),
+        }
+    }
+    Ok(removed)
+}
+
+/// Startup sweep of every folder the app stages temp files in: the
+/// recordings folder, the folders in `StagingDirs`, and the system temp
+/// directory. Runs off the main thread; a failure only costs disk space, so
+/// it is logged rather than surfaced. Call after `StagingDirs` is managed.
+pub fn cleanup_on_startup(app: &AppHandle) {
+    let mut dirs = vec![std::env::temp_dir()];
+    dirs.extend(recordings_dir(app).ok());
+    dirs.extend(app.state::<StagingDirs>().dirs());
+    tauri::async_runtime::spawn_blocking(move || {
+        for dir in dirs {
+            match cleanup_stale_temp_files(&dir, DEFAULT_MAX_AGE) {
+                Ok(0) => {}
+                Ok(count) => info!("Removed {count} stale temp file(s) from {}", dir.display()),
+                // A folder that was deleted or unmounted since has nothing
+                // left to sweep.
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => warn!("Failed to sweep temp files in {}: {e}", dir.display()),
+            }
+        }
+    });
+}
+
+/// Removes the app's abandoned temp files and returns how many were removed.
+///
+/// # Arguments
+/// * `directory` - Folder to sweep, e.g. a markdown output folder. Defaults
+///   to the system temp directory.
+/// * `older_than_secs` - Only remove files at least this old. Defaults to one
+///   hour, so writes in progress are never touched.
+#[tauri::command]
+#[specta::specta]
+pub async fn cleanup_temp_files(
+    directory: Option<String>,
+    older_than_secs: Option<u32>,
+) -> Result<u32, String> {
+    let dir = directory.map_or_else(std::env::temp_dir, PathBuf::from);
+    let max_age = older_than_secs.map_or(DEFAULT_MAX_AGE, |s| Duration::from_secs(s.into()));
+    tokio::task::spawn_blocking(move || {
+        cleanup_stale_temp_files(&dir, max_age)
+            .map_err(|e| format!("Failed to sweep temp files in {}: {e}", dir.display()))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_only_old_prefixed_files() {
+        let dir = std::env::temp_dir().join(format!("whispering-sweep-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let hour_ago = SystemTime::now() - Duration::from_secs(3600);
+
+        let stale = dir.join(format!("{TEMP_FILE_PREFIX}abc"));
+        let fresh = dir.join(format!("{TEMP_FILE_PREFIX}def"));
+        let unrelated = dir.join("notes.md");
+        for path in [&stale, &fresh, &unrelated] {
+            fs::write(path, b"x").unwrap();
+        }
+        for path in [&stale, &unrelated] {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(hour_ago)
+                .unwrap();
+        }
+
+        assert_eq!(
+            cleanup_stale_temp_files(&dir, Duration::from_secs(60)).unwrap(),
+            1
+        );
+        assert!(!stale.exists());
+        assert!(fresh.exists());
+        assert!(unrelated.exists());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn atomic_write_replaces_the_file_and_leaves_no_temp() {
+        let dir = std::env::temp_dir().join(format!("whispering-atomic-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("out.txt");
+        fs::write(&path, b"old").unwrap();
+
+        atomic_write(&path, b"new").unwrap();
+        let content = fs::read(&path).unwrap();
+        let entries = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(content, b"new");
+        assert_eq!(entries, 1);
+    }
+}
@@ -0,0 +1,107 @@
+//! Per-stage timings of the last dictation, for "dictation feels laggy"
+//! reports.
+//!
+//! Each stage records its own duration as it finishes:
+//!
+//! - `record_to_stop_ms`: `stop_recording`, from the stop request until the
+//!   WAV is on disk (drain, resample, write). The time spent talking is not
+//!   latency and is left out.
+//! - `conversion_ms`: reading the recording back and decoding it to 16 kHz
+//!   mono for the engine.
+//! - `model_load_ms`: loading the model; `0` when it was already resident.
+//! - `inference_ms`: the engine call itself.
+//! - `paste_ms`: `write_text`, including the focus wait and clipboard
+//!   restore.
+//!
+//! `stop_recording` starts a new cycle and clears the other stages, so a
+//! stage that did not run this time (say, a cancelled transcription) reads
+//! `null` instead of a stale number from an earlier dictation.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineTimings {
+    pub record_to_stop_ms: Option<u32>,
+    pub conversion_ms: Option<u32>,
+    pub model_load_ms: Option<u32>,
+    pub inference_ms: Option<u32>,
+    pub paste_ms: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineStage {
+    RecordToStop,
+    Conversion,
+    ModelLoad,
+    Inference,
+    Paste,
+}
+
+impl PipelineTimings {
+    fn record(&mut self, stage: PipelineStage, elapsed: Duration) {
+        let ms = Some(elapsed.as_millis().min(u32::MAX as u128) as u32);
+        match stage {
+            PipelineStage::RecordToStop => {
+                *self = Self {
+                    record_to_stop_ms: ms,
+                    ..Self::default()
+                }
+            }
+            PipelineStage::Conversion => self.conversion_ms = ms,
+            PipelineStage::ModelLoad => self.model_load_ms = ms,
+            PipelineStage::Inference => self.inference_ms = ms,
+            PipelineStage::Paste => self.paste_ms = ms,
+        }
+    }
+}
+
+/// Managed state holding the last cycle's timings.
+#[derive(Default)]
+pub struct PipelineTimer(Mutex<PipelineTimings>);
+
+/// Record how long `stage` took. A no-op before the state is managed.
+pub fn record_stage(app: &AppHandle, stage: PipelineStage, elapsed: Duration) {
+    if let Some(timer) = app.try_state::<PipelineTimer>() {
+        let mut timings = timer.0.lock().unwrap_or_else(|e| e.into_inner());
+        timings.record(stage, elapsed);
+    }
+}
+
+/// Per-stage timings of the most recent record → stop → transcribe → paste
+/// cycle. Stages that have not run since the last `stop_recording` are
+/// `null`.
+#[tauri::command]
+#[specta::specta]
+pub fn get_last_pipeline_timings(timer: State<'_, PipelineTimer>) -> PipelineTimings {
+    *timer.0.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stopping_a_recording_starts_a_new_cycle() {
+        let mut timings = PipelineTimings::default();
+        timings.record(PipelineStage::RecordToStop, Duration::from_millis(40));
+        timings.record(PipelineStage::Inference, Duration::from_millis(900));
+        timings.record(PipelineStage::Paste, Duration::from_millis(150));
+
+        timings.record(PipelineStage::RecordToStop, Duration::from_millis(35));
+        timings.record(PipelineStage::ModelLoad, Duration::ZERO);
+
+        assert_eq!(
+            timings,
+            PipelineTimings {
+                record_to_stop_ms: Some(35),
+                model_load_ms: Some(0),
+                ..PipelineTimings::default()
+            }
+        );
+    }
+}
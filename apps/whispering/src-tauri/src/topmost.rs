@@ -0,0 +1,146 @@
+//! Best-effort "keep the recording overlay above everything" on platforms
+//! where Tauri's `always_on_top` is not the whole story.
+//!
+//! macOS does not need this: the overlay is an `NSPanel` at status level (see
+//! `overlay.rs`). Windows honours `always_on_top` reliably. On Linux it depends
+//! on the display server:
+//!
+//! - X11: `always_on_top` is a one-shot hint. Some window managers drop it when
+//!   another window goes fullscreen, so the frontend calls
+//!   `ensure_overlay_topmost` each time it shows the overlay and we re-assert
+//!   `_NET_WM_STATE_ABOVE` through GTK's `set_keep_above`.
+//! - Wayland: clients cannot raise themselves above other surfaces, and most
+//!   compositors ignore keep-above for regular toplevels. There is nothing to
+//!   re-assert, so this is a documented no-op that reports back instead of
+//!   pretending to succeed.
+//!
+//! Whenever topmost cannot be guaranteed we emit `TOPMOST_UNAVAILABLE_EVENT`
+//! so the UI can suggest an alternative (sound cues, notifications).
+//!
+//! A recording shortcut pressed right after launch can reach here while the
+//! overlay is still being created (by the JS window manager, or on macOS by
+//! `overlay::create_recording_overlay`). Rather than fail with "window does
+//! not exist", we wait briefly for it to appear; on macOS we also create the
+//! panel on demand in case startup creation failed.
+
+use log::{debug, warn};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+
+// Must stay in sync with the JS window manager's `WINDOW_LABEL`.
+const WINDOW_LABEL: &str = "recording-overlay";
+
+pub const TOPMOST_UNAVAILABLE_EVENT: &str = "recording-overlay:topmost-unavailable";
+
+/// How long to wait for an overlay that is still being created. Window
+/// creation takes tens of milliseconds; this only has to cover a slow start.
+const OVERLAY_CREATE_WAIT: Duration = Duration::from_secs(2);
+const OVERLAY_POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TopmostUnavailablePayload {
+    reason: String,
+}
+
+/// Re-assert that the recording overlay stays above other windows. Returns
+/// `true` when the platform can guarantee it, `false` when it is best effort
+/// only (a `TOPMOST_UNAVAILABLE_EVENT` has been emitted with the reason).
+///
+/// Also `false`, without the event, when the overlay still does not exist
+/// after `OVERLAY_CREATE_WAIT`: there is nothing to keep on top yet, and the
+/// next show re-asserts.
+#[tauri::command]
+#[specta::specta]
+pub async fn ensure_overlay_topmost(app: AppHandle) -> Result<bool, String> {
+    let Some(window) = wait_for_overlay(&app).await else {
+        debug!("Recording overlay not created yet; skipping topmost re-assertion");
+        return Ok(false);
+    };
+
+    match reassert_topmost(&app, &window) {
+        Ok(()) => {
+            debug!("Recording overlay topmost re-asserted");
+            Ok(true)
+        }
+        Err(reason) => {
+            warn!("Recording overlay cannot be kept on top: {reason}");
+            let payload = TopmostUnavailablePayload { reason };
+            if let Err(e) = app.emit(TOPMOST_UNAVAILABLE_EVENT, payload) {
+                warn!("Failed to emit {TOPMOST_UNAVAILABLE_EVENT}: {e}");
+            }
+            Ok(false)
+        }
+    }
+}
+
+/// The overlay window, waiting up to `OVERLAY_CREATE_WAIT` for one that is
+/// still being created.
+async fn wait_for_overlay(app: &AppHandle) -> Option<WebviewWindow> {
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        return Some(window);
+    }
+
+    // The macOS panel is created by Rust at startup; if that failed, nothing
+    // else will create it, so try again here. Idempotent if it races with a
+    // creation already in flight. Panels must be built on the main thread.
+    #[cfg(target_os = "macos")]
+    {
+        let handle = app.clone();
+        if let Err(e) =
+            app.run_on_main_thread(move || crate::overlay::create_recording_overlay(&handle))
+        {
+            warn!("Failed to schedule recording overlay creation: {e}");
+        }
+    }
+
+    let deadline = Instant::now() + OVERLAY_CREATE_WAIT;
+    while Instant::now() < deadline {
+        tokio::time::sleep(OVERLAY_POLL_INTERVAL).await;
+        if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+            debug!("Recording overlay appeared after waiting for creation");
+            return Some(window);
+        }
+    }
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn reassert_topmost(app: &AppHandle, window: &tauri::WebviewWindow) -> Result<(), String> {
+    use gtk::prelude::GtkWindowExt;
+
+    if crate::wayland::is_wayland_session() {
+        return Err(
+            "Wayland compositors do not let applications keep a window above others".to_string(),
+        );
+    }
+
+    window
+        .set_always_on_top(true)
+        .map_err(|e| format!("Failed to set always-on-top: {e}"))?;
+
+    // GTK objects are main-thread only. `set_keep_above` sends the
+    // `_NET_WM_STATE_ABOVE` client message even when GTK believes the flag is
+    // already set, which is what revives it after a fullscreen app cleared it.
+    let window = window.clone();
+    app.run_on_main_thread(move || match window.gtk_window() {
+        Ok(gtk_window) => gtk_window.set_keep_above(true),
+        Err(e) => warn!("Failed to get GTK window for overlay: {e}"),
+    })
+    .map_err(|e| format!("Failed to schedule topmost re-assertion: {e}"))
+}
+
+/// The panel already sits at `PanelLevel::Status`. `set_always_on_top` would
+/// drop it to the floating level, below other apps' status-level windows.
+#[cfg(target_os = "macos")]
+fn reassert_topmost(_app: &AppHandle, _window: &tauri::WebviewWindow) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn reassert_topmost(_app: &AppHandle, window: &tauri::WebviewWindow) -> Result<(), String> {
+    window
+        .set_always_on_top(true)
+        .map_err(|e| format!("Failed to set always-on-top: {e}"))
+}
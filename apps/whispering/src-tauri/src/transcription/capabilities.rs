@@ -0,0 +1,126 @@
+//! What a local model can do with the `language` and translate settings,
+//! inferred from its name.
+//!
+//! None of the engines expose this cheaply at runtime (reading it would mean
+//! loading the model), but every model we ship follows a naming convention:
+//!
+//! - whisper.cpp: English-only checkpoints carry a `.en` suffix
+//!   (`ggml-base.en.bin`). Everything else covers Whisper's full language set
+//!   and can translate to English, except the `turbo` checkpoints, which were
+//!   not trained on the translate task.
+//! - Parakeet: `v2` is English-only; `v3` covers 25 European languages.
+//! - Moonshine: one language per checkpoint, named `moonshine-{variant}-{lang}`.
+//!
+//! Names outside these conventions get `ModelCapabilities::UNKNOWN`, which
+//! leaves the language picker enabled and translation off.
+
+use serde::Serialize;
+
+use super::config::Engine;
+
+/// Languages Parakeet TDT 0.6B v3 was trained on.
+const PARAKEET_V3_LANGUAGES: &[&str] = &[
+    "bg", "cs", "da", "de", "el", "en", "es", "et", "fi", "fr", "hr", "hu", "it", "lt", "lv", "mt",
+    "nl", "pl", "pt", "ro", "ru", "sk", "sl", "sv", "uk",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelCapabilities {
+    /// Accepts a `language` other than English.
+    pub multilingual: bool,
+    /// ISO 639-1 codes the model handles. `None` means no known restriction
+    /// (all of Whisper's languages, or a model we could not classify).
+    pub supported_languages: Option<Vec<String>>,
+    /// Can translate speech into English.
+    pub supports_translate: bool,
+}
+
+impl ModelCapabilities {
+    /// Conservative answer for a model we cannot classify: do not restrict
+    /// the language choice, and do not promise translation.
+    const UNKNOWN: Self = Self {
+        multilingual: true,
+        supported_languages: None,
+        supports_translate: false,
+    };
+
+    fn only(languages: &[&str]) -> Self {
+        Self {
+            multilingual: languages.len() > 1 || languages.first() != Some(&"en"),
+            supported_languages: Some(languages.iter().map(|l| l.to_string()).collect()),
+            supports_translate: false,
+        }
+    }
+}
+
+/// Capabilities of `model_name` in `engine`'s models folder.
+pub fn model_capabilities(engine: Engine, model_name: &str) -> ModelCapabilities {
+    let name = model_name.to_ascii_lowercase();
+    match engine {
+        Engine::Whispercpp => {
+            let stem = name.strip_suffix(".bin").unwrap_or(&name);
+            if stem.ends_with(".en") || stem.contains(".en-") || stem.contains(".en.") {
+                ModelCapabilities::only(&["en"])
+            } else {
+                ModelCapabilities {
+                    multilingual: true,
+                    supported_languages: None,
+                    supports_translate: !stem.contains("turbo"),
+                }
+            }
+        }
+        Engine::Parakeet if name.contains("-v2") => ModelCapabilities::only(&["en"]),
+        Engine::Parakeet if name.contains("-v3") => ModelCapabilities::only(PARAKEET_V3_LANGUAGES),
+        Engine::Moonshine => match name.strip_prefix("moonshine-").map(|rest| rest.split('-')) {
+            Some(mut parts) => match (parts.next(), parts.next(), parts.next()) {
+                (Some("tiny" | "base"), Some(lang), None) if lang.len() == 2 => {
+                    ModelCapabilities::only(&[lang])
+                }
+                _ => ModelCapabilities::UNKNOWN,
+            },
+            None => ModelCapabilities::UNKNOWN,
+        },
+        Engine::Parakeet => ModelCapabilities::UNKNOWN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_shipped_model_names() {
+        let english = ModelCapabilities::only(&["en"]);
+        assert_eq!(
+            model_capabilities(Engine::Whispercpp, "ggml-base.en.bin"),
+            english
+        );
+        assert_eq!(
+            model_capabilities(Engine::Whispercpp, "ggml-small.en-q5_1.bin"),
+            english
+        );
+        assert!(model_capabilities(Engine::Whispercpp, "ggml-small.bin").supports_translate);
+        assert!(
+            !model_capabilities(Engine::Whispercpp, "ggml-large-v3-turbo.bin").supports_translate
+        );
+        assert_eq!(
+            model_capabilities(Engine::Parakeet, "parakeet-tdt-0.6b-v2-int8"),
+            english
+        );
+        assert_eq!(
+            model_capabilities(Engine::Parakeet, "parakeet-tdt-0.6b-v3-int8")
+                .supported_languages
+                .map(|l| l.len()),
+            Some(25)
+        );
+        assert_eq!(
+            model_capabilities(Engine::Moonshine, "moonshine-tiny-ja"),
+            ModelCapabilities::only(&["ja"])
+        );
+        assert_eq!(
+            model_capabilities(Engine::Moonshine, "my-custom-model"),
+            ModelCapabilities::UNKNOWN
+        );
+    }
+}
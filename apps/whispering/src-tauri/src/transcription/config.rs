@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::audio::Downmix;
+
 /// Ambient configuration the frontend pushes once per change. The Rust side
 /// reads this on every `transcribe_recording` call instead of receiving
 /// a per-call payload. The model loads lazily on the next transcription, so a
@@ -19,6 +21,198 @@ pub struct TranscriptionConfig {
     #[serde(default)]
     pub initial_prompt: Option<String>,
     pub unload_policy: UnloadPolicy,
+    /// Prime each Whisper transcription with the tail of the previous one, so
+    /// consecutive chunks of a long dictation read as one text. The carried
+    /// tail is appended after `initial_prompt` (which is kept, not replaced);
+    /// see `context.rs` for the length budget. Ignored by Parakeet and
+    /// Moonshine, which take no prompt.
+    #[serde(default)]
+    pub carry_context: bool,
+    /// Trim leading and trailing audio quieter than this many dBFS before
+    /// inference (around -40 suits a close mic), so push-to-talk silence
+    /// costs no inference time and cannot turn into hallucinated text.
+    /// Segment timings still refer to the untrimmed audio. `None` disables
+    /// trimming.
+    #[serde(default)]
+    pub trim_silence_dbfs: Option<i32>,
+    /// Skip the engine and return an empty transcript when the audio holds
+    /// no speech louder than this many dBFS, by the same check as the
+    /// `contains_speech` command. Saves loading a model for an accidentally
+    /// empty recording. `None` always runs the engine.
+    #[serde(default)]
+    pub speech_check_dbfs: Option<i32>,
+    /// Whisper's task. `translate` outputs English whatever was spoken, with
+    /// `language` still hinting the source language. Ignored by Parakeet and
+    /// Moonshine, which cannot translate.
+    #[serde(default)]
+    pub task: WhisperTask,
+    /// Pick the model by audio length instead of always using `engine` and
+    /// `model_name`, e.g. Moonshine tiny for quick notes and Parakeet for
+    /// meetings. The rule with the smallest `max_duration_ms` that covers
+    /// the audio wins; audio longer than every limit uses `engine` and
+    /// `model_name`, as does an empty list.
+    #[serde(default)]
+    pub auto_model_selection: Vec<AutoModelRule>,
+    /// Transcribe audio longer than one window in overlapping windows, one
+    /// engine call each, instead of in a single call. Bounds the engine's
+    /// memory on hour-long recordings and reports progress per window.
+    /// `None` sends the whole audio at once.
+    #[serde(default)]
+    pub chunking: Option<Chunking>,
+    /// How a multichannel recording is folded to mono before inference,
+    /// e.g. front channels only for a 5.1 capture. `None` averages every
+    /// channel.
+    #[serde(default)]
+    pub downmix: Option<Downmix>,
+    /// How finely Parakeet times its output: `segment` for phrases, `word`
+    /// for one `segments` entry per word, e.g. to highlight words during
+    /// playback. Whisper and Moonshine ignore it.
+    #[serde(default)]
+    pub timestamp_granularity: TimestampGranularity,
+}
+
+/// Per-call changes to the ambient `TranscriptionConfig`, for one
+/// transcription that should not touch the saved settings (comparing two
+/// models on the same audio, say). Unset fields keep the ambient value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionOverrides {
+    /// Set together with `model_name`, since a model belongs to one engine.
+    /// An explicit model is used whatever the audio length, so
+    /// `auto_model_selection` is skipped.
+    #[serde(default)]
+    pub engine: Option<Engine>,
+    #[serde(default)]
+    pub model_name: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub initial_prompt: Option<String>,
+    #[serde(default)]
+    pub task: Option<WhisperTask>,
+    #[serde(default)]
+    pub timestamp_granularity: Option<TimestampGranularity>,
+}
+
+impl TranscriptionOverrides {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match (&self.engine, &self.model_name) {
+            (Some(_), Some(name)) => {
+                validate_model_name(name).map_err(|e| format!("modelName: {e}"))
+            }
+            (None, None) => Ok(()),
+            _ => Err("engine and modelName must be overridden together".to_string()),
+        }
+    }
+
+    /// `config` with the set fields replaced.
+    pub(crate) fn apply(&self, config: TranscriptionConfig) -> TranscriptionConfig {
+        let (engine, model_name, auto_model_selection) = match (self.engine, &self.model_name) {
+            (Some(engine), Some(name)) => (engine, name.clone(), Vec::new()),
+            _ => (
+                config.engine,
+                config.model_name,
+                config.auto_model_selection,
+            ),
+        };
+        TranscriptionConfig {
+            engine,
+            model_name,
+            auto_model_selection,
+            language: self.language.clone().or(config.language),
+            initial_prompt: self.initial_prompt.clone().or(config.initial_prompt),
+            task: self.task.unwrap_or(config.task),
+            timestamp_granularity: self
+                .timestamp_granularity
+                .unwrap_or(config.timestamp_granularity),
+            ..config
+        }
+    }
+}
+
+/// One entry of `TranscriptionConfig::auto_model_selection`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoModelRule {
+    /// Longest audio, in milliseconds, this model is used for. `None` covers
+    /// any length.
+    pub max_duration_ms: Option<u32>,
+    pub engine: Engine,
+    /// Entry name inside the engine's models directory, like `model_name`.
+    pub model_name: String,
+}
+
+/// Window sizes for `TranscriptionConfig::chunking`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Chunking {
+    /// Length of each window, e.g. 30000 to match Whisper's context.
+    pub chunk_ms: u32,
+    /// Audio each window repeats from the end of the one before, so a word
+    /// cut at a window boundary is heard whole by one of them. What the
+    /// engine hears in it is trimmed again when the windows are stitched.
+    pub overlap_ms: u32,
+}
+
+/// Shortest `Chunking::chunk_ms`; shorter windows cost more in per-call
+/// overhead than they save.
+const MIN_CHUNK_MS: u32 = 5_000;
+
+impl Chunking {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.chunk_ms < MIN_CHUNK_MS {
+            return Err(format!(
+                "chunkMs must be at least {MIN_CHUNK_MS}, got {}",
+                self.chunk_ms
+            ));
+        }
+        if self.overlap_ms >= self.chunk_ms / 2 {
+            return Err(format!(
+                "overlapMs must be less than half of chunkMs ({}), got {}",
+                self.chunk_ms, self.overlap_ms
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl TranscriptionConfig {
+    /// The auto-selection rule for `audio_ms` of audio, if any applies.
+    /// Rules failing `usable` (a model that is not installed) are skipped,
+    /// as if they were not in the list.
+    pub(crate) fn auto_model_for(
+        &self,
+        audio_ms: u32,
+        usable: impl Fn(&AutoModelRule) -> bool,
+    ) -> Option<&AutoModelRule> {
+        self.auto_model_selection
+            .iter()
+            .filter(|rule| rule.max_duration_ms.is_none_or(|max| audio_ms <= max))
+            .filter(|rule| usable(rule))
+            .min_by_key(|rule| rule.max_duration_ms.unwrap_or(u32::MAX))
+    }
+}
+
+/// What Whisper does with the speech.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, specta::Type)]
+#[serde(rename_all = "lowercase")]
+pub enum WhisperTask {
+    /// Text in the spoken language.
+    #[default]
+    Transcribe,
+    /// English text, whatever the spoken language.
+    Translate,
+}
+
+/// Unit of the timed `segments` in a detailed result.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, specta::Type)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampGranularity {
+    /// Phrases or sentences, as the engine splits them.
+    #[default]
+    Segment,
+    /// Single words.
+    Word,
 }
 
 /// Local transcription engine. Wire tags match the frontend
@@ -53,3 +247,117 @@ pub enum UnloadPolicy {
 impl UnloadPolicy {
     pub const DEFAULT: Self = Self::AfterFiveMinutes;
 }
+
+/// Check that a model name is a single models-folder entry: non-empty, no
+/// separators, no traversal. Shared by the ambient-config path and profile
+/// import so both reject the same names with the same message.
+pub(crate) fn validate_model_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("No local model selected. Choose a model in settings.".to_string());
+    }
+    if name.contains('/') || name.contains('\\') || name == "." || name == ".." {
+        return Err(format!(
+            "Model name must be a single models-folder entry, got: {}",
+            name
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_model_is_the_tightest_rule_covering_the_audio() {
+        let rule = |max_duration_ms, model_name: &str| AutoModelRule {
+            max_duration_ms,
+            engine: Engine::Moonshine,
+            model_name: model_name.to_string(),
+        };
+        let config = TranscriptionConfig {
+            engine: Engine::Whispercpp,
+            model_name: "ggml-base.en.bin".to_string(),
+            language: None,
+            initial_prompt: None,
+            unload_policy: UnloadPolicy::DEFAULT,
+            carry_context: false,
+            trim_silence_dbfs: None,
+            speech_check_dbfs: None,
+            task: WhisperTask::Transcribe,
+            auto_model_selection: vec![rule(Some(600_000), "meeting"), rule(Some(30_000), "note")],
+            chunking: None,
+            downmix: None,
+            timestamp_granularity: TimestampGranularity::Segment,
+        };
+
+        let picked = |audio_ms| {
+            config
+                .auto_model_for(audio_ms, |_| true)
+                .map(|r| r.model_name.as_str())
+        };
+        assert_eq!(picked(12_000), Some("note"));
+        assert_eq!(picked(30_000), Some("note"));
+        assert_eq!(picked(45_000), Some("meeting"));
+        assert_eq!(picked(3_600_000), None);
+
+        // A rule whose model is missing drops out; the others still apply.
+        let without_note = config
+            .auto_model_for(12_000, |rule| rule.model_name != "note")
+            .map(|r| r.model_name.as_str());
+        assert_eq!(without_note, Some("meeting"));
+    }
+
+    #[test]
+    fn overrides_replace_only_the_fields_they_set() {
+        let config = TranscriptionConfig {
+            engine: Engine::Whispercpp,
+            model_name: "ggml-base.en.bin".to_string(),
+            language: Some("en".to_string()),
+            initial_prompt: Some("Epicenter".to_string()),
+            unload_policy: UnloadPolicy::DEFAULT,
+            carry_context: true,
+            trim_silence_dbfs: None,
+            speech_check_dbfs: None,
+            task: WhisperTask::Transcribe,
+            auto_model_selection: vec![AutoModelRule {
+                max_duration_ms: Some(30_000),
+                engine: Engine::Moonshine,
+                model_name: "moonshine-tiny-en".to_string(),
+            }],
+            chunking: None,
+            downmix: None,
+            timestamp_granularity: TimestampGranularity::Segment,
+        };
+
+        let language_only = TranscriptionOverrides {
+            language: Some("de".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            language_only.apply(config.clone()),
+            TranscriptionConfig {
+                language: Some("de".to_string()),
+                ..config.clone()
+            }
+        );
+
+        let model = TranscriptionOverrides {
+            engine: Some(Engine::Parakeet),
+            model_name: Some("parakeet-tdt".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(model.validate(), Ok(()));
+        let applied = model.apply(config.clone());
+        assert_eq!(applied.engine, Engine::Parakeet);
+        assert_eq!(applied.model_name, "parakeet-tdt");
+        assert!(applied.auto_model_selection.is_empty());
+        assert_eq!(applied.initial_prompt, config.initial_prompt);
+
+        let engine_only = TranscriptionOverrides {
+            engine: Some(Engine::Parakeet),
+            ..Default::default()
+        };
+        assert!(engine_only.validate().is_err());
+    }
+}
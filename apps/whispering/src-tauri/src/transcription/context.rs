@@ -0,0 +1,72 @@
+//! Rolling prompt context for consecutive transcriptions (`carry_context`).
+//!
+//! Whisper conditions each decode on its `initial_prompt`. Feeding it the tail
+//! of the previous chunk's transcript keeps spelling, casing, and sentence
+//! flow consistent across chunk boundaries of a long dictation.
+//!
+//! Whisper only looks at the last ~224 tokens of a prompt (half its text
+//! context), silently dropping the front. To keep the user's own
+//! `initial_prompt` (vocabulary, names) from being the part that falls off,
+//! the composed prompt is the user prompt *followed by* the carried tail, and
+//! the tail is trimmed so the whole thing stays inside the budget. The user
+//! prompt is never trimmed here; a prompt over the limit on its own is left
+//! for Whisper to truncate as it always has.
+
+/// Word budget for the composed prompt. English averages ~1.3 tokens per
+/// word, so 150 words stays under Whisper's 224-token prompt window.
+pub const MAX_PROMPT_WORDS: usize = 150;
+
+/// `user_prompt` followed by as much of the end of `carried` as fits in
+/// `MAX_PROMPT_WORDS`. `None` when both are empty.
+pub fn compose_prompt(user_prompt: Option<&str>, carried: Option<&str>) -> Option<String> {
+    let user_prompt = user_prompt.map(str::trim).filter(|p| !p.is_empty());
+    let budget = MAX_PROMPT_WORDS.saturating_sub(user_prompt.map_or(0, word_count));
+    let tail = carried
+        .map(|text| tail_words(text, budget))
+        .filter(|t| !t.is_empty());
+
+    match (user_prompt, tail) {
+        (Some(user), Some(tail)) => Some(format!("{user} {tail}")),
+        (Some(user), None) => Some(user.to_string()),
+        (None, Some(tail)) => Some(tail),
+        (None, None) => None,
+    }
+}
+
+/// The last `max_words` whitespace-separated words of `text`.
+pub fn tail_words(text: &str, max_words: usize) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words[words.len().saturating_sub(max_words)..].join(" ")
+}
+
+fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_prompt_comes_first_and_tail_fills_the_rest() {
+        let carried = (0..200)
+            .map(|i| format!("w{i}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let prompt = compose_prompt(Some("Epicenter Whispering"), Some(&carried)).unwrap();
+
+        assert!(prompt.starts_with("Epicenter Whispering w52 "));
+        assert!(prompt.ends_with(" w199"));
+        assert_eq!(word_count(&prompt), MAX_PROMPT_WORDS);
+    }
+
+    #[test]
+    fn empty_parts_are_skipped() {
+        assert_eq!(compose_prompt(None, None), None);
+        assert_eq!(compose_prompt(Some("  "), Some("")), None);
+        assert_eq!(
+            compose_prompt(None, Some("previous chunk")).as_deref(),
+            Some("previous chunk")
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! Find-and-replace correction rules for transcripts ("get hub" ->
+//! "GitHub"), with enough bookkeeping to debug a rule set before saving it.
+//!
+//! Rules apply in order, each to the output of the previous one, so a later
+//! rule can see (and re-correct) an earlier rule's replacement. Matching is
+//! literal. `case_sensitive` off compares characters case-insensitively;
+//! `whole_word` on only matches where neither neighbour is a letter, digit,
+//! or underscore, so "art" does not fire inside "start".
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrectionRule {
+    pub find: String,
+    pub replace: String,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub whole_word: bool,
+}
+
+/// Where a replacement landed in the final text, for highlighting.
+/// Offsets are UTF-16 code units, matching JavaScript string indices.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrectionSpan {
+    /// Index into the rules that produced this text.
+    pub rule_index: u32,
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrectionPreview {
+    pub text: String,
+    /// Replacements made by each rule, in rule order. A zero points at a rule
+    /// that never fired.
+    pub replacement_counts: Vec<u32>,
+    /// Spans of replaced text still present in `text`, in order. A span that
+    /// a later rule rewrote is dropped in favour of the later rule's span.
+    pub spans: Vec<CorrectionSpan>,
+}
+
+/// Byte range `[start, end)` in the text after a rule ran, with its rule.
+struct Span {
+    rule_index: u32,
+    start: usize,
+    end: usize,
+}
+
+/// One replacement: bytes `[start, end)` of the input became `[start,
+/// start + new_len)` of the output, shifted by the edits before it.
+struct Edit {
+    start: usize,
+    end: usize,
+    new_len: usize,
+}
+
+pub fn validate_rules(rules: &[CorrectionRule]) -> Result<(), String> {
+    match rules.iter().position(|rule| rule.find.is_empty()) {
+        Some(index) => Err(format!("Correction rule {index} has an empty find text")),
+        None => Ok(()),
+    }
+}
+
+/// Apply `rules` to `text` in order. Rules must have passed
+/// `validate_rules`.
+pub fn apply_corrections(text: &str, rules: &[CorrectionRule]) -> CorrectionPreview {
+    let mut text = text.to_string();
+    let mut spans: Vec<Span> = Vec::new();
+    let mut replacement_counts = Vec::with_capacity(rules.len());
+
+    for (rule_index, rule) in rules.iter().enumerate() {
+        let matches = find_matches(&text, rule);
+        replacement_counts.push(matches.len() as u32);
+        if matches.is_empty() {
+            continue;
+        }
+
+        let mut output = String::with_capacity(text.len());
+        let mut edits = Vec::with_capacity(matches.len());
+        let mut copied = 0;
+        for (start, end) in matches {
+            output.push_str(&text[copied..start]);
+            output.push_str(&rule.replace);
+            edits.push(Edit {
+                start,
+                end,
+                new_len: rule.replace.len(),
+            });
+            copied = end;
+        }
+        output.push_str(&text[copied..]);
+
+        spans = remap_spans(spans, &edits);
+        let mut delta = 0isize;
+        for edit in &edits {
+            let start = edit.start.wrapping_add_signed(delta);
+            spans.push(Span {
+                rule_index: rule_index as u32,
+                start,
+                end: start + edit.new_len,
+            });
+            delta += edit.new_len as isize - (edit.end - edit.start) as isize;
+        }
+        spans.sort_by_key(|span| span.start);
+        text = output;
+    }
+
+    let spans = spans
+        .into_iter()
+        .filter(|span| span.end > span.start)
+        .map(|span| CorrectionSpan {
+            rule_index: span.rule_index,
+            start: utf16_offset(&text, span.start),
+            end: utf16_offset(&text, span.end),
+        })
+        .collect();
+    CorrectionPreview {
+        text,
+        replacement_counts,
+        spans,
+    }
+}
+
+/// Non-overlapping byte ranges where `rule.find` matches, left to right.
+fn find_matches(text: &str, rule: &CorrectionRule) -> Vec<(usize, usize)> {
+    let mut matches = Vec::new();
+    let mut resume = 0;
+    for (start, _) in text.char_indices() {
+        if start < resume {
+            continue;
+        }
+        let Some(end) = match_at(text, start, rule) else {
+            continue;
+        };
+        if rule.whole_word && !(is_boundary(text, start) && is_boundary(text, end)) {
+            continue;
+        }
+        matches.push((start, end));
+        resume = end;
+    }
+    matches
+}
+
+/// End of a match of `rule.find` starting at byte `start`, if there is one.
+fn match_at(text: &str, start: usize, rule: &CorrectionRule) -> Option<usize> {
+    let mut haystack = text[start..].char_indices();
+    for want in rule.find.chars() {
+        let (_, got) = haystack.next()?;
+        let equal = if rule.case_sensitive {
+            got == want
+        } else {
+            got == want || got.to_lowercase().eq(want.to_lowercase())
+        };
+        if !equal {
+            return None;
+        }
+    }
+    Some(haystack.next().map_or(text.len(), |(i, _)| start + i))
+}
+
+/// True when byte `at` does not sit between two word characters.
+fn is_boundary(text: &str, at: usize) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let before = text[..at].chars().next_back().is_some_and(is_word);
+    let after = text[at..].chars().next().is_some_and(is_word);
+    !(before && after)
+}
+
+/// Carry earlier spans through this rule's `edits`: shift spans after an
+/// edit, drop spans an edit overlaps.
+fn remap_spans(spans: Vec<Span>, edits: &[Edit]) -> Vec<Span> {
+    spans
+        .into_iter()
+        .filter_map(|span| {
+            let mut delta = 0isize;
+            for edit in edits {
+                if edit.end <= span.start {
+                    delta += edit.new_len as isize - (edit.end - edit.start) as isize;
+                } else if edit.start < span.end {
+                    return None;
+                }
+            }
+            Some(Span {
+                start: span.start.wrapping_add_signed(delta),
+                end: span.end.wrapping_add_signed(delta),
+                ..span
+            })
+        })
+        .collect()
+}
+
+fn utf16_offset(text: &str, byte: usize) -> u32 {
+    text[..byte].encode_utf16().count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(find: &str, replace: &str, case_sensitive: bool, whole_word: bool) -> CorrectionRule {
+        CorrectionRule {
+            find: find.to_string(),
+            replace: replace.to_string(),
+            case_sensitive,
+            whole_word,
+        }
+    }
+
+    #[test]
+    fn counts_replacements_per_rule_and_respects_options() {
+        let rules = [
+            rule("get hub", "GitHub", false, false),
+            rule("art", "ART", true, true),
+            rule("Art", "ART", true, true),
+            rule("never", "x", false, false),
+        ];
+
+        let preview = apply_corrections("Get hub art start, get hub.", &rules);
+
+        assert_eq!(preview.text, "GitHub ART start, GitHub.");
+        assert_eq!(preview.replacement_counts, vec![2, 1, 0, 0]);
+        let spans: Vec<(u32, u32, u32)> = preview
+            .spans
+            .iter()
+            .map(|s| (s.rule_index, s.start, s.end))
+            .collect();
+        assert_eq!(spans, vec![(0, 0, 6), (1, 7, 10), (0, 18, 24)]);
+    }
+
+    #[test]
+    fn a_later_rule_replaces_an_earlier_span_and_offsets_are_utf16() {
+        let rules = [
+            rule("colour", "color", false, false),
+            rule("color", "hue", false, false),
+        ];
+
+        let preview = apply_corrections("😀 colour", &rules);
+
+        assert_eq!(preview.text, "😀 hue");
+        assert_eq!(preview.replacement_counts, vec![1, 1]);
+        assert_eq!(
+            preview.spans,
+            vec![CorrectionSpan {
+                rule_index: 1,
+                start: 3,
+                end: 6,
+            }]
+        );
+        assert!(validate_rules(&[rule("", "x", false, false)]).is_err());
+    }
+}
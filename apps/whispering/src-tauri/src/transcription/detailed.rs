@@ -0,0 +1,130 @@
+//! The full result of one transcription, of which the plain-text commands
+//! return only `text`.
+//!
+//! Every local transcription goes through `ModelManager::transcribe_detailed`;
+//! `transcribe` is that with the extras dropped. Adding a field here (and
+//! filling it in there) makes it available on every path at once.
+
+use serde::{Deserialize, Serialize};
+
+/// A stretch of the transcript with its position in the audio.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptSegment {
+    pub start_ms: u32,
+    pub end_ms: u32,
+    pub text: String,
+}
+
+impl TranscriptSegment {
+    /// From an engine segment timed in seconds. Whitespace-only segments
+    /// (whisper.cpp emits them around pauses) are dropped.
+    pub(crate) fn from_seconds(start: f32, end: f32, text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        let to_ms = |seconds: f32| (seconds.max(0.0) * 1000.0).round() as u32;
+        Some(Self {
+            start_ms: to_ms(start),
+            end_ms: to_ms(end.max(start)),
+            text: text.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct DetailedTranscription {
+    pub text: String,
+    /// Empty when the engine reports no timing (Moonshine). Single words
+    /// when Parakeet runs at `TimestampGranularity::Word`.
+    pub segments: Vec<TranscriptSegment>,
+    /// The language the engine was told to use. `None` means auto-detect;
+    /// the engines do not report which language they then detected.
+    pub language: Option<String>,
+    /// Length of the audio that was transcribed.
+    pub audio_ms: u32,
+    /// Wall time spent in the engine, including a cold model load.
+    pub elapsed_ms: u32,
+}
+
+impl DetailedTranscription {
+    /// Remove the first `context_ms` of audio from the result, for audio that
+    /// was fed to the engine with lead-in context before the requested start.
+    ///
+    /// A segment belongs to the lead-in when its midpoint falls inside it, so
+    /// a word straddling the boundary is kept exactly once: by the range that
+    /// holds most of it. Chunks requested back to back with padding therefore
+    /// stitch without duplicated words; the padding only ever supplies
+    /// context. The remaining segments are shifted to start from the
+    /// requested position and `text` is rebuilt from them. Without segments
+    /// (Moonshine) there is nothing to locate the boundary with, so the result
+    /// is left as is.
+    pub(crate) fn trim_leading_context(&mut self, context_ms: u32) {
+        if context_ms == 0 || self.segments.is_empty() {
+            return;
+        }
+        self.segments
+            .retain(|s| (s.start_ms as u64 + s.end_ms as u64) / 2 >= context_ms as u64);
+        for segment in &mut self.segments {
+            segment.start_ms = segment.start_ms.saturating_sub(context_ms);
+            segment.end_ms = segment.end_ms.saturating_sub(context_ms);
+        }
+        self.text = self
+            .segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.audio_ms = self.audio_ms.saturating_sub(context_ms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_convert_to_ms_and_skip_blank_text() {
+        assert_eq!(
+            TranscriptSegment::from_seconds(1.25, 2.5, " Hello there. "),
+            Some(TranscriptSegment {
+                start_ms: 1250,
+                end_ms: 2500,
+                text: "Hello there.".to_string(),
+            })
+        );
+        assert_eq!(TranscriptSegment::from_seconds(3.0, 3.5, "  "), None);
+        assert_eq!(
+            TranscriptSegment::from_seconds(-0.1, -0.2, "x").map(|s| (s.start_ms, s.end_ms)),
+            Some((0, 0))
+        );
+    }
+
+    #[test]
+    fn trimming_context_drops_lead_in_segments_and_rebases_the_rest() {
+        let segment = |start: f32, end: f32, text: &str| {
+            TranscriptSegment::from_seconds(start, end, text).unwrap()
+        };
+        let mut result = DetailedTranscription {
+            text: "end of last chunk. Boundary word here. Next.".to_string(),
+            segments: vec![
+                segment(0.0, 0.4, "end of last chunk."),
+                segment(0.4, 1.2, "Boundary word here."),
+                segment(1.2, 2.0, "Next."),
+            ],
+            language: None,
+            audio_ms: 2000,
+            elapsed_ms: 10,
+        };
+
+        result.trim_leading_context(500);
+
+        assert_eq!(result.text, "Boundary word here. Next.");
+        assert_eq!(result.segments[0].start_ms, 0);
+        assert_eq!(result.segments[0].end_ms, 700);
+        assert_eq!(result.segments[1].start_ms, 700);
+        assert_eq!(result.audio_ms, 1500);
+    }
+}
@@ -0,0 +1,178 @@
+//! Guess which engine a models-folder entry belongs to by looking at it,
+//! rather than trusting the folder it was put in.
+//!
+//! Each engine's models have a recognizable shape:
+//! - whisper.cpp: a single file starting with the GGML (`lmgg`, the
+//!   little-endian `ggml` magic) or GGUF magic bytes.
+//! - Parakeet: a directory with `encoder-model*.onnx`,
+//!   `decoder_joint-model*.onnx`, and `vocab.txt`.
+//! - Moonshine: a directory with `encoder_model*.onnx`,
+//!   `decoder_model_merged*.onnx`, and `tokenizer.json`.
+//!
+//! Without this, a Whisper `.bin` dropped into the Parakeet folder only fails
+//! at transcription time with an opaque load error.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use super::config::Engine;
+
+const GGML_MAGIC: &[u8; 4] = b"lmgg";
+const GGUF_MAGIC: &[u8; 4] = b"GGUF";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(
+    tag = "kind",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum EngineInference {
+    /// The entry looks like a model for `engine`.
+    Detected { engine: Engine },
+    /// The entry matches no engine's layout; `reason` says what was seen.
+    Unknown { reason: String },
+}
+
+/// Inspect `path` (a models-folder entry) and guess its engine.
+pub fn infer_engine(path: &Path) -> EngineInference {
+    let unknown = |reason: String| EngineInference::Unknown { reason };
+
+    if path.is_file() {
+        return match read_magic(path) {
+            Ok(magic) if &magic == GGML_MAGIC || &magic == GGUF_MAGIC => {
+                EngineInference::Detected {
+                    engine: Engine::Whispercpp,
+                }
+            }
+            Ok(_) if has_extension(path, "onnx") => unknown(
+                "a single ONNX file; Parakeet and Moonshine models are folders of several files"
+                    .to_string(),
+            ),
+            Ok(_) => unknown("a file without a GGML/GGUF header".to_string()),
+            Err(e) => unknown(format!("could not read the file: {e}")),
+        };
+    }
+
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) => return unknown(format!("could not read the folder: {e}")),
+    };
+    let names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    let has = |name: &str| names.iter().any(|n| n == name);
+    let has_onnx = |prefix: &str| {
+        names
+            .iter()
+            .any(|n| n.starts_with(prefix) && n.ends_with(".onnx"))
+    };
+
+    if has("vocab.txt") && has_onnx("encoder-model") && has_onnx("decoder_joint-model") {
+        return EngineInference::Detected {
+            engine: Engine::Parakeet,
+        };
+    }
+    if has("tokenizer.json") && has_onnx("encoder_model") && has_onnx("decoder_model_merged") {
+        return EngineInference::Detected {
+            engine: Engine::Moonshine,
+        };
+    }
+    if names.iter().any(|n| n.ends_with(".bin")) {
+        return unknown(
+            "a folder containing a .bin file; Whisper models are selected as the file itself"
+                .to_string(),
+        );
+    }
+    unknown(format!(
+        "a folder with none of the expected model files (found: {})",
+        if names.is_empty() {
+            "nothing".to_string()
+        } else {
+            names.join(", ")
+        }
+    ))
+}
+
+fn read_magic(path: &Path) -> std::io::Result<[u8; 4]> {
+    let mut magic = [0u8; 4];
+    fs::File::open(path)?.read_exact(&mut magic)?;
+    Ok(magic)
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("whispering-detect-{name}-{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn detects_whisper_file_by_magic() {
+        let dir = scratch("whisper");
+        let model = dir.join("ggml-base.en.bin");
+        fs::write(&model, b"lmgg\x00\x01rest-of-header").unwrap();
+        let not_a_model = dir.join("notes.bin");
+        fs::write(&not_a_model, b"hello").unwrap();
+
+        assert_eq!(
+            infer_engine(&model),
+            EngineInference::Detected {
+                engine: Engine::Whispercpp
+            }
+        );
+        assert!(matches!(
+            infer_engine(&not_a_model),
+            EngineInference::Unknown { .. }
+        ));
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn detects_onnx_engines_by_folder_layout() {
+        let parakeet = scratch("parakeet");
+        for file in [
+            "encoder-model.int8.onnx",
+            "decoder_joint-model.int8.onnx",
+            "nemo128.onnx",
+            "vocab.txt",
+        ] {
+            fs::write(parakeet.join(file), b"x").unwrap();
+        }
+        let moonshine = scratch("moonshine");
+        for file in [
+            "encoder_model.onnx",
+            "decoder_model_merged.onnx",
+            "tokenizer.json",
+        ] {
+            fs::write(moonshine.join(file), b"x").unwrap();
+        }
+
+        assert_eq!(
+            infer_engine(&parakeet),
+            EngineInference::Detected {
+                engine: Engine::Parakeet
+            }
+        );
+        assert_eq!(
+            infer_engine(&moonshine),
+            EngineInference::Detected {
+                engine: Engine::Moonshine
+            }
+        );
+        fs::remove_dir_all(&parakeet).ok();
+        fs::remove_dir_all(&moonshine).ok();
+    }
+}
@@ -0,0 +1,208 @@
+//! Word diff between two transcripts, for comparing the output of different
+//! settings (denoise on or off, another model) on the same recording.
+//!
+//! Tokens are whitespace-separated words compared exactly, so a change in
+//! case or punctuation ("hello" -> "Hello,") shows up as a change: that is
+//! output the user would have to fix by hand. The diff is a longest common
+//! subsequence over the words left after trimming the shared prefix and
+//! suffix. Runs of words with the same op are merged into one op, so the UI
+//! renders "deleted a phrase" rather than a strip of one-word ops.
+
+use serde::Serialize;
+
+/// Above this many LCS cells (words left in the old text times words left in
+/// the new one, after trimming), the middle is reported as one deletion and
+/// one insertion instead. About two 2,000-word transcripts that share no
+/// prefix or suffix, and 16 MB of table.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DiffOp {
+    /// Words in both transcripts.
+    Equal { text: String },
+    /// Words only in the new transcript.
+    Insert { text: String },
+    /// Words only in the previous transcript.
+    Delete { text: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptDiff {
+    /// The new transcript.
+    pub text: String,
+    /// Ops in reading order. Concatenating the `equal` and `insert` texts
+    /// with spaces gives `text` with its whitespace normalized; `equal` and
+    /// `delete` give the previous transcript.
+    pub ops: Vec<DiffOp>,
+    /// Words inserted across all ops.
+    pub inserted_words: u32,
+    /// Words deleted across all ops.
+    pub deleted_words: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// Diff `previous` against `text`, word by word.
+pub fn diff_transcripts(previous: &str, text: String) -> TranscriptDiff {
+    let old: Vec<&str> = previous.split_whitespace().collect();
+    let new: Vec<&str> = text.split_whitespace().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut tokens: Vec<(Kind, &str)> = Vec::with_capacity(old.len() + new.len());
+    tokens.extend(old[..prefix].iter().map(|word| (Kind::Equal, *word)));
+    tokens.extend(diff_middle(old_middle, new_middle));
+    tokens.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|word| (Kind::Equal, *word)),
+    );
+
+    let count = |kind| tokens.iter().filter(|(k, _)| *k == kind).count() as u32;
+    let inserted_words = count(Kind::Insert);
+    let deleted_words = count(Kind::Delete);
+    TranscriptDiff {
+        ops: merge_runs(&tokens),
+        text,
+        inserted_words,
+        deleted_words,
+    }
+}
+
+/// LCS diff of the words between the shared prefix and suffix. Deletions are
+/// emitted before insertions at each change, so a replaced phrase reads as
+/// "old, then new".
+fn diff_middle<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Kind, &'a str)> {
+    if old.is_empty() || new.is_empty() || old.len() * new.len() > MAX_LCS_CELLS {
+        let deleted = old.iter().map(|word| (Kind::Delete, *word));
+        return deleted
+            .chain(new.iter().map(|word| (Kind::Insert, *word)))
+            .collect();
+    }
+
+    // lcs[i][j]: LCS length of old[i..] and new[j..], flattened row-major.
+    let width = new.len() + 1;
+    let mut lcs = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i * width + j] = if old[i] == new[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut tokens = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            tokens.push((Kind::Equal, old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            tokens.push((Kind::Delete, old[i]));
+            i += 1;
+        } else {
+            tokens.push((Kind::Insert, new[j]));
+            j += 1;
+        }
+    }
+    tokens.extend(old[i..].iter().map(|word| (Kind::Delete, *word)));
+    tokens.extend(new[j..].iter().map(|word| (Kind::Insert, *word)));
+    tokens
+}
+
+/// One op per run of same-kind words.
+fn merge_runs(tokens: &[(Kind, &str)]) -> Vec<DiffOp> {
+    let mut ops = Vec::new();
+    for run in tokens.chunk_by(|a, b| a.0 == b.0) {
+        let text = run
+            .iter()
+            .map(|(_, word)| *word)
+            .collect::<Vec<_>>()
+            .join(" ");
+        ops.push(match run[0].0 {
+            Kind::Equal => DiffOp::Equal { text },
+            Kind::Insert => DiffOp::Insert { text },
+            Kind::Delete => DiffOp::Delete { text },
+        });
+    }
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_words_become_merged_delete_and_insert_ops() {
+        let diff = diff_transcripts(
+            "we shipped the the build on friday",
+            "We shipped the build on  Friday afternoon".to_string(),
+        );
+
+        assert_eq!(
+            diff.ops,
+            vec![
+                DiffOp::Delete {
+                    text: "we".to_string()
+                },
+                DiffOp::Insert {
+                    text: "We".to_string()
+                },
+                DiffOp::Equal {
+                    text: "shipped the".to_string()
+                },
+                DiffOp::Delete {
+                    text: "the".to_string()
+                },
+                DiffOp::Equal {
+                    text: "build on".to_string()
+                },
+                DiffOp::Delete {
+                    text: "friday".to_string()
+                },
+                DiffOp::Insert {
+                    text: "Friday afternoon".to_string()
+                },
+            ]
+        );
+        assert_eq!((diff.inserted_words, diff.deleted_words), (3, 3));
+    }
+
+    #[test]
+    fn identical_and_empty_transcripts() {
+        let same = diff_transcripts("hello world", "hello world".to_string());
+        assert_eq!(
+            same.ops,
+            vec![DiffOp::Equal {
+                text: "hello world".to_string()
+            }]
+        );
+
+        let from_nothing = diff_transcripts("", "hello".to_string());
+        assert_eq!(
+            from_nothing.ops,
+            vec![DiffOp::Insert {
+                text: "hello".to_string()
+            }]
+        );
+        assert!(diff_transcripts("", String::new()).ops.is_empty());
+    }
+}
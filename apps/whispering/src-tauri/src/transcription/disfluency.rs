@@ -0,0 +1,197 @@
+//! Transcript clean-up for dictation: drops filler words ("um", "uh") and
+//! collapses stutters ("the the" -> "the").
+//!
+//! Works on whitespace-separated tokens, comparing each token's word core
+//! case-insensitively while keeping its surrounding punctuation, so "Um, so
+//! we shipped. Uh." becomes "So we shipped." rather than ", so we shipped. .".
+//!
+//! The default filler list is conservative on purpose. Words like "like" or
+//! "you know" are often meaningful ("I like it"), so they are only stripped
+//! in aggressive mode or when the caller supplies its own list.
+
+/// Sounds that are never meaningful words in dictation.
+pub const DEFAULT_FILLERS: &[&str] = &["um", "umm", "uh", "uhh", "uhm", "erm", "er", "hmm", "mm"];
+
+/// Added on top of `DEFAULT_FILLERS` in aggressive mode. These have real
+/// meanings too, which is why they are opt-in.
+pub const AGGRESSIVE_FILLERS: &[&str] = &["like", "you know", "i mean", "sort of", "kind of"];
+
+/// Words that legitimately repeat ("I know that that works", "we had had
+/// enough"), so stutter collapsing leaves them alone.
+const GRAMMATICAL_REPEATS: &[&str] = &["that", "had", "is"];
+
+/// Filler phrases as lowercase word sequences, longest first so "you know"
+/// wins over a single-word entry that shares its first word.
+pub fn filler_phrases(custom: Option<Vec<String>>, aggressive: bool) -> Vec<Vec<String>> {
+    let mut phrases: Vec<Vec<String>> = match custom {
+        Some(custom) => custom.iter().map(|phrase| split_phrase(phrase)).collect(),
+        None => DEFAULT_FILLERS
+            .iter()
+            .chain(if aggressive { AGGRESSIVE_FILLERS } else { &[] })
+            .map(|phrase| split_phrase(phrase))
+            .collect(),
+    };
+    phrases.retain(|phrase| !phrase.is_empty());
+    phrases.sort_by_key(|phrase| std::cmp::Reverse(phrase.len()));
+    phrases
+}
+
+fn split_phrase(phrase: &str) -> Vec<String> {
+    phrase.split_whitespace().map(str::to_lowercase).collect()
+}
+
+struct Token<'a> {
+    leading: &'a str,
+    core: &'a str,
+    trailing: &'a str,
+}
+
+impl<'a> Token<'a> {
+    fn parse(raw: &'a str) -> Self {
+        let start = raw.find(|c: char| c.is_alphanumeric()).unwrap_or(raw.len());
+        let end = raw.rfind(|c: char| c.is_alphanumeric()).map_or(start, |i| {
+            i + raw[i..].chars().next().map_or(1, char::len_utf8)
+        });
+        Self {
+            leading: &raw[..start],
+            core: &raw[start..end],
+            trailing: &raw[end..],
+        }
+    }
+
+    fn matches(&self, word: &str) -> bool {
+        self.core.to_lowercase() == word
+    }
+}
+
+fn ends_sentence(trailing: &str) -> bool {
+    trailing.contains(['.', '?', '!'])
+}
+
+/// Remove `fillers` and collapse immediate word repeats in `text`.
+pub fn clean_transcript(text: &str, fillers: &[Vec<String>]) -> String {
+    let tokens: Vec<Token> = text.split_whitespace().map(Token::parse).collect();
+    let mut kept: Vec<(String, String)> = Vec::with_capacity(tokens.len());
+    let mut capitalize_next = false;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        if let Some(len) = match_filler(&tokens[i..], fillers) {
+            let last = &tokens[i + len - 1];
+            let at_sentence_start = kept.last().is_none_or(|(_, t)| ends_sentence(t));
+            if at_sentence_start && tokens[i].core.starts_with(char::is_uppercase) {
+                capitalize_next = true;
+            }
+            if let Some((_, trailing)) = kept.last_mut() {
+                if ends_sentence(last.trailing) && !ends_sentence(trailing) {
+                    // Keep the sentence boundary a filler closed ("done, um.").
+                    *trailing = last.trailing.to_string();
+                } else if *trailing == "," && last.trailing.starts_with(',') {
+                    // A parenthetical filler ("we, uh, shipped") takes both
+                    // of its commas with it.
+                    trailing.clear();
+                }
+            }
+            i += len;
+            continue;
+        }
+
+        let token = &tokens[i];
+        let is_stutter = tokens.get(i + 1).is_some_and(|next| {
+            matches!(token.trailing, "" | "-")
+                && next.leading.is_empty()
+                && !token.core.is_empty()
+                && next.matches(&token.core.to_lowercase())
+                && !GRAMMATICAL_REPEATS.contains(&token.core.to_lowercase().as_str())
+        });
+        if is_stutter {
+            // Drop the first copy, carrying its sentence-start capital over.
+            if kept.last().is_none_or(|(_, t)| ends_sentence(t))
+                && token.core.starts_with(char::is_uppercase)
+            {
+                capitalize_next = true;
+            }
+            i += 1;
+            continue;
+        }
+
+        let mut word = format!("{}{}", token.leading, token.core);
+        if std::mem::take(&mut capitalize_next) {
+            word = capitalize_first(&word);
+        }
+        kept.push((word, token.trailing.to_string()));
+        i += 1;
+    }
+
+    kept.into_iter()
+        .map(|(word, trailing)| word + &trailing)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Length in tokens of the filler phrase starting at `tokens[0]`, if any.
+/// Punctuation inside a multi-word phrase ("you, know") breaks the match.
+fn match_filler(tokens: &[Token], fillers: &[Vec<String>]) -> Option<usize> {
+    fillers
+        .iter()
+        .find(|phrase| {
+            phrase.len() <= tokens.len()
+                && phrase
+                    .iter()
+                    .zip(tokens)
+                    .enumerate()
+                    .all(|(j, (word, token))| {
+                        token.matches(word) && (j + 1 == phrase.len() || token.trailing.is_empty())
+                    })
+        })
+        .map(Vec::len)
+}
+
+fn capitalize_first(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clean(text: &str, aggressive: bool) -> String {
+        clean_transcript(text, &filler_phrases(None, aggressive))
+    }
+
+    #[test]
+    fn strips_default_fillers_and_keeps_punctuation() {
+        assert_eq!(
+            clean("Um, so we, uh, shipped it. Uh.", false),
+            "So we shipped it."
+        );
+        assert_eq!(clean("I like it, you know", false), "I like it, you know");
+    }
+
+    #[test]
+    fn aggressive_mode_strips_multi_word_fillers() {
+        assert_eq!(clean("It was, like, you know fast", true), "It was fast");
+    }
+
+    #[test]
+    fn collapses_stutters_but_not_grammatical_repeats() {
+        assert_eq!(
+            clean("the the plan is is ready", false),
+            "the plan is is ready"
+        );
+        assert_eq!(
+            clean("I- I think that that works", false),
+            "I think that that works"
+        );
+    }
+
+    #[test]
+    fn custom_list_replaces_the_default() {
+        let fillers = filler_phrases(Some(vec!["basically".to_string()]), false);
+        assert_eq!(clean_transcript("um basically done", &fillers), "um done");
+    }
+}
@@ -13,6 +13,16 @@ pub enum TranscriptionError {
     #[error("Model load error: {message}")]
     ModelLoadError { message: String },
 
+    /// The memory guard estimated the model would not fit in available RAM,
+    /// so the load was refused instead of risking an OOM kill. Sizes are in
+    /// megabytes; the guard can be relaxed via `set_model_memory_guard`.
+    #[error("Insufficient memory: {message}")]
+    InsufficientMemory {
+        message: String,
+        required_mb: u32,
+        available_mb: u32,
+    },
+
     #[error("Transcription error: {message}")]
     TranscriptionError { message: String },
 
@@ -22,6 +32,11 @@ pub enum TranscriptionError {
     #[error("No transcription config: {message}")]
     NoConfig { message: String },
 
+    /// `cancel_transcription` was called while this transcription was queued
+    /// or running. Not a failure: the FE should clear its spinner quietly.
+    #[error("Transcription cancelled: {message}")]
+    Cancelled { message: String },
+
     /// The ambient config holds a value that cannot be dispatched (e.g. a
     /// Moonshine model path that does not match `moonshine-{variant}-{lang}`).
     #[error("Transcription config error: {message}")]
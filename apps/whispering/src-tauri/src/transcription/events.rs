@@ -1,4 +1,5 @@
 use super::config::Engine;
+use super::language::Script;
 use serde::{Deserialize, Serialize};
 
 /// Snapshot of everything observable about the resident model. Every event
@@ -14,6 +15,44 @@ pub struct LocalModelState {
     /// `TranscriptionConfig::model_name`.
     pub model_name: Option<String>,
     pub status: ModelStatus,
+    /// Auto-selection rules that are skipped because their model cannot be
+    /// used, for the settings UI to flag. The other rules stay active.
+    /// Omitted when every rule is usable.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auto_model_errors: Vec<AutoModelRuleError>,
+}
+
+/// Why one `TranscriptionConfig::auto_model_selection` rule is skipped.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoModelRuleError {
+    /// Position of the rule in `auto_model_selection`.
+    pub index: u32,
+    pub model_name: String,
+    pub message: String,
+}
+
+/// The model actually resident in memory, as opposed to the selection in
+/// `LocalModelState`: the two differ after a selection change while a
+/// transcription held the old model. All fields but `idle_secs` are `None`
+/// when no model is loaded.
+#[derive(Debug, Clone, Serialize, specta::Type, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedModelInfo {
+    pub engine: Option<Engine>,
+    /// Path the model was loaded from.
+    pub model_path: Option<String>,
+    /// Millis since the UNIX epoch when the load finished.
+    #[specta(type = Option<u32>)]
+    pub loaded_at_ms: Option<u64>,
+    /// Seconds since the last transcription started or finished.
+    #[specta(type = u32)]
+    pub idle_secs: u64,
+    /// Seconds until the idle watcher unloads the model under the current
+    /// unload policy; `None` when nothing is loaded or the policy has no
+    /// idle timeout.
+    #[specta(type = Option<u32>)]
+    pub unloads_in_secs: Option<u64>,
 }
 
 /// Lifecycle state of the resident model. Owned by an `Arc<RwLock<...>>`
@@ -59,6 +98,12 @@ pub enum UnloadReason {
     /// User selected a different model in settings; the old one was dropped
     /// before the new one preloads.
     ConfigChanged,
+    /// The app is shutting down the engine ahead of an update replacing the
+    /// binary.
+    Shutdown,
+    /// The user asked to free the model's memory with
+    /// `unload_transcription_model`.
+    Manual,
 }
 
 /// Single event type for everything observable about the model lifecycle.
@@ -108,6 +153,34 @@ pub enum ModelStateEvent {
     },
 }
 
+/// Advisory warning that a transcript is mostly written in a script the
+/// forced `language` does not use (e.g. Han characters under `"en"`), which
+/// usually means the language setting is wrong. Emitted after the transcript
+/// is produced; the transcript itself is returned unchanged.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type, tauri_specta::Event)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionLanguageMismatch {
+    pub requested_language: String,
+    /// Most common script in the output.
+    pub detected_script: Script,
+    /// Share (0.0-1.0) of the output's letters in `detected_script`.
+    pub detected_share: f32,
+}
+
+/// How far the running local transcription has got, for the overlay's
+/// progress bar. `progress` runs from 0.0 to 1.0. The engines expose no
+/// progress hook (transcribe-rs does not forward whisper.cpp's callback),
+/// so a transcription made in one engine call reports only its start and
+/// its end; with `TranscriptionConfig::chunking` set, long audio also
+/// reports after every window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type, tauri_specta::Event)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionProgress {
+    pub progress: f32,
+    /// Length of the audio being transcribed.
+    pub audio_ms: u32,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,6 +193,7 @@ mod tests {
                 engine: Some(Engine::Whispercpp),
                 model_name: Some("ggml-tiny.bin".to_string()),
                 status: ModelStatus::Ready,
+                auto_model_errors: Vec::new(),
             },
             elapsed_ms: 123,
         };
@@ -147,5 +221,9 @@ mod tests {
                 "idleSecs": 30
             })
         );
+        assert_eq!(
+            serde_json::to_value(UnloadReason::Manual).unwrap(),
+            json!({ "kind": "manual" })
+        );
     }
 }
@@ -0,0 +1,415 @@
+//! Reading and writing transcripts as SRT, WebVTT, plain text, JSON, and
+//! markdown, so a transcript can be converted without re-transcribing.
+//!
+//! Every format parses into `TranscriptSegment`s. Plain text and markdown
+//! may carry no timing, in which case the transcript is untimed and cannot
+//! be written as subtitles; that is reported instead of inventing
+//! timestamps.
+//!
+//! - SRT: numbered cues, `00:01:02,500 --> 00:01:04,000`.
+//! - WebVTT: a `WEBVTT` header, cues timed `00:01:02.500 --> 00:01:04.000`
+//!   (hours optional), optional cue identifiers and settings. `NOTE`,
+//!   `STYLE`, and `REGION` blocks are skipped.
+//! - Plain text: one segment per non-empty line.
+//! - JSON: an array of `{ "startMs", "endMs", "text" }`.
+//! - Markdown: one paragraph per segment, prefixed `**[01:02]**` when timed.
+//!   Headings are skipped and list markers stripped on input. A segment
+//!   runs until the next one starts, since markdown records no end.
+//!
+//! Malformed SRT and WebVTT timing lines are reported with their 1-based line
+//! number.
+
+use serde::Deserialize;
+
+use super::detailed::TranscriptSegment;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, specta::Type)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    Srt,
+    Vtt,
+    Text,
+    Json,
+    Markdown,
+}
+
+/// Parsed transcript. `timed` is false when the input had no timestamps, in
+/// which case every segment's times are zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcript {
+    pub segments: Vec<TranscriptSegment>,
+    pub timed: bool,
+}
+
+pub fn parse(input: &str, format: TranscriptFormat) -> Result<Transcript, String> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    match format {
+        TranscriptFormat::Srt => parse_cues(input, ','),
+        TranscriptFormat::Vtt => {
+            let header = input.lines().next().unwrap_or_default();
+            if !(header == "WEBVTT"
+                || header.starts_with("WEBVTT ")
+                || header.starts_with("WEBVTT\t"))
+            {
+                return Err("line 1: expected a WEBVTT header".to_string());
+            }
+            parse_cues(input, '.')
+        }
+        TranscriptFormat::Text => Ok(Transcript {
+            segments: input
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| untimed(line.trim()))
+                .collect(),
+            timed: false,
+        }),
+        TranscriptFormat::Json => {
+            let segments: Vec<TranscriptSegment> =
+                serde_json::from_str(input).map_err(|e| format!("invalid transcript JSON: {e}"))?;
+            if let Some(index) = segments.iter().position(|s| s.end_ms < s.start_ms) {
+                return Err(format!("segment {index} ends before it starts"));
+            }
+            Ok(Transcript {
+                segments,
+                timed: true,
+            })
+        }
+        TranscriptFormat::Markdown => Ok(parse_markdown(input)),
+    }
+}
+
+pub fn render(transcript: &Transcript, format: TranscriptFormat) -> Result<String, String> {
+    let needs_timing = matches!(format, TranscriptFormat::Srt | TranscriptFormat::Vtt);
+    if needs_timing && !transcript.timed {
+        return Err(
+            "The transcript has no timestamps, so it cannot be written as subtitles".to_string(),
+        );
+    }
+    let segments = &transcript.segments;
+    let mut out = String::new();
+    match format {
+        TranscriptFormat::Srt => {
+            for (index, segment) in segments.iter().enumerate() {
+                out.push_str(&format!(
+                    "{}\n{} --> {}\n{}\n\n",
+                    index + 1,
+                    timestamp(segment.start_ms, ','),
+                    timestamp(segment.end_ms, ','),
+                    segment.text
+                ));
+            }
+        }
+        TranscriptFormat::Vtt => {
+            out.push_str("WEBVTT\n\n");
+            for segment in segments {
+                out.push_str(&format!(
+                    "{} --> {}\n{}\n\n",
+                    timestamp(segment.start_ms, '.'),
+                    timestamp(segment.end_ms, '.'),
+                    segment.text
+                ));
+            }
+        }
+        TranscriptFormat::Text => {
+            for segment in segments {
+                out.push_str(&segment.text);
+                out.push('\n');
+            }
+        }
+        TranscriptFormat::Json => {
+            out = serde_json::to_string_pretty(segments)
+                .map_err(|e| format!("serialize transcript: {e}"))?;
+            out.push('\n');
+        }
+        TranscriptFormat::Markdown => {
+            for segment in segments {
+                if transcript.timed {
+                    out.push_str(&format!("**[{}]** ", short_timestamp(segment.start_ms)));
+                }
+                out.push_str(&segment.text);
+                out.push_str("\n\n");
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn untimed(text: &str) -> TranscriptSegment {
+    TranscriptSegment {
+        start_ms: 0,
+        end_ms: 0,
+        text: text.to_string(),
+    }
+}
+
+/// SRT and WebVTT cues: blocks separated by blank lines, each with a timing
+/// line and text lines, optionally preceded by a number or identifier.
+fn parse_cues(input: &str, decimal: char) -> Result<Transcript, String> {
+    let lines: Vec<&str> = input.lines().collect();
+    let webvtt = decimal == '.';
+    let mut segments = Vec::new();
+    // The WebVTT header block runs to the first blank line.
+    let mut i = if webvtt {
+        lines
+            .iter()
+            .position(|l| l.trim().is_empty())
+            .unwrap_or(lines.len())
+    } else {
+        0
+    };
+
+    while i < lines.len() {
+        if lines[i].trim().is_empty() {
+            i += 1;
+            continue;
+        }
+        let block_end = lines[i..]
+            .iter()
+            .position(|l| l.trim().is_empty())
+            .map_or(lines.len(), |n| i + n);
+        let first = lines[i].trim();
+        if webvtt
+            && ["NOTE", "STYLE", "REGION"]
+                .iter()
+                .any(|kw| first == *kw || first.starts_with(&format!("{kw} ")))
+        {
+            i = block_end;
+            continue;
+        }
+
+        // Skip the cue number (SRT) or identifier (WebVTT).
+        let timing_at = if first.contains("-->") { i } else { i + 1 };
+        let Some(timing) = lines.get(timing_at).filter(|_| timing_at < block_end) else {
+            return Err(format!("line {}: cue has no timing line", i + 1));
+        };
+        let (start_ms, end_ms) =
+            parse_timing(timing, decimal).map_err(|e| format!("line {}: {e}", timing_at + 1))?;
+        let text = lines[timing_at + 1..block_end]
+            .iter()
+            .map(|l| l.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+        segments.push(TranscriptSegment {
+            start_ms,
+            end_ms,
+            text,
+        });
+        i = block_end;
+    }
+    Ok(Transcript {
+        segments,
+        timed: true,
+    })
+}
+
+/// `start --> end`, ignoring WebVTT cue settings after the end time.
+fn parse_timing(line: &str, decimal: char) -> Result<(u32, u32), String> {
+    let expected = || {
+        format!(
+            "expected \"HH:MM:SS{decimal}mmm --> HH:MM:SS{decimal}mmm\", got \"{}\"",
+            line.trim()
+        )
+    };
+    let (start, rest) = line.split_once("-->").ok_or_else(expected)?;
+    let end = rest.split_whitespace().next().ok_or_else(expected)?;
+    let start_ms = parse_timestamp(start.trim(), decimal).ok_or_else(expected)?;
+    let end_ms = parse_timestamp(end, decimal).ok_or_else(expected)?;
+    if end_ms < start_ms {
+        return Err(format!("cue ends before it starts: \"{}\"", line.trim()));
+    }
+    Ok((start_ms, end_ms))
+}
+
+/// `[HH:]MM:SS<decimal>mmm`. SRT always has hours; WebVTT may omit them.
+fn parse_timestamp(text: &str, decimal: char) -> Option<u32> {
+    let (clock, millis) = text.split_once(decimal)?;
+    if millis.len() != 3 {
+        return None;
+    }
+    let mut parts: Vec<&str> = clock.split(':').collect();
+    if parts.len() == 2 && decimal == '.' {
+        parts.insert(0, "0");
+    }
+    let [hours, minutes, seconds] = parts[..] else {
+        return None;
+    };
+    let number = |s: &str| {
+        (!s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| s.parse::<u32>().ok())
+            .flatten()
+    };
+    let (hours, minutes, seconds, millis) = (
+        number(hours)?,
+        number(minutes)?,
+        number(seconds)?,
+        number(millis)?,
+    );
+    if minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+    hours
+        .checked_mul(3_600_000)?
+        .checked_add(minutes * 60_000 + seconds * 1000 + millis)
+}
+
+fn timestamp(ms: u32, decimal: char) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{decimal}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+/// `MM:SS`, or `H:MM:SS` past the first hour.
+fn short_timestamp(ms: u32) -> String {
+    let (hours, minutes, seconds) = (ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}")
+    }
+}
+
+/// Paragraphs as segments. Timed only when every paragraph starts with a
+/// `**[MM:SS]**` (or bare `[MM:SS]`) stamp; otherwise stamps are dropped and
+/// the transcript is untimed.
+fn parse_markdown(input: &str) -> Transcript {
+    let mut paragraphs: Vec<(Option<u32>, String)> = Vec::new();
+    let mut current: Option<(Option<u32>, String)> = None;
+    for line in input.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            paragraphs.extend(current.take());
+            continue;
+        }
+        let line = ["- ", "* "]
+            .iter()
+            .find_map(|marker| line.strip_prefix(marker))
+            .unwrap_or(line);
+        match &mut current {
+            Some((_, text)) => {
+                text.push(' ');
+                text.push_str(line);
+            }
+            None => current = Some(split_stamp(line)),
+        }
+    }
+    paragraphs.extend(current);
+
+    let timed = !paragraphs.is_empty() && paragraphs.iter().all(|(start, _)| start.is_some());
+    let starts: Vec<u32> = paragraphs
+        .iter()
+        .map(|(start, _)| start.unwrap_or(0))
+        .collect();
+    let segments = paragraphs
+        .into_iter()
+        .enumerate()
+        .map(|(index, (start, text))| match start.filter(|_| timed) {
+            Some(start_ms) => TranscriptSegment {
+                start_ms,
+                end_ms: starts
+                    .get(index + 1)
+                    .copied()
+                    .unwrap_or(start_ms)
+                    .max(start_ms),
+                text,
+            },
+            None => untimed(&text),
+        })
+        .collect();
+    Transcript { segments, timed }
+}
+
+/// A leading `**[MM:SS]**`, `**[H:MM:SS]**`, or `[MM:SS]` stamp and the rest.
+fn split_stamp(line: &str) -> (Option<u32>, String) {
+    let (inner, bold) = match line.strip_prefix("**[") {
+        Some(rest) => (rest, true),
+        None => match line.strip_prefix('[') {
+            Some(rest) => (rest, false),
+            None => return (None, line.to_string()),
+        },
+    };
+    let close = if bold { "]**" } else { "]" };
+    let Some((stamp, rest)) = inner.split_once(close) else {
+        return (None, line.to_string());
+    };
+    let mut ms = 0u32;
+    let parts: Vec<&str> = stamp.split(':').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return (None, line.to_string());
+    }
+    for part in parts {
+        match part.parse::<u32>() {
+            Ok(value) if !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()) => {
+                ms = ms.saturating_mul(60).saturating_add(value)
+            }
+            _ => return (None, line.to_string()),
+        }
+    }
+    (Some(ms.saturating_mul(1000)), rest.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start_ms: u32, end_ms: u32, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn srt_round_trips_through_vtt_and_markdown() {
+        let srt = "1\r\n00:00:01,500 --> 00:00:03,000\r\nHello there.\r\n\r\n2\r\n00:01:02,000 --> 00:01:04,250\r\nTwo\r\nlines.\r\n";
+
+        let transcript = parse(srt, TranscriptFormat::Srt).unwrap();
+        assert_eq!(
+            transcript.segments,
+            vec![
+                segment(1500, 3000, "Hello there."),
+                segment(62_000, 64_250, "Two lines."),
+            ]
+        );
+
+        let vtt = render(&transcript, TranscriptFormat::Vtt).unwrap();
+        assert!(vtt.starts_with("WEBVTT\n\n00:00:01.500 --> 00:00:03.000\nHello there.\n"));
+        assert_eq!(parse(&vtt, TranscriptFormat::Vtt).unwrap(), transcript);
+
+        let markdown = render(&transcript, TranscriptFormat::Markdown).unwrap();
+        assert_eq!(
+            markdown,
+            "**[00:01]** Hello there.\n\n**[01:02]** Two lines.\n\n"
+        );
+        let notes = parse(&markdown, TranscriptFormat::Markdown).unwrap();
+        assert!(notes.timed);
+        assert_eq!(notes.segments[0], segment(1000, 62_000, "Hello there."));
+    }
+
+    #[test]
+    fn malformed_timing_reports_its_line() {
+        let srt =
+            "1\n00:00:01,000 --> 00:00:02,000\nFine.\n\n2\n00:00:03.000 --> 00:00:04,000\nBad.\n";
+        let err = parse(srt, TranscriptFormat::Srt).unwrap_err();
+        assert!(err.starts_with("line 6:"), "{err}");
+
+        let err = parse("1\n00:00:01,000\n", TranscriptFormat::Srt).unwrap_err();
+        assert!(err.starts_with("line 2:"), "{err}");
+        assert!(parse("00:01.000 --> 00:02.000\nHi\n", TranscriptFormat::Vtt).is_err());
+    }
+
+    #[test]
+    fn untimed_text_cannot_become_subtitles() {
+        let transcript = parse("First line.\n\nSecond line.\n", TranscriptFormat::Text).unwrap();
+        assert!(!transcript.timed);
+        assert_eq!(transcript.segments.len(), 2);
+        assert!(render(&transcript, TranscriptFormat::Srt).is_err());
+        assert_eq!(
+            render(&transcript, TranscriptFormat::Markdown).unwrap(),
+            "First line.\n\nSecond line.\n\n"
+        );
+    }
+}
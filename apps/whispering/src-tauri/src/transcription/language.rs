@@ -0,0 +1,132 @@
+//! Post-transcription sanity check: does the output's writing system match
+//! the language the user forced?
+//!
+//! Whisper with a forced `language` will still produce *something* for audio
+//! in another language, usually garbled or translated text. We cannot detect
+//! the spoken language cheaply, but the script of the output is a strong hint:
+//! mostly Han characters under `language: "en"` means the setting is wrong.
+//!
+//! Purely advisory. The caller emits a warning; the transcript is untouched.
+
+use serde::{Deserialize, Serialize};
+
+use super::events::TranscriptionLanguageMismatch;
+
+/// Fewer letters than this is too little evidence ("OK", a name) to judge.
+const MIN_LETTERS: usize = 12;
+
+/// Share of letters that must be in the expected script(s).
+const MIN_EXPECTED_SHARE: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum Script {
+    Latin,
+    Cyrillic,
+    Greek,
+    Arabic,
+    Hebrew,
+    Devanagari,
+    Thai,
+    Han,
+    Kana,
+    Hangul,
+}
+
+impl Script {
+    fn of(c: char) -> Option<Self> {
+        let script = match c as u32 {
+            0x0041..=0x005A | 0x0061..=0x007A | 0x00C0..=0x024F | 0x1E00..=0x1EFF => Self::Latin,
+            0x0370..=0x03FF | 0x1F00..=0x1FFF => Self::Greek,
+            0x0400..=0x052F => Self::Cyrillic,
+            0x0590..=0x05FF => Self::Hebrew,
+            0x0600..=0x06FF | 0x0750..=0x077F => Self::Arabic,
+            0x0900..=0x097F => Self::Devanagari,
+            0x0E00..=0x0E7F => Self::Thai,
+            0x3040..=0x30FF => Self::Kana,
+            0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Self::Hangul,
+            0x4E00..=0x9FFF | 0x3400..=0x4DBF => Self::Han,
+            _ => return None,
+        };
+        Some(script)
+    }
+}
+
+/// Scripts a language is normally written in. `None` for languages we do not
+/// know, which skips the check rather than guessing.
+fn expected_scripts(language: &str) -> Option<&'static [Script]> {
+    use Script::*;
+    let scripts: &'static [Script] = match language {
+        "ru" | "uk" | "be" | "bg" | "mk" | "kk" | "mn" => &[Cyrillic],
+        "sr" => &[Cyrillic, Latin],
+        "el" => &[Greek],
+        "ar" | "fa" | "ur" | "ps" => &[Arabic],
+        "he" | "yi" => &[Hebrew],
+        "hi" | "mr" | "ne" | "sa" => &[Devanagari],
+        "th" => &[Thai],
+        "zh" | "yue" => &[Han],
+        "ja" => &[Han, Kana],
+        "ko" => &[Hangul, Han],
+        "en" | "fr" | "de" | "es" | "it" | "pt" | "nl" | "sv" | "da" | "no" | "nn" | "fi"
+        | "pl" | "cs" | "sk" | "sl" | "hr" | "bs" | "ro" | "hu" | "tr" | "vi" | "id" | "ms"
+        | "tl" | "ca" | "gl" | "eu" | "et" | "lv" | "lt" | "is" | "ga" | "cy" | "sq" | "af"
+        | "sw" | "az" | "uz" | "mt" | "la" | "haw" | "jw" | "su" | "yo" | "so" | "ln" => &[Latin],
+        _ => return None,
+    };
+    Some(scripts)
+}
+
+/// `Some` when `text` is mostly written in a script `requested_language` does
+/// not use. `"auto"`, unknown languages, and short outputs always pass.
+pub fn check_script(requested_language: &str, text: &str) -> Option<TranscriptionLanguageMismatch> {
+    let expected = expected_scripts(requested_language)?;
+
+    let mut counts: Vec<(Script, usize)> = Vec::new();
+    let mut total = 0;
+    for script in text.chars().filter_map(Script::of) {
+        total += 1;
+        match counts.iter_mut().find(|(s, _)| *s == script) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((script, 1)),
+        }
+    }
+    if total < MIN_LETTERS {
+        return None;
+    }
+
+    let in_expected: usize = counts
+        .iter()
+        .filter(|(script, _)| expected.contains(script))
+        .map(|(_, n)| n)
+        .sum();
+    if in_expected as f32 / total as f32 >= MIN_EXPECTED_SHARE {
+        return None;
+    }
+
+    let (detected_script, detected) = counts.into_iter().max_by_key(|(_, n)| *n)?;
+    Some(TranscriptionLanguageMismatch {
+        requested_language: requested_language.to_string(),
+        detected_script,
+        detected_share: detected as f32 / total as f32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_cjk_output_when_english_was_requested() {
+        let mismatch = check_script("en", "今天我们讨论一下项目的进度和下一步计划").unwrap();
+        assert_eq!(mismatch.detected_script, Script::Han);
+        assert!(mismatch.detected_share > 0.9);
+    }
+
+    #[test]
+    fn passes_matching_short_and_unknown_languages() {
+        assert!(check_script("en", "We shipped the release on Friday.").is_none());
+        assert!(check_script("ja", "今日はとても良い天気ですね、散歩に行きましょう").is_none());
+        assert!(check_script("en", "東京").is_none());
+        assert!(check_script("auto", "今天我们讨论一下项目的进度和下一步计划").is_none());
+    }
+}
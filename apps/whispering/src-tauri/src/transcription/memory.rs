@@ -0,0 +1,163 @@
+//! Pre-load memory guard. Loading a model larger than the free RAM does not
+//! fail cleanly: the OS kills the whole app mid-load. Estimating the peak
+//! footprint from the model's size on disk and comparing it to available
+//! memory turns that silent crash into an `InsufficientMemory` error.
+//!
+//! The estimate is deliberately rough (file size times a multiplier), so both
+//! the multiplier and whether the guard blocks at all are user-configurable.
+//!
+//! `estimate_model_memory` answers the same question for display, before
+//! anything is loaded. It uses a per-engine multiplier instead of the guard's
+//! single one, since the runtimes differ more than one knob can express.
+
+use sysinfo::System;
+
+use super::config::Engine;
+
+/// Peak RAM per byte of model on disk. Engines mmap or copy the weights and
+/// then allocate working buffers on top, so the footprint is somewhat larger
+/// than the file.
+pub const DEFAULT_MEMORY_MULTIPLIER: f32 = 1.2;
+
+/// Bounds for a user-supplied multiplier. Below 1.0 the guard would claim a
+/// model needs less RAM than its own weights; above 10.0 it would block
+/// every load on any real machine.
+const MIN_MEMORY_MULTIPLIER: f32 = 1.0;
+const MAX_MEMORY_MULTIPLIER: f32 = 10.0;
+
+/// Peak RAM per byte on disk for whisper.cpp: ggml maps the weights nearly
+/// one to one, plus compute buffers of a few hundred MB at most.
+pub const WHISPER_MEMORY_MULTIPLIER: f32 = 1.1;
+
+/// Peak RAM per byte on disk for the ONNX Runtime engines (Parakeet,
+/// Moonshine). The runtime copies the weights into its own arena and
+/// pre-allocates activation buffers, so the overhead is larger.
+pub const ONNX_MEMORY_MULTIPLIER: f32 = 1.5;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemoryGuard {
+    /// When false the estimate is still logged but never blocks a load.
+    pub enforce: bool,
+    pub multiplier: f32,
+}
+
+impl Default for MemoryGuard {
+    fn default() -> Self {
+        Self {
+            enforce: true,
+            multiplier: DEFAULT_MEMORY_MULTIPLIER,
+        }
+    }
+}
+
+/// A load the guard refused, in whole megabytes for display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryShortfall {
+    pub required_mb: u32,
+    pub available_mb: u32,
+}
+
+impl MemoryGuard {
+    pub fn new(enforce: bool, multiplier: f32) -> Result<Self, String> {
+        if !(MIN_MEMORY_MULTIPLIER..=MAX_MEMORY_MULTIPLIER).contains(&multiplier) {
+            return Err(format!(
+                "Memory multiplier must be between {MIN_MEMORY_MULTIPLIER} and {MAX_MEMORY_MULTIPLIER}, got {multiplier}"
+            ));
+        }
+        Ok(Self {
+            enforce,
+            multiplier,
+        })
+    }
+
+    /// Estimated peak RAM, in bytes, for a model of `model_bytes` on disk.
+    pub fn estimate_bytes(&self, model_bytes: u64) -> u64 {
+        (model_bytes as f64 * self.multiplier as f64) as u64
+    }
+
+    /// Compare the estimate against `available_bytes`. Always passes when the
+    /// guard is not enforced.
+    pub fn check(&self, model_bytes: u64, available_bytes: u64) -> Result<(), MemoryShortfall> {
+        let required = self.estimate_bytes(model_bytes);
+        if !self.enforce || required <= available_bytes {
+            return Ok(());
+        }
+        Err(MemoryShortfall {
+            required_mb: bytes_to_mb(required),
+            available_mb: bytes_to_mb(available_bytes),
+        })
+    }
+}
+
+/// Rough peak RAM, in bytes, for loading `model_bytes` of `engine` weights.
+/// Measured against typical models on desktop builds; quantization, thread
+/// count, and GPU offload all shift the real figure, so treat it as a
+/// ballpark for the settings UI, not a guarantee.
+pub fn estimate_model_bytes(engine: Engine, model_bytes: u64) -> u64 {
+    let multiplier = match engine {
+        Engine::Whispercpp => WHISPER_MEMORY_MULTIPLIER,
+        Engine::Parakeet | Engine::Moonshine => ONNX_MEMORY_MULTIPLIER,
+    };
+    (model_bytes as f64 * multiplier as f64) as u64
+}
+
+/// Memory the OS can hand out right now without swapping (free plus
+/// reclaimable cache). `None` when the platform reports nothing usable, in
+/// which case the guard stands aside rather than blocking blindly.
+pub fn available_memory_bytes() -> Option<u64> {
+    let mut system = System::new();
+    system.refresh_memory();
+    Some(system.available_memory()).filter(|&bytes| bytes > 0)
+}
+
+pub fn bytes_to_mb(bytes: u64) -> u32 {
+    (bytes / BYTES_PER_MB).min(u32::MAX as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1024 * BYTES_PER_MB;
+
+    #[test]
+    fn guard_rejects_out_of_range_multipliers() {
+        assert!(MemoryGuard::new(true, 0.5).is_err());
+        assert!(MemoryGuard::new(true, 11.0).is_err());
+        assert!(MemoryGuard::new(true, f32::NAN).is_err());
+        assert!(MemoryGuard::new(false, 2.0).is_ok());
+    }
+
+    #[test]
+    fn guard_blocks_only_when_enforced_and_short() {
+        let guard = MemoryGuard::new(true, 1.5).unwrap();
+        assert!(guard.check(GB, 2 * GB).is_ok());
+        assert_eq!(
+            guard.check(2 * GB, 2 * GB),
+            Err(MemoryShortfall {
+                required_mb: 3072,
+                available_mb: 2048,
+            })
+        );
+
+        let advisory = MemoryGuard::new(false, 1.5).unwrap();
+        assert!(advisory.check(2 * GB, 2 * GB).is_ok());
+    }
+
+    #[test]
+    fn estimates_scale_by_engine() {
+        assert_eq!(
+            bytes_to_mb(estimate_model_bytes(
+                Engine::Whispercpp,
+                1000 * BYTES_PER_MB
+            )),
+            1100
+        );
+        assert_eq!(
+            bytes_to_mb(estimate_model_bytes(Engine::Parakeet, 1000 * BYTES_PER_MB)),
+            1500
+        );
+    }
+}
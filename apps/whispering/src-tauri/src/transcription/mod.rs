@@ -1,14 +1,49 @@
+mod capabilities;
 mod config;
+mod context;
+mod corrections;
+mod detailed;
+mod detect;
+mod diff;
+mod disfluency;
 mod error;
 mod events;
+mod formats;
+mod language;
+mod memory;
 mod model_manager;
+mod profile;
+mod samples;
+mod segment_limit;
+mod whisper_params;
 
-use crate::recorder::read_artifact_samples;
-pub use config::TranscriptionConfig;
+use crate::audio::{decode_to_pcm16k_mono_with, resample_mono, Downmix};
+use crate::download::{fetch_to_memory, DownloadManager, DownloadProgress, MAX_FETCH_BYTES};
+use crate::recorder::{read_artifact_channel_samples, read_artifact_samples_with};
+use crate::temp_files::{atomic_write, StagingDirs};
+use crate::timings::{record_stage, PipelineStage};
+pub use capabilities::ModelCapabilities;
+pub use config::{AutoModelRule, Chunking, Engine, TranscriptionConfig, TranscriptionOverrides};
+pub use corrections::{CorrectionPreview, CorrectionRule, CorrectionSpan};
+pub use detailed::{DetailedTranscription, TranscriptSegment};
+pub use detect::EngineInference;
+pub use diff::{DiffOp, TranscriptDiff};
 pub use error::TranscriptionError;
-pub use events::{LocalModelState, ModelStateEvent};
+pub use events::{
+    AutoModelRuleError, LoadedModelInfo, LocalModelState, ModelStateEvent,
+    TranscriptionLanguageMismatch, TranscriptionProgress,
+};
+pub use formats::TranscriptFormat;
+use memory::MemoryGuard;
 pub use model_manager::ModelManager;
-use tauri::{AppHandle, State};
+use profile::{ImportedSettingsProfile, SettingsProfile};
+pub use segment_limit::SegmentLimit;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Instant;
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Manager, State};
+pub use whisper_params::WhisperDefaults;
 
 /// Push the ambient transcription configuration. Replaces the per-call
 /// `config` argument that `transcribe_recording` used to take. The FE
@@ -41,6 +76,194 @@ pub fn get_transcription_state(model_manager: State<'_, ModelManager>) -> LocalM
     model_manager.snapshot()
 }
 
+/// Whether a local transcription is running or queued, e.g. to disable the
+/// transcribe button. Authoritative across a webview reload, unlike state
+/// the frontend tracks itself. Stays true from the start of a call to its
+/// end, including model loading and between the windows of a chunked
+/// transcription; `get_transcription_state` only reports the engine call.
+#[tauri::command]
+#[specta::specta]
+pub fn is_transcribing(model_manager: State<'_, ModelManager>) -> bool {
+    model_manager.is_transcribing()
+}
+
+/// The model resident in memory right now, when it was loaded, how long it
+/// has sat idle, and how long until the unload policy drops it, e.g. for
+/// "Parakeet loaded, unloads in 4:12" in settings. Never waits on an
+/// in-flight transcription.
+#[tauri::command]
+#[specta::specta]
+pub fn get_loaded_model_info(model_manager: State<'_, ModelManager>) -> LoadedModelInfo {
+    model_manager.loaded_model_info()
+}
+
+/// Clear the prompt context carried between transcriptions when
+/// `carryContext` is on, so the next one is not primed with text from an
+/// unrelated earlier dictation.
+#[tauri::command]
+#[specta::specta]
+pub fn reset_transcription_context(model_manager: State<'_, ModelManager>) {
+    model_manager.reset_context();
+}
+
+/// Abort the running local transcription and any queued behind it; each
+/// returns `Cancelled`. Queued calls are dropped before they reach the
+/// engine. The engines expose no abort hook, so a call already inside the
+/// engine finishes in the background and its result is thrown away.
+#[tauri::command]
+#[specta::specta]
+pub fn cancel_transcription(model_manager: State<'_, ModelManager>) {
+    model_manager.cancel_transcriptions();
+}
+
+/// Unload the resident transcription model now to free its memory (about
+/// 2 GB for a large Whisper or Parakeet model), instead of waiting for the
+/// unload policy's idle timeout. Waits for a transcription in progress to
+/// finish first. Emits `Unloaded` with reason `manual` and returns `true`
+/// when a model was resident; the next transcription reloads it.
+#[tauri::command]
+#[specta::specta]
+pub async fn unload_transcription_model(
+    model_manager: State<'_, ModelManager>,
+) -> Result<bool, TranscriptionError> {
+    let manager = model_manager.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || manager.unload_model())
+        .await
+        .map_err(join_err)
+}
+
+/// Configure the pre-load memory guard. `multiplier` scales the model's size
+/// on disk into an estimated peak RAM need (default 1.2, allowed 1.0-10.0);
+/// with `enforce` off the estimate is only logged and never blocks a load.
+#[tauri::command]
+#[specta::specta]
+pub fn set_model_memory_guard(
+    enforce: bool,
+    multiplier: f32,
+    model_manager: State<'_, ModelManager>,
+) -> Result<(), TranscriptionError> {
+    let guard = MemoryGuard::new(enforce, multiplier)
+        .map_err(|message| TranscriptionError::ConfigError { message })?;
+    model_manager.set_memory_guard(guard);
+    Ok(())
+}
+
+/// Override the whisper.cpp decoding defaults (no-speech threshold and
+/// blank / non-speech token suppression) for every later Whisper
+/// transcription. Omitted fields take the shipped defaults. Fails with
+/// `ConfigError` when `noSpeechThold` is outside 0-1.
+#[tauri::command]
+#[specta::specta]
+pub fn set_whisper_defaults(
+    defaults: WhisperDefaults,
+    model_manager: State<'_, ModelManager>,
+) -> Result<(), TranscriptionError> {
+    defaults
+        .validate()
+        .map_err(|message| TranscriptionError::ConfigError { message })?;
+    model_manager.set_whisper_defaults(defaults);
+    Ok(())
+}
+
+/// Rough peak RAM, in MB, that loading `model_name` from `engine`'s models
+/// folder will take: the model's size on disk times an engine-specific
+/// multiplier (see `memory::estimate_model_bytes`). Reads only file sizes,
+/// so the settings UI can show it next to every model. The pre-load memory
+/// guard applies its own configurable multiplier instead.
+#[tauri::command]
+#[specta::specta]
+pub fn estimate_model_memory(
+    engine: Engine,
+    model_name: String,
+    model_manager: State<'_, ModelManager>,
+) -> Result<u32, TranscriptionError> {
+    let path = model_manager
+        .resolve_model_path(engine, &model_name)
+        .map_err(|message| TranscriptionError::ConfigError { message })?;
+    let model_bytes =
+        model_manager::model_size_bytes(&path).ok_or_else(|| TranscriptionError::ConfigError {
+            message: format!("Cannot read the size of {}", path.display()),
+        })?;
+    Ok(memory::bytes_to_mb(memory::estimate_model_bytes(
+        engine,
+        model_bytes,
+    )))
+}
+
+/// Inspect `model_name` in `engine`'s models folder and report which engine
+/// it actually looks like (from magic bytes or folder layout), so the UI can
+/// flag a Whisper file sitting in the Parakeet folder before transcription
+/// fails on it. `Unknown` carries a reason when no engine matches.
+#[tauri::command]
+#[specta::specta]
+pub fn infer_engine_for_model(
+    engine: Engine,
+    model_name: String,
+    model_manager: State<'_, ModelManager>,
+) -> Result<EngineInference, TranscriptionError> {
+    let path = model_manager
+        .resolve_model_path(engine, &model_name)
+        .map_err(|message| TranscriptionError::ConfigError { message })?;
+    Ok(detect::infer_engine(&path))
+}
+
+/// What `model_name` supports: other languages, which ones, and translation.
+/// Inferred from the engine's naming conventions (e.g. Whisper's `.en`
+/// suffix) so the settings UI can disable the language picker for
+/// English-only models without loading anything. Unrecognized names get a
+/// conservative answer that restricts nothing.
+#[tauri::command]
+#[specta::specta]
+pub fn get_model_capabilities(engine: Engine, model_name: String) -> ModelCapabilities {
+    capabilities::model_capabilities(engine, &model_name)
+}
+
+/// Export the Rust-managed transcription settings, plus the FE-owned
+/// `preferred_device` and `correction_rules`, as a versioned JSON profile
+/// that can be shared and re-applied with `import_settings_profile`.
+///
+/// Returns `NoConfig` if the FE has not pushed a config yet.
+#[tauri::command]
+#[specta::specta]
+pub fn export_settings_profile(
+    preferred_device: Option<String>,
+    correction_rules: Vec<CorrectionRule>,
+    model_manager: State<'_, ModelManager>,
+) -> Result<String, TranscriptionError> {
+    let config = model_manager
+        .current_config()
+        .ok_or_else(|| TranscriptionError::NoConfig {
+            message: "No transcription settings to export yet.".to_string(),
+        })?;
+    let profile = SettingsProfile::new(&config, preferred_device, correction_rules);
+    serde_json::to_string_pretty(&profile).map_err(|e| TranscriptionError::ConfigError {
+        message: format!("Failed to serialize settings profile: {e}"),
+    })
+}
+
+/// Validate and apply a profile produced by `export_settings_profile`.
+///
+/// Nothing is applied unless the whole profile is valid and its model exists
+/// in this machine's models folder. Returns the applied config so the FE can
+/// persist it into its own settings store (which stays the source of truth
+/// and pushes it back through `set_transcription_config`), along with the
+/// preferred device and correction rules, which only the FE stores.
+#[tauri::command]
+#[specta::specta]
+pub fn import_settings_profile(
+    json: String,
+    model_manager: State<'_, ModelManager>,
+) -> Result<ImportedSettingsProfile, TranscriptionError> {
+    let imported = SettingsProfile::parse(&json)
+        .map_err(|message| TranscriptionError::ConfigError { message })?
+        .into_imported();
+    model_manager
+        .check_config(&imported.config)
+        .map_err(|message| TranscriptionError::ConfigError { message })?;
+    model_manager.set_transcription_config(imported.config.clone());
+    Ok(imported)
+}
+
 /// Canonical transcribe-by-id path. Resolves the audio file under
 /// `<appDataDir>/recordings/{recordingId}.*` (cpal-written WAV,
 /// navigator-saved webm/opus/mp4, etc.), decodes, runs inference using
@@ -54,8 +277,150 @@ pub async fn transcribe_recording(
     app_handle: AppHandle,
     model_manager: State<'_, ModelManager>,
 ) -> Result<String, TranscriptionError> {
-    let samples = read_artifact_samples(&app_handle, &recording_id)
+    let samples = read_recording(&app_handle, &model_manager, &recording_id)?;
+
+    let manager = model_manager.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || manager.transcribe(samples))
+        .await
+        .map_err(join_err)?
+}
+
+/// Download the audio at `url` and transcribe it with the ambient
+/// configuration, for a recording shared as a link. Any format
+/// `transcribe_recording` reads works.
+///
+/// The file is downloaded into memory and decoded there, like a recording
+/// artifact, so no temp file is left behind on failure. Download progress
+/// goes to `on_progress`, and `cancel_download(download_id)` stops the
+/// download. A file over 512 MB, a download taking over 10 minutes, or a
+/// failed request is an `AudioReadError`.
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_url(
+    url: String,
+    download_id: String,
+    on_progress: Channel<DownloadProgress>,
+    app_handle: AppHandle,
+    downloads: State<'_, DownloadManager>,
+    model_manager: State<'_, ModelManager>,
+) -> Result<String, TranscriptionError> {
+    let bytes = downloads
+        .run(&download_id, {
+            let url = url.clone();
+            async move { fetch_to_memory(&url, MAX_FETCH_BYTES, on_progress).await }
+        })
+        .await
+        .map_err(|e| TranscriptionError::AudioReadError {
+            message: format!("download {url}: {e}"),
+        })?;
+
+    let manager = model_manager.inner().clone();
+    let downmix = ambient_downmix(&manager);
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        let samples = decode_to_pcm16k_mono_with(&bytes, &downmix).map_err(|e| {
+            TranscriptionError::AudioReadError {
+                message: format!("decode {url}: {e}"),
+            }
+        })?;
+        record_stage(&app_handle, PipelineStage::Conversion, started.elapsed());
+        manager.transcribe(samples)
+    })
+    .await
+    .map_err(join_err)?
+}
+
+/// `transcribe_recording` with the segment timings kept, for a clickable
+/// transcript synced to playback. Same artifact lookup, decode, and ambient
+/// configuration; `segments` is empty for engines that report no timing
+/// (Moonshine).
+///
+/// `max_segment` splits longer segments at word boundaries, e.g. to 42
+/// characters for subtitles; the split pieces' timings are estimated from
+/// their share of the text. `text` is unaffected.
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_recording_with_segments(
+    recording_id: String,
+    max_segment: Option<SegmentLimit>,
+    app_handle: AppHandle,
+    model_manager: State<'_, ModelManager>,
+) -> Result<DetailedTranscription, TranscriptionError> {
+    if let Some(limit) = &max_segment {
+        limit
+            .validate()
+            .map_err(|message| TranscriptionError::ConfigError { message })?;
+    }
+    let samples = read_recording(&app_handle, &model_manager, &recording_id)?;
+
+    let manager = model_manager.inner().clone();
+    let mut result =
+        tauri::async_runtime::spawn_blocking(move || manager.transcribe_detailed(samples))
+            .await
+            .map_err(join_err)??;
+    if let Some(limit) = max_segment {
+        result.segments = segment_limit::split_segments(result.segments, limit);
+    }
+    Ok(result)
+}
+
+/// Transcribe caller-provided mono samples and return everything the engine
+/// reports: text, timed segments, and timing. `samples` at any
+/// `sample_rate` are resampled to 16 kHz first.
+///
+/// Uses the ambient configuration like every other transcription command,
+/// with the fields set in `overrides` (engine and model, language, prompt,
+/// task, timestamp granularity) replaced for this call only; this is the
+/// same `ModelManager` dispatch they go through, minus the artifact lookup.
+/// Meant for callers that already hold PCM (tests, tooling); recordings
+/// should go through `transcribe_recording` so the audio does not cross IPC.
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_samples_detailed(
+    samples: Vec<f32>,
+    sample_rate: u32,
+    overrides: Option<TranscriptionOverrides>,
+    model_manager: State<'_, ModelManager>,
+) -> Result<DetailedTranscription, TranscriptionError> {
+    if sample_rate == 0 {
+        return Err(TranscriptionError::AudioReadError {
+            message: "Sample rate must be greater than 0".to_string(),
+        });
+    }
+    let overrides = overrides.unwrap_or_default();
+    overrides
+        .validate()
+        .map_err(|message| TranscriptionError::ConfigError { message })?;
+    let manager = model_manager.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let samples = resample_mono(samples, sample_rate, samples::SAMPLE_RATE).map_err(|e| {
+            TranscriptionError::AudioReadError {
+                message: e.to_string(),
+            }
+        })?;
+        manager.transcribe_detailed_with(samples, &overrides)
+    })
+    .await
+    .map_err(join_err)?
+}
+
+/// Transcribe one channel of a multichannel recording (0-based
+/// `channel_index`), e.g. only the interviewer's mic of a stereo interview
+/// capture. The channel is de-interleaved before resampling, so the other
+/// channels never bleed into the transcript. Fails with `AudioReadError` when
+/// the file has no such channel.
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_recording_channel(
+    recording_id: String,
+    channel_index: u16,
+    app_handle: AppHandle,
+    model_manager: State<'_, ModelManager>,
+) -> Result<String, TranscriptionError> {
+    let started = Instant::now();
+    let samples = read_artifact_channel_samples(&app_handle, &recording_id, channel_index)
         .map_err(|e| TranscriptionError::AudioReadError { message: e })?;
+    record_stage(&app_handle, PipelineStage::Conversion, started.elapsed());
 
     let manager = model_manager.inner().clone();
     tauri::async_runtime::spawn_blocking(move || manager.transcribe(samples))
@@ -63,6 +428,168 @@ pub async fn transcribe_recording(
         .map_err(join_err)?
 }
 
+/// Transcribe a recording with the current ambient configuration and diff
+/// the result word by word against `previous_text`, e.g. the transcript from
+/// before denoise was switched on or from another model. Tells the user
+/// whether a settings change actually improved the output without reading
+/// both transcripts side by side.
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_and_diff(
+    recording_id: String,
+    previous_text: String,
+    app_handle: AppHandle,
+    model_manager: State<'_, ModelManager>,
+) -> Result<TranscriptDiff, TranscriptionError> {
+    let samples = read_recording(&app_handle, &model_manager, &recording_id)?;
+
+    let manager = model_manager.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let text = manager.transcribe(samples)?;
+        Ok(diff::diff_transcripts(&previous_text, text))
+    })
+    .await
+    .map_err(join_err)?
+}
+
+/// Transcript of a slice of a recording. `start_ms`/`end_ms` are positions
+/// in the original recording (not the slice), so the UI can place the text
+/// on the full recording's timeline.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RangeTranscription {
+    pub text: String,
+    pub start_ms: u32,
+    pub end_ms: u32,
+}
+
+/// Transcribe only `[start_seconds, end_seconds)` of a recording, using the
+/// ambient configuration. The decoded samples are sliced before they reach
+/// the engine, so a spot check of a long meeting costs only the slice's
+/// inference time. The range is validated against the decoded duration.
+///
+/// `context_padding_ms` feeds that much audio before `start_seconds` to the
+/// engine as well (clamped at the start of the recording), so a word right
+/// at the cut is not clipped. What the engine hears in the padding is
+/// trimmed from the text again; see
+/// `DetailedTranscription::trim_leading_context` for how the boundary is
+/// deduplicated when stitching consecutive ranges.
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_recording_range(
+    recording_id: String,
+    start_seconds: f64,
+    end_seconds: f64,
+    context_padding_ms: Option<u32>,
+    app_handle: AppHandle,
+    model_manager: State<'_, ModelManager>,
+) -> Result<RangeTranscription, TranscriptionError> {
+    let samples = read_recording(&app_handle, &model_manager, &recording_id)?;
+    let range = samples::sample_range(samples.len(), start_seconds, end_seconds)
+        .map_err(|message| TranscriptionError::ConfigError { message })?;
+    let start_ms = samples::samples_to_ms(range.start);
+    let end_ms = samples::samples_to_ms(range.end);
+    let padding = samples::ms_to_samples(context_padding_ms.unwrap_or(0)).min(range.start);
+    let padded_start = range.start - padding;
+    let slice = samples[padded_start..range.end].to_vec();
+
+    let manager = model_manager.inner().clone();
+    let mut result =
+        tauri::async_runtime::spawn_blocking(move || manager.transcribe_detailed(slice))
+            .await
+            .map_err(join_err)??;
+    result.trim_leading_context(samples::samples_to_ms(padding));
+    Ok(RangeTranscription {
+        text: result.text,
+        start_ms,
+        end_ms,
+    })
+}
+
+/// Raw and cleaned forms of a transcript, so the UI can show (or undo) the
+/// clean-up instead of silently replacing what the engine heard.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanedTranscript {
+    pub raw: String,
+    pub cleaned: String,
+}
+
+/// Strip filler words and collapse stutters ("the the" -> "the").
+///
+/// `filler_words` replaces the built-in list when given (multi-word entries
+/// like "you know" are allowed). Without it only unambiguous fillers ("um",
+/// "uh") are removed; `aggressive` adds words that can carry meaning, such
+/// as "like" and "you know".
+#[tauri::command]
+#[specta::specta]
+pub fn clean_transcript(
+    text: String,
+    filler_words: Option<Vec<String>>,
+    aggressive: bool,
+) -> CleanedTranscript {
+    let fillers = disfluency::filler_phrases(filler_words, aggressive);
+    let cleaned = disfluency::clean_transcript(&text, &fillers);
+    CleanedTranscript { raw: text, cleaned }
+}
+
+/// Run correction rules over `sample_text` without transcribing anything,
+/// so a rule editor can show what each rule does before it is saved. Returns
+/// the corrected text, how many replacements each rule made (a zero is a
+/// rule that never fired), and the replaced spans for highlighting. Fails
+/// with `ConfigError` when a rule has an empty find text.
+#[tauri::command]
+#[specta::specta]
+pub fn preview_corrections(
+    sample_text: String,
+    rules: Vec<CorrectionRule>,
+) -> Result<CorrectionPreview, TranscriptionError> {
+    corrections::validate_rules(&rules)
+        .map_err(|message| TranscriptionError::ConfigError { message })?;
+    Ok(corrections::apply_corrections(&sample_text, &rules))
+}
+
+/// Convert a saved transcript between SRT, WebVTT, plain text, JSON, and
+/// markdown without transcribing again. Both paths must be absolute; the
+/// output is written atomically, replacing any existing file. Returns the
+/// number of segments written.
+///
+/// Fails on malformed input, naming the line for a bad subtitle timing, and
+/// when an untimed transcript (plain text, or markdown without timestamps)
+/// is converted to SRT or WebVTT.
+#[tauri::command]
+#[specta::specta]
+pub async fn convert_transcript(
+    app: AppHandle,
+    input_path: String,
+    input_format: TranscriptFormat,
+    output_path: String,
+    output_format: TranscriptFormat,
+) -> Result<u32, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let (input, output) = (Path::new(&input_path), Path::new(&output_path));
+        if !input.is_absolute() || !output.is_absolute() {
+            return Err(format!(
+                "Transcript paths must be absolute: {input_path}, {output_path}"
+            ));
+        }
+
+        let content = std::fs::read_to_string(input)
+            .map_err(|e| format!("Failed to read {input_path}: {e}"))?;
+        let transcript = formats::parse(&content, input_format)
+            .map_err(|e| format!("Invalid transcript {input_path}: {e}"))?;
+        let rendered = formats::render(&transcript, output_format)?;
+
+        app.state::<StagingDirs>().remember_parent(output);
+        atomic_write(output, rendered.as_bytes())
+            .map_err(|e| format!("Failed to write {output_path}: {e}"))?;
+
+        Ok(transcript.segments.len() as u32)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 /// Map a join failure from spawn_blocking into a TranscriptionError so the
 /// frontend always sees a structured error even when the background task
 /// panics or is cancelled.
@@ -71,3 +598,26 @@ fn join_err(e: tauri::Error) -> TranscriptionError {
         message: format!("Background transcription task failed: {}", e),
     }
 }
+
+/// `read_artifact_samples` with the ambient config's downmix, recording the
+/// decode as the pipeline's conversion stage.
+fn read_recording(
+    app_handle: &AppHandle,
+    model_manager: &ModelManager,
+    recording_id: &str,
+) -> Result<Vec<f32>, TranscriptionError> {
+    let started = Instant::now();
+    let samples =
+        read_artifact_samples_with(app_handle, recording_id, &ambient_downmix(model_manager))
+            .map_err(|e| TranscriptionError::AudioReadError { message: e })?;
+    record_stage(app_handle, PipelineStage::Conversion, started.elapsed());
+    Ok(samples)
+}
+
+/// The ambient config's downmix, or plain averaging when none is set.
+fn ambient_downmix(model_manager: &ModelManager) -> Downmix {
+    model_manager
+        .current_config()
+        .and_then(|config| config.downmix)
+        .unwrap_or_default()
+}
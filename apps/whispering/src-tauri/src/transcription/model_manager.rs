@@ -1,18 +1,52 @@
-use super::config::{Engine as EngineKind, TranscriptionConfig, UnloadPolicy};
+use super::capabilities::model_capabilities;
+use super::config::{
+    validate_model_name, AutoModelRule, Chunking, Engine as EngineKind, TimestampGranularity,
+    TranscriptionConfig, TranscriptionOverrides, UnloadPolicy, WhisperTask,
+};
+use super::context;
+use super::detailed::{DetailedTranscription, TranscriptSegment};
 use super::error::TranscriptionError;
-use super::events::{LocalModelState, ModelStateEvent, ModelStatus, UnloadReason};
+use super::events::{
+    AutoModelRuleError, LoadedModelInfo, LocalModelState, ModelStateEvent, ModelStatus,
+    TranscriptionProgress, UnloadReason,
+};
+use super::language;
+use super::memory::{available_memory_bytes, bytes_to_mb, MemoryGuard};
+use super::samples::{chunk_samples, ms_to_samples, samples_to_ms, trim_silence, SAMPLE_RATE};
+use super::whisper_params::WhisperDefaults;
+use crate::audio::detect_speech;
+use crate::timings::{record_stage, PipelineStage};
 use log::{debug, info, warn};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Manager};
 use tauri_specta::Event;
 use transcribe_rs::onnx::moonshine::{MoonshineModel, MoonshineVariant};
-use transcribe_rs::onnx::parakeet::{ParakeetModel, ParakeetParams, TimestampGranularity};
+use transcribe_rs::onnx::parakeet::{
+    ParakeetModel, ParakeetParams, TimestampGranularity as ParakeetGranularity,
+};
 use transcribe_rs::onnx::Quantization;
 use transcribe_rs::whisper_cpp::{WhisperEngine, WhisperInferenceParams};
-use transcribe_rs::{SpeechModel, TranscribeOptions};
+use transcribe_rs::{SpeechModel, TranscribeOptions, TranscriptionResult};
+
+/// Counts one call in `ModelManager::in_flight` until dropped, so every
+/// return path, early errors included, counts it back out.
+struct InFlight<'a>(&'a AtomicU32);
+
+impl<'a> InFlight<'a> {
+    fn enter(count: &'a AtomicU32) -> Self {
+        count.fetch_add(1, Ordering::AcqRel);
+        Self(count)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
 
 /// Resident engine variants. Dropping any variant releases the model
 /// resources held by the inner type.
@@ -31,6 +65,24 @@ enum Engine {
 /// mutex slot holds all three.
 type Cached = Option<(PathBuf, Option<DiskIdentity>, Engine)>;
 
+/// What the resident engine is and where and when it was loaded from.
+#[derive(Debug, Clone)]
+struct LoadedModel {
+    engine: EngineKind,
+    path: PathBuf,
+    loaded_at_ms: u64,
+}
+
+/// The stretch of a transcription's progress one engine call covers: all of
+/// it, or one window's share when the audio is chunked.
+#[derive(Debug, Clone, Copy)]
+struct ProgressSpan {
+    from: f32,
+    to: f32,
+    /// Length of the whole audio, not just this call's part.
+    audio_ms: u32,
+}
+
 /// Owns the resident engine's lifecycle and the state observers see while it
 /// runs. Cache + ambient config + policy + status snapshot + lifecycle event
 /// emission all serve that one concern; they share the struct because they
@@ -42,11 +94,28 @@ pub struct ModelManager {
     /// so concurrent transcribe calls serialize (one engine fits in memory).
     cached: Arc<Mutex<Cached>>,
 
+    /// `cached`'s engine and path mirrored with its load time, so
+    /// `loaded_model_info()` can answer without waiting on the cache mutex
+    /// held across inference. Set on load and cleared wherever the cache is
+    /// emptied.
+    loaded: Arc<RwLock<Option<LoadedModel>>>,
+
     /// Millis since UNIX_EPOCH of the last transcription start or completion.
     /// Atomic so the idle watcher can read it without contending with the
     /// cache mutex during long inference.
     last_activity_ms: Arc<AtomicU64>,
 
+    /// Bumped by `cancel_transcriptions`. Each transcription records the
+    /// value when it is requested and gives up once it changes, so one bump
+    /// cancels the running call and every call queued behind the cache
+    /// mutex, while later calls start from the new value unaffected.
+    cancel_generation: Arc<AtomicU64>,
+
+    /// Transcriptions inside `transcribe_detailed`, queued ones included.
+    /// A count rather than a flag so one call finishing does not report the
+    /// manager idle while another is still waiting for the engine.
+    in_flight: Arc<AtomicU32>,
+
     /// Ambient configuration pushed by the FE via `set_transcription_config`.
     /// Read by `transcribe()` to dispatch and by `snapshot()` to report
     /// `(engine, model_name)` without touching the cache mutex.
@@ -58,6 +127,17 @@ pub struct ModelManager {
     /// so snapshot never blocks behind a transcription.
     status: Arc<RwLock<ModelStatus>>,
 
+    /// Pre-load memory guard settings, pushed via `set_model_memory_guard`.
+    memory_guard: Arc<RwLock<MemoryGuard>>,
+
+    /// Whisper decoding knobs, pushed via `set_whisper_defaults`.
+    whisper_defaults: Arc<RwLock<WhisperDefaults>>,
+
+    /// Tail of the last Whisper transcript, fed into the next prompt when
+    /// `carry_context` is on. Cleared by `reset_context` and whenever the
+    /// model or the setting changes.
+    carried_context: Arc<Mutex<Option<String>>>,
+
     /// Handle used for `Emitter::emit` on the lifecycle event channel.
     /// Constructed once in `setup` and cloned cheaply through `Clone` on
     /// the manager.
@@ -68,9 +148,15 @@ impl ModelManager {
     pub fn new(app: AppHandle) -> Self {
         Self {
             cached: Arc::new(Mutex::new(None)),
+            loaded: Arc::new(RwLock::new(None)),
             last_activity_ms: Arc::new(AtomicU64::new(now_millis())),
+            cancel_generation: Arc::new(AtomicU64::new(0)),
+            in_flight: Arc::new(AtomicU32::new(0)),
             config: Arc::new(RwLock::new(None)),
             status: Arc::new(RwLock::new(ModelStatus::Idle)),
+            memory_guard: Arc::new(RwLock::new(MemoryGuard::default())),
+            whisper_defaults: Arc::new(RwLock::new(WhisperDefaults::default())),
+            carried_context: Arc::new(Mutex::new(None)),
             app,
         }
     }
@@ -87,7 +173,7 @@ impl ModelManager {
     pub fn set_transcription_config(&self, config: TranscriptionConfig) {
         // Validate eagerly. This is a path check, not a model load, so it is
         // cheap and surfaces a bad selection now instead of mid-transcription.
-        if let Err(message) = self.model_path_for(&config) {
+        if let Err(message) = self.check_config(&config) {
             warn!("[Transcription] rejected local model config: {}", message);
             *self.write_config() = None;
             self.evict(UnloadReason::ConfigChanged);
@@ -101,15 +187,31 @@ impl ModelManager {
             return;
         }
 
-        let model_changed = {
+        for error in self.auto_model_errors(&config) {
+            warn!(
+                "[Transcription] skipping auto-selection rule {} ({}): {}",
+                error.index, error.model_name, error.message
+            );
+        }
+
+        let (model_changed, context_changed) = {
             let mut guard = self.write_config();
             let changed = guard.as_ref().is_none_or(|prev| {
                 prev.engine != config.engine || prev.model_name != config.model_name
             });
+            let context_changed = guard
+                .as_ref()
+                .is_none_or(|prev| prev.carry_context != config.carry_context);
             *guard = Some(config);
-            changed
+            (changed, context_changed)
         };
 
+        // Context from another model (or from before the user toggled the
+        // setting) would prime the next decode with unrelated text.
+        if model_changed || context_changed {
+            self.reset_context();
+        }
+
         // A different model means the resident one is stale. Drop it (Idle,
         // not loaded) so the FE reflects the new selection honestly; the next
         // transcription loads it. `evict` skips a cache busy with an in-flight
@@ -132,16 +234,13 @@ impl ModelManager {
     /// honored; the link lives in the folder even when its target does not,
     /// and the engine loaders follow links natively.
     fn model_path_for(&self, config: &TranscriptionConfig) -> Result<PathBuf, String> {
-        let name = config.model_name.as_str();
-        if name.is_empty() {
-            return Err("No local model selected. Choose a model in settings.".to_string());
-        }
-        if name.contains('/') || name.contains('\\') || name == "." || name == ".." {
-            return Err(format!(
-                "Model name must be a single models-folder entry, got: {}",
-                name
-            ));
-        }
+        self.resolve_model_path(config.engine, &config.model_name)
+    }
+
+    /// `model_path_for` without a full config: the existing entry `name` in
+    /// `engine`'s models directory.
+    pub fn resolve_model_path(&self, engine: EngineKind, name: &str) -> Result<PathBuf, String> {
+        validate_model_name(name)?;
         let app_data_dir = self
             .app
             .path()
@@ -149,7 +248,7 @@ impl ModelManager {
             .map_err(|e| format!("resolve app data directory: {}", e))?;
         let path = app_data_dir
             .join("models")
-            .join(engine_models_dir(config.engine))
+            .join(engine_models_dir(engine))
             .join(name);
         if !path.exists() {
             return Err(format!(
@@ -160,6 +259,124 @@ impl ModelManager {
         Ok(path)
     }
 
+    /// Forget the carried prompt context so the next transcription starts
+    /// fresh. The FE calls this at the start of each new dictation.
+    pub fn reset_context(&self) {
+        *self.lock_context() = None;
+    }
+
+    fn carried_context(&self) -> Option<String> {
+        self.lock_context().clone()
+    }
+
+    fn carry_forward(&self, transcript: &str) {
+        let tail = context::tail_words(transcript, context::MAX_PROMPT_WORDS);
+        *self.lock_context() = Some(tail).filter(|t| !t.is_empty());
+    }
+
+    fn lock_context(&self) -> MutexGuard<'_, Option<String>> {
+        self.carried_context
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Resolve a config's model path without applying it. Lets callers that
+    /// receive a config from outside the FE settings flow (profile import)
+    /// reject a missing model before it replaces the ambient config.
+    /// Auto-selection rules are not checked here: one with a missing model
+    /// is skipped and reported by `auto_model_errors`, not fatal.
+    pub fn check_config(&self, config: &TranscriptionConfig) -> Result<(), String> {
+        self.model_path_for(config)?;
+        if let Some(chunking) = &config.chunking {
+            chunking.validate().map_err(|e| format!("chunking: {e}"))?;
+        }
+        if let Some(downmix) = &config.downmix {
+            downmix.validate().map_err(|e| format!("downmix: {e}"))?;
+        }
+        Ok(())
+    }
+
+    /// The auto-selection rules of `config` whose model cannot be resolved,
+    /// which transcription skips. Checked on every call rather than once at
+    /// `set_transcription_config`, so a rule comes back as soon as its model
+    /// is downloaded.
+    fn auto_model_errors(&self, config: &TranscriptionConfig) -> Vec<AutoModelRuleError> {
+        config
+            .auto_model_selection
+            .iter()
+            .enumerate()
+            .filter_map(|(index, rule)| {
+                let message = self
+                    .resolve_model_path(rule.engine, &rule.model_name)
+                    .err()?;
+                Some(AutoModelRuleError {
+                    index: index as u32,
+                    model_name: rule.model_name.clone(),
+                    message,
+                })
+            })
+            .collect()
+    }
+
+    /// The ambient config, if the FE has pushed one.
+    pub fn current_config(&self) -> Option<TranscriptionConfig> {
+        self.read_config()
+    }
+
+    pub fn set_memory_guard(&self, guard: MemoryGuard) {
+        match self.memory_guard.write() {
+            Ok(mut g) => *g = guard,
+            Err(poisoned) => *poisoned.into_inner() = guard,
+        }
+    }
+
+    fn read_memory_guard(&self) -> MemoryGuard {
+        self.memory_guard
+            .read()
+            .map(|g| *g)
+            .unwrap_or_else(|poisoned| *poisoned.into_inner())
+    }
+
+    pub fn set_whisper_defaults(&self, defaults: WhisperDefaults) {
+        match self.whisper_defaults.write() {
+            Ok(mut d) => *d = defaults,
+            Err(poisoned) => *poisoned.into_inner() = defaults,
+        }
+    }
+
+    fn read_whisper_defaults(&self) -> WhisperDefaults {
+        self.whisper_defaults
+            .read()
+            .map(|d| *d)
+            .unwrap_or_else(|poisoned| *poisoned.into_inner())
+    }
+
+    /// Refuse a load whose estimated footprint exceeds available RAM. Runs
+    /// after the previous engine is dropped, so its memory counts as free.
+    /// A model whose size cannot be read, or a platform that reports no
+    /// memory figure, passes: the guard only blocks on real numbers.
+    fn check_memory(&self, model_bytes: Option<u64>) -> Result<(), TranscriptionError> {
+        let (Some(model_bytes), Some(available)) = (model_bytes, available_memory_bytes()) else {
+            return Ok(());
+        };
+        let guard = self.read_memory_guard();
+        debug!(
+            "[Transcription] memory estimate: need ~{}MB, available {}MB",
+            bytes_to_mb(guard.estimate_bytes(model_bytes)),
+            bytes_to_mb(available)
+        );
+        guard.check(model_bytes, available).map_err(|shortfall| {
+            TranscriptionError::InsufficientMemory {
+                message: format!(
+                    "This model needs about {} MB of RAM but only {} MB is available. Close other apps, choose a smaller model, or relax the memory guard.",
+                    shortfall.required_mb, shortfall.available_mb
+                ),
+                required_mb: shortfall.required_mb,
+                available_mb: shortfall.available_mb,
+            }
+        })
+    }
+
     fn write_config(&self) -> std::sync::RwLockWriteGuard<'_, Option<TranscriptionConfig>> {
         self.config
             .write()
@@ -201,10 +418,40 @@ impl ModelManager {
     pub fn snapshot(&self) -> LocalModelState {
         let config = self.read_config();
         let status = self.read_status();
-        LocalModelState {
-            engine: config.as_ref().map(|c| c.engine),
-            model_name: config.map(|c| c.model_name),
-            status,
+        let auto_model_errors = config
+            .as_ref()
+            .map(|c| self.auto_model_errors(c))
+            .unwrap_or_default();
+        state_for_config_option(config.as_ref(), status, auto_model_errors)
+    }
+
+    /// The model resident right now, read without touching the cache mutex.
+    pub fn loaded_model_info(&self) -> LoadedModelInfo {
+        let loaded = self
+            .loaded
+            .read()
+            .map(|g| g.clone())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().clone());
+        let idle = Duration::from_millis(
+            now_millis().saturating_sub(self.last_activity_ms.load(Ordering::Relaxed)),
+        );
+        let unloads_in_secs = loaded
+            .as_ref()
+            .and(idle_timeout_for(self.current_policy()))
+            .map(|timeout| timeout.saturating_sub(idle).as_secs());
+        LoadedModelInfo {
+            engine: loaded.as_ref().map(|l| l.engine),
+            model_path: loaded.as_ref().map(|l| l.path.display().to_string()),
+            loaded_at_ms: loaded.map(|l| l.loaded_at_ms),
+            idle_secs: idle.as_secs(),
+            unloads_in_secs,
+        }
+    }
+
+    fn set_loaded(&self, loaded: Option<LoadedModel>) {
+        match self.loaded.write() {
+            Ok(mut g) => *g = loaded,
+            Err(poisoned) => *poisoned.into_inner() = loaded,
         }
     }
 
@@ -217,10 +464,58 @@ impl ModelManager {
 
     // ── Transcribe ────────────────────────────────────────────────────
 
-    /// Synchronous inference dispatch. Reads the ambient configuration,
-    /// validates the samples, then routes to the engine-specific path.
-    /// Called from a blocking-pool thread.
+    /// Cancel the running transcription and any queued behind it. They
+    /// return `Cancelled`. transcribe-rs runs inference synchronously with
+    /// no abort hook, so a call already inside the engine runs to the end
+    /// and its result is discarded; calls still waiting for the engine are
+    /// dropped before they reach it.
+    pub fn cancel_transcriptions(&self) {
+        self.cancel_generation.fetch_add(1, Ordering::AcqRel);
+        info!("[Transcription] cancellation requested");
+    }
+
+    /// Whether a transcription is running or queued for the engine. Reads
+    /// an atomic, so it answers immediately even mid-inference.
+    pub fn is_transcribing(&self) -> bool {
+        self.in_flight.load(Ordering::Acquire) > 0
+    }
+
+    fn check_cancelled(&self, generation: u64) -> Result<(), TranscriptionError> {
+        if self.cancel_generation.load(Ordering::Acquire) == generation {
+            return Ok(());
+        }
+        Err(TranscriptionError::Cancelled {
+            message: "Transcription was cancelled".to_string(),
+        })
+    }
+
+    /// Plain-text transcript of `samples` (16 kHz mono). See
+    /// `transcribe_detailed`.
     pub fn transcribe(&self, samples: Vec<f32>) -> Result<String, TranscriptionError> {
+        self.transcribe_detailed(samples).map(|result| result.text)
+    }
+
+    /// Synchronous inference dispatch with the ambient configuration. See
+    /// `transcribe_detailed_with`.
+    pub fn transcribe_detailed(
+        &self,
+        samples: Vec<f32>,
+    ) -> Result<DetailedTranscription, TranscriptionError> {
+        self.transcribe_detailed_with(samples, &TranscriptionOverrides::default())
+    }
+
+    /// Synchronous inference dispatch, and the one path every local
+    /// transcription takes. Reads the ambient configuration, applies
+    /// `overrides` (which must have passed `validate`), validates the
+    /// samples, then routes to the engine-specific path. Called from a
+    /// blocking-pool thread.
+    pub fn transcribe_detailed_with(
+        &self,
+        samples: Vec<f32>,
+        overrides: &TranscriptionOverrides,
+    ) -> Result<DetailedTranscription, TranscriptionError> {
+        let _in_flight = InFlight::enter(&self.in_flight);
+        let generation = self.cancel_generation.load(Ordering::Acquire);
         let Some(config) = self.read_config() else {
             return Err(TranscriptionError::NoConfig {
                 message:
@@ -228,13 +523,169 @@ impl ModelManager {
                         .to_string(),
             });
         };
+        let config = overrides.apply(config);
 
         if samples.is_empty() {
             warn!("[Transcription] zero samples, returning empty transcript");
-            return Ok(String::new());
+            return Ok(DetailedTranscription {
+                text: String::new(),
+                segments: Vec::new(),
+                language: config.language,
+                audio_ms: 0,
+                elapsed_ms: 0,
+            });
         }
 
-        let samples = sanitize_samples(samples);
+        let audio_ms = samples_to_ms(samples.len());
+        if let Some(threshold) = config.speech_check_dbfs {
+            let presence = detect_speech(&samples, SAMPLE_RATE, threshold as f32);
+            if !presence.has_speech {
+                info!(
+                    "[Transcription] no speech above {} dBFS in {}ms of audio, skipping the engine",
+                    threshold, audio_ms,
+                );
+                return Ok(DetailedTranscription {
+                    text: String::new(),
+                    segments: Vec::new(),
+                    language: config.language,
+                    audio_ms,
+                    elapsed_ms: 0,
+                });
+            }
+        }
+        let usable = |rule: &AutoModelRule| {
+            self.resolve_model_path(rule.engine, &rule.model_name)
+                .is_ok()
+        };
+        let config = match config.auto_model_for(audio_ms, usable).cloned() {
+            Some(rule) => {
+                info!(
+                    "[Transcription] auto-selected {:?} {} for {}ms of audio (rule up to {:?}ms)",
+                    rule.engine, rule.model_name, audio_ms, rule.max_duration_ms,
+                );
+                TranscriptionConfig {
+                    engine: rule.engine,
+                    model_name: rule.model_name,
+                    ..config
+                }
+            }
+            None => {
+                if !config.auto_model_selection.is_empty() {
+                    info!(
+                        "[Transcription] {}ms of audio is longer than every auto-selection rule, using {:?} {}",
+                        audio_ms, config.engine, config.model_name,
+                    );
+                }
+                config
+            }
+        };
+        let result = match config.chunking {
+            Some(chunking) if audio_ms > chunking.chunk_ms => {
+                self.transcribe_chunked(samples, &config, chunking, generation)?
+            }
+            _ => {
+                let whole = ProgressSpan {
+                    from: 0.0,
+                    to: 1.0,
+                    audio_ms,
+                };
+                self.run_engine(samples, &config, whole, generation)?
+            }
+        };
+        self.warn_on_language_mismatch(&config, &result.text);
+        self.evict_if_immediate(config.unload_policy);
+        Ok(result)
+    }
+
+    /// `transcribe_detailed` for audio longer than one `chunking` window: one
+    /// engine call per window, stitched with
+    /// `DetailedTranscription::trim_leading_context` so the overlap is kept
+    /// once. Moonshine reports no segments to find the overlap with, so its
+    /// windows' text is joined as is and may repeat a word at a cut.
+    fn transcribe_chunked(
+        &self,
+        samples: Vec<f32>,
+        config: &TranscriptionConfig,
+        chunking: Chunking,
+        generation: u64,
+    ) -> Result<DetailedTranscription, TranscriptionError> {
+        let audio_ms = samples_to_ms(samples.len());
+        let overlap = ms_to_samples(chunking.overlap_ms);
+        let chunks = chunk_samples(
+            &samples,
+            chunking.chunk_ms as f32 / 1000.0,
+            chunking.overlap_ms as f32 / 1000.0,
+        );
+        drop(samples);
+        let count = chunks.len();
+        info!(
+            "[Transcription] transcribing {}ms of audio in {} chunks",
+            audio_ms, count,
+        );
+
+        let mut merged = DetailedTranscription {
+            text: String::new(),
+            segments: Vec::new(),
+            language: config.language.clone(),
+            audio_ms,
+            elapsed_ms: 0,
+        };
+        let mut start = 0;
+        for (index, chunk) in chunks.into_iter().enumerate() {
+            let len = chunk.len();
+            let span = ProgressSpan {
+                from: index as f32 / count as f32,
+                to: (index + 1) as f32 / count as f32,
+                audio_ms,
+            };
+            let mut part = self.run_engine(chunk, config, span, generation)?;
+            // Every window but the first opens with the end of the one before.
+            let context = if index == 0 { 0 } else { overlap };
+            part.trim_leading_context(samples_to_ms(context));
+            let offset_ms = samples_to_ms(start + context);
+            merged
+                .segments
+                .extend(part.segments.into_iter().map(|mut segment| {
+                    segment.start_ms += offset_ms;
+                    segment.end_ms += offset_ms;
+                    segment
+                }));
+            if !part.text.is_empty() {
+                if !merged.text.is_empty() {
+                    merged.text.push(' ');
+                }
+                merged.text.push_str(&part.text);
+            }
+            merged.elapsed_ms += part.elapsed_ms;
+            start += len - overlap;
+        }
+        Ok(merged)
+    }
+
+    /// One engine call over `samples`, which may be a whole recording or one
+    /// window of it. Reports `span` of the overall progress.
+    fn run_engine(
+        &self,
+        samples: Vec<f32>,
+        config: &TranscriptionConfig,
+        span: ProgressSpan,
+        generation: u64,
+    ) -> Result<DetailedTranscription, TranscriptionError> {
+        let audio_ms = samples_to_ms(samples.len());
+        let mut samples = sanitize_samples(samples);
+        let trimmed_lead = match config.trim_silence_dbfs {
+            Some(threshold) => {
+                let before = samples.len();
+                let lead = trim_silence(&mut samples, threshold as f32);
+                debug!(
+                    "[Transcription] trimmed silence: lead_samples={} total_samples={}",
+                    lead,
+                    before - samples.len(),
+                );
+                lead
+            }
+            None => 0,
+        };
 
         info!(
             "[Transcription] starting {:?} transcription: pcm_samples={}",
@@ -243,60 +694,130 @@ impl ModelManager {
         );
 
         let model_path = self
-            .model_path_for(&config)
+            .model_path_for(config)
             .map_err(|message| TranscriptionError::ConfigError { message })?;
         let inference_started = std::time::Instant::now();
-        let transcript = match config.engine {
+        self.report_progress(span.from, span.audio_ms);
+        let result = match config.engine {
             EngineKind::Whispercpp => {
                 let mut params = WhisperInferenceParams::default();
                 params.language = config.language.clone();
-                params.initial_prompt = config.initial_prompt.clone();
+                params.initial_prompt = if config.carry_context {
+                    context::compose_prompt(
+                        config.initial_prompt.as_deref(),
+                        self.carried_context().as_deref(),
+                    )
+                } else {
+                    config.initial_prompt.clone()
+                };
+                params.translate = config.task == WhisperTask::Translate;
+                if params.translate
+                    && !model_capabilities(config.engine, &config.model_name).supports_translate
+                {
+                    warn!(
+                        "[Transcription] translate requested, but {} may not support it",
+                        config.model_name
+                    );
+                }
                 params.print_special = false;
                 params.print_progress = false;
                 params.print_realtime = false;
                 params.print_timestamps = false;
-                params.suppress_blank = true;
-                params.suppress_non_speech_tokens = true;
-                params.no_speech_thold = 0.2;
+                let defaults = self.read_whisper_defaults();
+                params.suppress_blank = defaults.suppress_blank;
+                params.suppress_non_speech_tokens = defaults.suppress_non_speech_tokens;
+                params.no_speech_thold = defaults.no_speech_thold;
 
-                self.with_whisper(&config, model_path, |engine| {
-                    let result = engine
+                self.with_whisper(config, model_path, generation, |engine| {
+                    engine
                         .transcribe_with(&samples, &params)
-                        .map_err(transcription_err)?;
-                    Ok(result.text.trim().to_string())
+                        .map_err(transcription_err)
                 })?
             }
             EngineKind::Parakeet => {
+                let granularity = match config.timestamp_granularity {
+                    TimestampGranularity::Segment => ParakeetGranularity::Segment,
+                    TimestampGranularity::Word => ParakeetGranularity::Word,
+                };
                 let params = ParakeetParams {
-                    timestamp_granularity: Some(TimestampGranularity::Segment),
+                    timestamp_granularity: Some(granularity),
                     ..Default::default()
                 };
-                self.with_parakeet(&config, model_path, |engine| {
-                    let result = engine
+                self.with_parakeet(config, model_path, generation, |engine| {
+                    engine
                         .transcribe_with(&samples, &params)
-                        .map_err(transcription_err)?;
-                    Ok(result.text.trim().to_string())
+                        .map_err(transcription_err)
                 })?
             }
             EngineKind::Moonshine => {
                 let variant = parse_moonshine_variant(&config.model_name)?;
-                self.with_moonshine(&config, model_path, variant, |engine| {
-                    let result = engine
+                self.with_moonshine(config, model_path, variant, generation, |engine| {
+                    engine
                         .transcribe(&samples, &TranscribeOptions::default())
-                        .map_err(transcription_err)?;
-                    Ok(result.text.trim().to_string())
+                        .map_err(transcription_err)
                 })?
             }
         };
+        let elapsed_ms = inference_started.elapsed().as_millis() as u32;
+        self.report_progress(span.to, span.audio_ms);
+        let transcript = result.text.trim().to_string();
 
         info!(
             "[Transcription] {:?} transcription complete: characters={} elapsed_ms={}",
             config.engine,
             transcript.len(),
-            inference_started.elapsed().as_millis(),
+            elapsed_ms,
         );
-        self.evict_if_immediate(config.unload_policy);
-        Ok(transcript)
+        if config.carry_context && config.engine == EngineKind::Whispercpp {
+            self.carry_forward(&transcript);
+        }
+        let offset_ms = samples_to_ms(trimmed_lead);
+        let mut segments = segments_of(&result);
+        for segment in &mut segments {
+            segment.start_ms += offset_ms;
+            segment.end_ms += offset_ms;
+        }
+        Ok(DetailedTranscription {
+            segments,
+            text: transcript,
+            language: config.language.clone(),
+            audio_ms,
+            elapsed_ms,
+        })
+    }
+
+    /// Heuristic, non-blocking check that the output's script matches a
+    /// forced language. Only warns; never alters the transcript. Skipped when
+    /// Whisper translates, since the output is English by design.
+    fn warn_on_language_mismatch(&self, config: &TranscriptionConfig, transcript: &str) {
+        if config.engine == EngineKind::Whispercpp && config.task == WhisperTask::Translate {
+            return;
+        }
+        let Some(language) = config.language.as_deref() else {
+            return;
+        };
+        let Some(mismatch) = language::check_script(language, transcript) else {
+            return;
+        };
+        warn!(
+            "[Transcription] language '{}' requested but output is {:.0}% {:?}",
+            language,
+            mismatch.detected_share * 100.0,
+            mismatch.detected_script,
+        );
+        if let Err(err) = mismatch.emit(&self.app) {
+            warn!(
+                "[Transcription] failed to emit language mismatch event: {}",
+                err
+            );
+        }
+    }
+
+    fn report_progress(&self, progress: f32, audio_ms: u32) {
+        let event = TranscriptionProgress { progress, audio_ms };
+        if let Err(err) = event.emit(&self.app) {
+            warn!("[Transcription] failed to emit progress event: {}", err);
+        }
     }
 
     // ── Engine cache + eviction ───────────────────────────────────────
@@ -305,11 +826,13 @@ impl ModelManager {
         &self,
         config: &TranscriptionConfig,
         model_path: PathBuf,
+        generation: u64,
         f: impl FnOnce(&mut WhisperEngine) -> Result<T, TranscriptionError>,
     ) -> Result<T, TranscriptionError> {
         self.with_engine(
             config,
             model_path,
+            generation,
             |e| matches!(e, Engine::Whisper(_)),
             |path| {
                 WhisperEngine::load(path)
@@ -327,11 +850,13 @@ impl ModelManager {
         &self,
         config: &TranscriptionConfig,
         model_path: PathBuf,
+        generation: u64,
         f: impl FnOnce(&mut ParakeetModel) -> Result<T, TranscriptionError>,
     ) -> Result<T, TranscriptionError> {
         self.with_engine(
             config,
             model_path,
+            generation,
             |e| matches!(e, Engine::Parakeet(_)),
             |path| {
                 ParakeetModel::load(path, &Quantization::Int8)
@@ -350,11 +875,13 @@ impl ModelManager {
         config: &TranscriptionConfig,
         model_path: PathBuf,
         variant: MoonshineVariant,
+        generation: u64,
         f: impl FnOnce(&mut MoonshineModel) -> Result<T, TranscriptionError>,
     ) -> Result<T, TranscriptionError> {
         self.with_engine(
             config,
             model_path,
+            generation,
             |e| matches!(e, Engine::Moonshine(_)),
             |path| {
                 MoonshineModel::load(path, variant, &Quantization::default())
@@ -398,20 +925,43 @@ impl ModelManager {
                     && &current_identity == id
         );
 
-        if !reuse {
+        if reuse {
+            record_stage(&self.app, PipelineStage::ModelLoad, Duration::ZERO);
+        } else {
             let _ = guard.take();
+            self.set_loaded(None);
+            if let Err(e) = self.check_memory(current_identity.as_ref().map(|id| id.len)) {
+                let message = e.to_string();
+                self.publish(
+                    config,
+                    ModelStatus::Error {
+                        message: message.clone(),
+                    },
+                    |state| ModelStateEvent::LoadingFailed {
+                        state,
+                        error: message,
+                    },
+                );
+                return Err(e);
+            }
             self.publish(config, ModelStatus::Loading, |state| {
                 ModelStateEvent::LoadingStarted { state }
             });
             let started = Instant::now();
             match load(&model_path) {
                 Ok(engine) => {
+                    record_stage(&self.app, PipelineStage::ModelLoad, started.elapsed());
                     let elapsed_ms = started.elapsed().as_millis() as u64;
                     debug!(
                         "[Transcription] model loaded: {} ({}ms)",
                         model_path.display(),
                         elapsed_ms
                     );
+                    self.set_loaded(Some(LoadedModel {
+                        engine: config.engine,
+                        path: model_path.clone(),
+                        loaded_at_ms: now_millis(),
+                    }));
                     *guard = Some((model_path, current_identity, engine));
                     self.publish(config, ModelStatus::Ready, |state| {
                         ModelStateEvent::LoadingCompleted { state, elapsed_ms }
@@ -437,17 +987,23 @@ impl ModelManager {
     }
 
     /// Hold the cache lock across load and use, emitting semantic inference
-    /// events around the user closure.
+    /// events around the user closure. A transcription cancelled since
+    /// `generation` stops before loading a model and again once it holds the
+    /// lock (so queued calls never reach the engine), and a result that
+    /// finishes after a cancel is discarded.
     fn with_engine<T>(
         &self,
         config: &TranscriptionConfig,
         model_path: PathBuf,
+        generation: u64,
         can_reuse: impl Fn(&Engine) -> bool,
         load: impl FnOnce(&Path) -> Result<Engine, String>,
         use_engine: impl FnOnce(&mut Engine) -> Result<T, TranscriptionError>,
     ) -> Result<T, TranscriptionError> {
         self.touch_activity();
+        self.check_cancelled(generation)?;
         let mut guard = self.ensure_loaded(config, model_path, can_reuse, load)?;
+        self.check_cancelled(generation)?;
 
         let (_, _, engine) = guard.as_mut().expect("cache slot populated above");
         self.publish(config, ModelStatus::Inferring, |state| {
@@ -455,6 +1011,7 @@ impl ModelManager {
         });
         let started = Instant::now();
         let result = use_engine(engine);
+        record_stage(&self.app, PipelineStage::Inference, started.elapsed());
         let elapsed_ms = started.elapsed().as_millis() as u64;
         self.touch_activity();
         match &result {
@@ -481,6 +1038,7 @@ impl ModelManager {
                 );
             }
         }
+        self.check_cancelled(generation)?;
         result
     }
 
@@ -502,11 +1060,40 @@ impl ModelManager {
     /// transcription reloads against the current config anyway. A no-op when the
     /// cache is already empty.
     fn evict(&self, reason: UnloadReason) {
-        let Ok(mut guard) = self.cached.try_lock() else {
+        let Ok(guard) = self.cached.try_lock() else {
             return;
         };
+        self.evict_locked(guard, reason);
+    }
+
+    /// Drop the resident model, waiting for an in-flight transcription to
+    /// finish first (unlike `evict`). Used before an app update, where the
+    /// model files must not be held open when the binary is replaced.
+    /// Returns whether a model was resident.
+    pub fn unload_for_shutdown(&self) -> bool {
+        self.unload_waiting(UnloadReason::Shutdown)
+    }
+
+    /// Drop the resident model now, regardless of the unload policy, so its
+    /// memory is reclaimed without waiting for the idle watcher. Waits for
+    /// an in-flight transcription like `unload_for_shutdown`; the next
+    /// transcription loads the model again. Returns whether a model was
+    /// resident.
+    pub fn unload_model(&self) -> bool {
+        self.unload_waiting(UnloadReason::Manual)
+    }
+
+    fn unload_waiting(&self, reason: UnloadReason) -> bool {
+        let guard = lock_cached(&self.cached);
+        let was_resident = guard.is_some();
+        self.evict_locked(guard, reason);
+        was_resident
+    }
+
+    fn evict_locked(&self, mut guard: MutexGuard<'_, Cached>, reason: UnloadReason) {
         let config_guard = self.read_config_guard();
         if let Some((path, _identity, _engine)) = guard.take() {
+            self.set_loaded(None);
             debug!(
                 "[Transcription] unloaded model ({:?}): {}",
                 reason,
@@ -517,7 +1104,15 @@ impl ModelManager {
             // ordering is cheap).
             drop(guard);
             self.set_status(ModelStatus::Idle);
-            let state = state_for_config_option(config_guard.as_ref(), ModelStatus::Idle);
+            let auto_model_errors = config_guard
+                .as_ref()
+                .map(|c| self.auto_model_errors(c))
+                .unwrap_or_default();
+            let state = state_for_config_option(
+                config_guard.as_ref(),
+                ModelStatus::Idle,
+                auto_model_errors,
+            );
             self.emit(ModelStateEvent::Unloaded { state, reason });
         }
     }
@@ -553,6 +1148,7 @@ impl ModelManager {
             return;
         };
         if let Some((path, _identity, _engine)) = guard.take() {
+            self.set_loaded(None);
             let idle_secs = idle.as_secs();
             debug!(
                 "[Transcription] unloaded model (idle {}s): {}",
@@ -585,23 +1181,34 @@ impl ModelManager {
         build_event: impl FnOnce(LocalModelState) -> ModelStateEvent,
     ) {
         self.set_status(status.clone());
-        self.emit(build_event(state_for_config(config, status)));
+        let auto_model_errors = self.auto_model_errors(config);
+        self.emit(build_event(state_for_config(
+            config,
+            status,
+            auto_model_errors,
+        )));
     }
 }
 
 /// Build a `LocalModelState` from a known (non-optional) config and status.
-fn state_for_config(config: &TranscriptionConfig, status: ModelStatus) -> LocalModelState {
-    state_for_config_option(Some(config), status)
+fn state_for_config(
+    config: &TranscriptionConfig,
+    status: ModelStatus,
+    auto_model_errors: Vec<AutoModelRuleError>,
+) -> LocalModelState {
+    state_for_config_option(Some(config), status, auto_model_errors)
 }
 
 fn state_for_config_option(
     config: Option<&TranscriptionConfig>,
     status: ModelStatus,
+    auto_model_errors: Vec<AutoModelRuleError>,
 ) -> LocalModelState {
     LocalModelState {
         engine: config.map(|config| config.engine),
         model_name: config.map(|config| config.model_name.clone()),
         status,
+        auto_model_errors,
     }
 }
 
@@ -658,6 +1265,18 @@ fn parse_moonshine_variant(model_name: &str) -> Result<MoonshineVariant, Transcr
     }
 }
 
+/// Engine segments in ms, or none when the engine reported no timing.
+fn segments_of(result: &TranscriptionResult) -> Vec<TranscriptSegment> {
+    result
+        .segments
+        .iter()
+        .flatten()
+        .filter_map(|segment| {
+            TranscriptSegment::from_seconds(segment.start, segment.end, &segment.text)
+        })
+        .collect()
+}
+
 fn transcription_err(e: impl std::fmt::Display) -> TranscriptionError {
     TranscriptionError::TranscriptionError {
         message: e.to_string(),
@@ -704,6 +1323,11 @@ struct DiskIdentity {
     mtime: Option<SystemTime>,
 }
 
+/// Size on disk of a resolved model file or directory (the sum of its files).
+pub(super) fn model_size_bytes(path: &Path) -> Option<u64> {
+    disk_identity(path).map(|identity| identity.len)
+}
+
 /// Read the disk identity of a resolved model path, following symlinks so the
 /// identity reflects the bytes the engine loaders actually read. For a
 /// directory model the fields aggregate over the contained files (sum of sizes,
@@ -854,6 +1478,17 @@ mod tests {
         std::fs::remove_dir_all(&root).ok();
     }
 
+    #[test]
+    fn in_flight_counts_overlapping_calls() {
+        let count = AtomicU32::new(0);
+        let first = InFlight::enter(&count);
+        let second = InFlight::enter(&count);
+        drop(first);
+        assert_eq!(count.load(Ordering::Acquire), 1);
+        drop(second);
+        assert_eq!(count.load(Ordering::Acquire), 0);
+    }
+
     #[test]
     fn disk_identity_none_for_missing_path() {
         let path = std::env::temp_dir().join("whispering-id-missing-does-not-exist");
@@ -869,9 +1504,17 @@ mod tests {
             language: Some("en".to_string()),
             initial_prompt: None,
             unload_policy: UnloadPolicy::AfterFiveMinutes,
+            carry_context: false,
+            trim_silence_dbfs: None,
+            speech_check_dbfs: None,
+            task: WhisperTask::Transcribe,
+            auto_model_selection: Vec::new(),
+            chunking: None,
+            downmix: None,
+            timestamp_granularity: TimestampGranularity::Segment,
         };
 
-        let state = state_for_config(&config, ModelStatus::Inferring);
+        let state = state_for_config(&config, ModelStatus::Inferring, Vec::new());
 
         assert_eq!(state.engine, Some(EngineKind::Parakeet));
         assert_eq!(
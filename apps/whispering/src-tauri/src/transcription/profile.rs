@@ -0,0 +1,295 @@
+//! Shareable settings profile: the Rust-managed transcription settings as a
+//! single JSON blob, so a known-good setup can be handed to a teammate and
+//! applied in one step.
+//!
+//! The preferred recording device and the correction rules belong to the
+//! frontend's settings store and reach Rust only per call, so the FE passes
+//! them to export and gets them back from import to store itself.
+//!
+//! The profile is deliberately strict on import. Unknown fields, an
+//! unsupported version, or a value the engine cannot dispatch are rejected
+//! with a message naming the offending field, rather than half-applied.
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::Downmix;
+
+use super::config::{
+    validate_model_name, AutoModelRule, Chunking, Engine, TimestampGranularity,
+    TranscriptionConfig, UnloadPolicy, WhisperTask,
+};
+use super::corrections::{validate_rules, CorrectionRule};
+
+/// Wire version written by `export_settings_profile`. Bump on any change that
+/// an older build could not apply faithfully. Version 2 added
+/// `preferredDevice` and `correctionRules`; version 1 profiles still import,
+/// with neither set.
+pub const PROFILE_VERSION: u32 = 2;
+
+/// What `import_settings_profile` applied, and the frontend-owned settings
+/// for the FE to store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedSettingsProfile {
+    pub config: TranscriptionConfig,
+    /// Recording device identifier, as `init_recording_session` takes it.
+    /// May not exist on this machine; the FE falls back as for any missing
+    /// device.
+    pub preferred_device: Option<String>,
+    pub correction_rules: Vec<CorrectionRule>,
+}
+
+/// Flat, versioned mirror of `TranscriptionConfig`. Kept as its own type (not
+/// a wrapper around the config) so `deny_unknown_fields` covers every field
+/// and the ambient config can grow without silently changing the profile.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SettingsProfile {
+    pub version: u32,
+    pub engine: Engine,
+    pub model_name: String,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub initial_prompt: Option<String>,
+    pub unload_policy: UnloadPolicy,
+    #[serde(default)]
+    pub carry_context: bool,
+    /// Omitted when unset, so profiles without trimming still import into
+    /// builds that predate the field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trim_silence_dbfs: Option<i32>,
+    /// Omitted when unset, for the same reason.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speech_check_dbfs: Option<i32>,
+    /// Omitted when transcribing, for the same reason.
+    #[serde(default, skip_serializing_if = "is_transcribe")]
+    pub task: WhisperTask,
+    /// Omitted when empty, for the same reason.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auto_model_selection: Vec<AutoModelRule>,
+    /// Omitted when unset, for the same reason.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunking: Option<Chunking>,
+    /// Omitted when unset, for the same reason.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub downmix: Option<Downmix>,
+    /// Omitted at segment granularity, for the same reason.
+    #[serde(default, skip_serializing_if = "is_segment")]
+    pub timestamp_granularity: TimestampGranularity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_device: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub correction_rules: Vec<CorrectionRule>,
+}
+
+impl SettingsProfile {
+    pub fn new(
+        config: &TranscriptionConfig,
+        preferred_device: Option<String>,
+        correction_rules: Vec<CorrectionRule>,
+    ) -> Self {
+        Self {
+            version: PROFILE_VERSION,
+            engine: config.engine,
+            model_name: config.model_name.clone(),
+            language: config.language.clone(),
+            initial_prompt: config.initial_prompt.clone(),
+            unload_policy: config.unload_policy,
+            carry_context: config.carry_context,
+            trim_silence_dbfs: config.trim_silence_dbfs,
+            speech_check_dbfs: config.speech_check_dbfs,
+            task: config.task,
+            auto_model_selection: config.auto_model_selection.clone(),
+            chunking: config.chunking,
+            downmix: config.downmix.clone(),
+            timestamp_granularity: config.timestamp_granularity,
+            preferred_device,
+            correction_rules,
+        }
+    }
+
+    /// Parse and validate a profile blob. Serde errors already carry the
+    /// line/column and the unexpected field or variant name.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let profile: Self =
+            serde_json::from_str(json).map_err(|e| format!("Invalid settings profile: {e}"))?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(1..=PROFILE_VERSION).contains(&self.version) {
+            return Err(format!(
+                "Unsupported settings profile version {} (expected 1 to {})",
+                self.version, PROFILE_VERSION
+            ));
+        }
+        validate_model_name(&self.model_name).map_err(|e| format!("modelName: {e}"))?;
+        for (index, rule) in self.auto_model_selection.iter().enumerate() {
+            validate_model_name(&rule.model_name)
+                .map_err(|e| format!("autoModelSelection[{index}].modelName: {e}"))?;
+        }
+        if let Some(chunking) = &self.chunking {
+            chunking.validate().map_err(|e| format!("chunking: {e}"))?;
+        }
+        if let Some(downmix) = &self.downmix {
+            downmix.validate().map_err(|e| format!("downmix: {e}"))?;
+        }
+        if let Some(language) = &self.language {
+            if !is_valid_language(language) {
+                return Err(format!(
+                    "language: expected \"auto\" or an ISO 639 code like \"en\", got \"{language}\""
+                ));
+            }
+        }
+        if self
+            .preferred_device
+            .as_ref()
+            .is_some_and(|device| device.trim().is_empty())
+        {
+            return Err("preferredDevice: must not be empty".to_string());
+        }
+        validate_rules(&self.correction_rules).map_err(|e| format!("correctionRules: {e}"))?;
+        Ok(())
+    }
+
+    pub fn into_imported(self) -> ImportedSettingsProfile {
+        let config = TranscriptionConfig {
+            engine: self.engine,
+            model_name: self.model_name,
+            language: self.language,
+            initial_prompt: self.initial_prompt,
+            unload_policy: self.unload_policy,
+            carry_context: self.carry_context,
+            trim_silence_dbfs: self.trim_silence_dbfs,
+            speech_check_dbfs: self.speech_check_dbfs,
+            task: self.task,
+            auto_model_selection: self.auto_model_selection,
+            chunking: self.chunking,
+            downmix: self.downmix,
+            timestamp_granularity: self.timestamp_granularity,
+        };
+        ImportedSettingsProfile {
+            config,
+            preferred_device: self.preferred_device,
+            correction_rules: self.correction_rules,
+        }
+    }
+}
+
+fn is_transcribe(task: &WhisperTask) -> bool {
+    *task == WhisperTask::Transcribe
+}
+
+fn is_segment(granularity: &TimestampGranularity) -> bool {
+    *granularity == TimestampGranularity::Segment
+}
+
+/// `"auto"` or a two/three-letter lowercase ISO 639 code, which is the set of
+/// values the engines accept as a language hint.
+fn is_valid_language(language: &str) -> bool {
+    language == "auto"
+        || ((2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> TranscriptionConfig {
+        TranscriptionConfig {
+            engine: Engine::Whispercpp,
+            model_name: "ggml-base.en.bin".to_string(),
+            language: Some("en".to_string()),
+            initial_prompt: Some("Epicenter, Whispering".to_string()),
+            unload_policy: UnloadPolicy::AfterFiveMinutes,
+            carry_context: true,
+            trim_silence_dbfs: Some(-40),
+            speech_check_dbfs: Some(-45),
+            task: WhisperTask::Translate,
+            auto_model_selection: vec![AutoModelRule {
+                max_duration_ms: Some(30_000),
+                engine: Engine::Moonshine,
+                model_name: "moonshine-tiny-en".to_string(),
+            }],
+            chunking: Some(Chunking {
+                chunk_ms: 30_000,
+                overlap_ms: 2_000,
+            }),
+            downmix: Some(Downmix::Front),
+            timestamp_granularity: TimestampGranularity::Word,
+        }
+    }
+
+    #[test]
+    fn profile_round_trips_through_json() {
+        let rules = vec![CorrectionRule {
+            find: "get hub".to_string(),
+            replace: "GitHub".to_string(),
+            case_sensitive: false,
+            whole_word: true,
+        }];
+        let profile = SettingsProfile::new(&config(), Some("USB Mic".to_string()), rules.clone());
+        let json = serde_json::to_string(&profile).unwrap();
+        let imported = SettingsProfile::parse(&json)
+            .expect("parse exported profile")
+            .into_imported();
+        assert_eq!(imported.config, config());
+        assert_eq!(imported.preferred_device.as_deref(), Some("USB Mic"));
+        assert_eq!(imported.correction_rules, rules);
+    }
+
+    #[test]
+    fn version_1_profile_imports_without_frontend_settings() {
+        let imported = SettingsProfile::parse(
+            r#"{"version":1,"engine":"parakeet","modelName":"p","unloadPolicy":"never"}"#,
+        )
+        .expect("parse version 1 profile")
+        .into_imported();
+        assert_eq!(imported.preferred_device, None);
+        assert!(imported.correction_rules.is_empty());
+    }
+
+    #[test]
+    fn profile_rejects_unknown_fields() {
+        let err = SettingsProfile::parse(
+            r#"{"version":1,"engine":"parakeet","modelName":"p","unloadPolicy":"never","ffmpegPath":"/x"}"#,
+        )
+        .unwrap_err();
+        assert!(err.contains("ffmpegPath"), "{err}");
+    }
+
+    #[test]
+    fn profile_rejects_bad_values_with_field_names() {
+        let bad_version = SettingsProfile::parse(
+            r#"{"version":9,"engine":"parakeet","modelName":"p","unloadPolicy":"never"}"#,
+        )
+        .unwrap_err();
+        assert!(bad_version.contains("version 9"), "{bad_version}");
+
+        let bad_model = SettingsProfile::parse(
+            r#"{"version":1,"engine":"parakeet","modelName":"../p","unloadPolicy":"never"}"#,
+        )
+        .unwrap_err();
+        assert!(bad_model.starts_with("modelName:"), "{bad_model}");
+
+        let bad_language = SettingsProfile::parse(
+            r#"{"version":1,"engine":"parakeet","modelName":"p","language":"English","unloadPolicy":"never"}"#,
+        )
+        .unwrap_err();
+        assert!(bad_language.starts_with("language:"), "{bad_language}");
+
+        let bad_chunking = SettingsProfile::parse(
+            r#"{"version":1,"engine":"parakeet","modelName":"p","unloadPolicy":"never","chunking":{"chunkMs":30000,"overlapMs":20000}}"#,
+        )
+        .unwrap_err();
+        assert!(bad_chunking.starts_with("chunking:"), "{bad_chunking}");
+
+        let bad_rule = SettingsProfile::parse(
+            r#"{"version":2,"engine":"parakeet","modelName":"p","unloadPolicy":"never","correctionRules":[{"find":"","replace":"x"}]}"#,
+        )
+        .unwrap_err();
+        assert!(bad_rule.starts_with("correctionRules:"), "{bad_rule}");
+    }
+}
@@ -0,0 +1,177 @@
+//! Sample-level helpers applied to decoded 16 kHz mono PCM before it reaches
+//! an engine. Pure functions over `&[f32]` so they are unit-testable without
+//! a model or an `AppHandle`.
+
+/// Rate every `read_artifact_samples` output lands on (see
+/// `recorder::artifact::ARTIFACT_RATE`), and the rate all engines expect.
+pub const SAMPLE_RATE: u32 = 16_000;
+
+/// Window over which `trim_silence` measures level: 10 ms.
+const TRIM_WINDOW: usize = SAMPLE_RATE as usize / 100;
+
+/// Audio kept on either side of the speech `trim_silence` finds, so a soft
+/// onset or a trailing consonant below the threshold is not cut.
+const TRIM_PADDING_MS: u32 = 200;
+
+/// Convert a sample offset at `SAMPLE_RATE` to whole milliseconds.
+pub fn samples_to_ms(samples: usize) -> u32 {
+    (samples as u64 * 1000 / SAMPLE_RATE as u64) as u32
+}
+
+/// Convert milliseconds to a sample count at `SAMPLE_RATE`.
+pub fn ms_to_samples(ms: u32) -> usize {
+    (ms as u64 * SAMPLE_RATE as u64 / 1000) as usize
+}
+
+/// Cut the silence before the first and after the last 10 ms window whose
+/// RMS reaches `threshold_dbfs`, keeping `TRIM_PADDING_MS` around the
+/// speech. Returns how many samples were removed from the front, so timings
+/// can be mapped back onto the untrimmed audio.
+///
+/// When no window reaches the threshold the samples are left alone: a quiet
+/// microphone or a threshold set too high should cost inference time, not
+/// the whole transcript.
+pub fn trim_silence(samples: &mut Vec<f32>, threshold_dbfs: f32) -> usize {
+    let loud = |window: &[f32]| window_dbfs(window) >= threshold_dbfs;
+    let Some(first) = samples.chunks(TRIM_WINDOW).position(loud) else {
+        return 0;
+    };
+    let last = samples.chunks(TRIM_WINDOW).rposition(loud).unwrap_or(first);
+    let padding = ms_to_samples(TRIM_PADDING_MS);
+    let start = (first * TRIM_WINDOW).saturating_sub(padding);
+    let end = ((last + 1) * TRIM_WINDOW + padding).min(samples.len());
+    samples.truncate(end);
+    samples.drain(..start);
+    start
+}
+
+fn window_dbfs(window: &[f32]) -> f32 {
+    let mean_square = window.iter().map(|&s| s * s).sum::<f32>() / window.len() as f32;
+    10.0 * mean_square.log10()
+}
+
+/// Half-open sample range `[start, end)` for `[start_seconds, end_seconds)`,
+/// validated against the clip length. An `end_seconds` that overshoots the
+/// clip by less than one sample (float rounding on "until the end") is
+/// clamped; anything further is rejected so a stale duration in the UI
+/// surfaces as an error instead of a silently shorter transcript.
+pub fn sample_range(
+    len: usize,
+    start_seconds: f64,
+    end_seconds: f64,
+) -> Result<std::ops::Range<usize>, String> {
+    if !start_seconds.is_finite() || !end_seconds.is_finite() {
+        return Err("Range bounds must be finite numbers".to_string());
+    }
+    if start_seconds < 0.0 {
+        return Err(format!(
+            "Range start {start_seconds}s is before the start of the audio"
+        ));
+    }
+    if end_seconds <= start_seconds {
+        return Err(format!(
+            "Range end {end_seconds}s must be after range start {start_seconds}s"
+        ));
+    }
+
+    let duration_seconds = len as f64 / SAMPLE_RATE as f64;
+    let start = (start_seconds * SAMPLE_RATE as f64).round() as usize;
+    let end = (end_seconds * SAMPLE_RATE as f64).round() as usize;
+    if start >= len {
+        return Err(format!(
+            "Range start {start_seconds}s is past the end of the audio ({duration_seconds:.3}s)"
+        ));
+    }
+    if end > len + 1 {
+        return Err(format!(
+            "Range end {end_seconds}s is past the end of the audio ({duration_seconds:.3}s)"
+        ));
+    }
+    Ok(start..end.min(len))
+}
+
+/// Split `samples` into windows of `chunk_seconds`, each starting
+/// `overlap_seconds` before the previous one ends. The last window stops at
+/// the end of the audio, and audio no longer than one window comes back as
+/// a single chunk. The overlap is capped below the window length so the
+/// windows always advance.
+pub fn chunk_samples(samples: &[f32], chunk_seconds: f32, overlap_seconds: f32) -> Vec<Vec<f32>> {
+    let chunk = seconds_to_samples(chunk_seconds).max(1);
+    let overlap = seconds_to_samples(overlap_seconds).min(chunk - 1);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start + chunk).min(samples.len());
+        chunks.push(samples[start..end].to_vec());
+        if end == samples.len() {
+            return chunks;
+        }
+        start = end - overlap;
+    }
+}
+
+fn seconds_to_samples(seconds: f32) -> usize {
+    (seconds.max(0.0) * SAMPLE_RATE as f32).round() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_range_converts_seconds_to_indices() {
+        let len = 10 * SAMPLE_RATE as usize;
+        assert_eq!(sample_range(len, 1.0, 2.5).unwrap(), 16_000..40_000);
+        assert_eq!(sample_range(len, 0.0, 10.0).unwrap(), 0..len);
+    }
+
+    #[test]
+    fn trim_silence_keeps_speech_plus_padding() {
+        let second = SAMPLE_RATE as usize;
+        let mut samples = [
+            vec![0.0; second],
+            vec![0.5; second / 2],
+            vec![0.001; second],
+        ]
+        .concat();
+
+        let removed = trim_silence(&mut samples, -40.0);
+
+        let padding = ms_to_samples(TRIM_PADDING_MS);
+        assert_eq!(removed, second - padding);
+        assert_eq!(samples.len(), padding + second / 2 + padding);
+
+        let mut quiet = vec![0.001; second];
+        assert_eq!(trim_silence(&mut quiet, -40.0), 0);
+        assert_eq!(quiet.len(), second);
+    }
+
+    #[test]
+    fn chunks_overlap_and_cover_the_audio_once_past_the_overlap() {
+        let second = SAMPLE_RATE as usize;
+        let samples: Vec<f32> = (0..25 * second).map(|i| i as f32).collect();
+
+        let chunks = chunk_samples(&samples, 10.0, 1.0);
+
+        let lengths: Vec<usize> = chunks.iter().map(Vec::len).collect();
+        assert_eq!(lengths, [10 * second, 10 * second, 7 * second]);
+        assert_eq!(chunks[1][0], (9 * second) as f32);
+        assert_eq!(chunks[2][0], (18 * second) as f32);
+        assert_eq!(chunks[2].last(), samples.last());
+
+        let short = chunk_samples(&samples[..5 * second], 10.0, 1.0);
+        assert_eq!(short.len(), 1);
+        assert_eq!(short[0].len(), 5 * second);
+    }
+
+    #[test]
+    fn sample_range_rejects_out_of_bounds_and_inverted_ranges() {
+        let len = 10 * SAMPLE_RATE as usize;
+        assert!(sample_range(len, -1.0, 2.0).is_err());
+        assert!(sample_range(len, 3.0, 3.0).is_err());
+        assert!(sample_range(len, 4.0, 2.0).is_err());
+        assert!(sample_range(len, 10.0, 11.0).is_err());
+        assert!(sample_range(len, 1.0, 12.0).is_err());
+        assert!(sample_range(len, f64::NAN, 2.0).is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! Split long transcript segments into subtitle-sized ones.
+//!
+//! None of the engines take a maximum segment length (whisper.cpp has one,
+//! but transcribe-rs does not forward it), so this post-processes their
+//! segments instead. A segment over the limit is cut at word boundaries,
+//! preferring the end of a clause or sentence when one falls in the back
+//! half of a piece. The engines only time whole segments, so each piece's
+//! time is the segment's time shared out by character count: an estimate,
+//! but pieces stay in order, touch end to start, and never overlap.
+
+use serde::Deserialize;
+
+use super::detailed::TranscriptSegment;
+
+/// Longest a segment may be. A limit left `None` is not enforced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentLimit {
+    /// Characters, spaces included, e.g. 42 for a subtitle line.
+    pub max_chars: Option<u32>,
+    pub max_duration_ms: Option<u32>,
+}
+
+impl SegmentLimit {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.max_chars == Some(0) || self.max_duration_ms == Some(0) {
+            return Err("Segment limits must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Split every segment of `segments` that exceeds `limit`. A single word
+/// longer than the limit becomes a piece of its own rather than being cut.
+pub fn split_segments(
+    segments: Vec<TranscriptSegment>,
+    limit: SegmentLimit,
+) -> Vec<TranscriptSegment> {
+    segments
+        .into_iter()
+        .flat_map(|segment| split_segment(segment, limit))
+        .collect()
+}
+
+fn split_segment(segment: TranscriptSegment, limit: SegmentLimit) -> Vec<TranscriptSegment> {
+    let words: Vec<&str> = segment.text.split_whitespace().collect();
+    // Time is shared out per word plus the space after it, so a piece's
+    // share of `span` is its share of the segment's duration.
+    let length = |range: &[&str]| {
+        range.iter().map(|w| w.chars().count() as u64).sum::<u64>() + range.len() as u64
+    };
+    let span = length(&words).max(1);
+    let duration = u64::from(segment.end_ms.saturating_sub(segment.start_ms));
+    // Longest text, without its trailing space, within both limits at this
+    // segment's speaking rate.
+    let by_duration = limit.max_duration_ms.map(|max| {
+        (u64::from(max) * span)
+            .checked_div(duration)
+            .map_or(u64::MAX, |budget| budget.saturating_sub(1).max(1))
+    });
+    let max_chars = limit
+        .max_chars
+        .map(u64::from)
+        .into_iter()
+        .chain(by_duration)
+        .min()
+        .unwrap_or(u64::MAX);
+    if span - 1 <= max_chars || words.len() < 2 {
+        return vec![segment];
+    }
+
+    // Word index ranges, one per piece.
+    let mut pieces: Vec<(usize, usize)> = Vec::new();
+    let mut start = 0;
+    while start < words.len() {
+        let mut end = start + 1;
+        let mut chars = words[start].chars().count() as u64;
+        while end < words.len() {
+            let next = chars + 1 + words[end].chars().count() as u64;
+            if next > max_chars {
+                break;
+            }
+            chars = next;
+            end += 1;
+        }
+        if end < words.len() {
+            end = clause_break(&words[start..end], max_chars).map_or(end, |n| start + n);
+        }
+        pieces.push((start, end));
+        start = end;
+    }
+
+    let at = |offset: u64| segment.start_ms + (duration * offset / span) as u32;
+    let mut offset = 0;
+    pieces
+        .into_iter()
+        .map(|(first, last)| {
+            let piece = &words[first..last];
+            let start_ms = at(offset);
+            offset += length(piece);
+            TranscriptSegment {
+                start_ms,
+                end_ms: if last == words.len() {
+                    segment.end_ms
+                } else {
+                    at(offset)
+                },
+                text: piece.join(" "),
+            }
+        })
+        .collect()
+}
+
+/// Words to keep in a full piece so it ends on punctuation, when a clause
+/// ends in its back half; `None` to cut after the last word that fits.
+fn clause_break(piece: &[&str], max_chars: u64) -> Option<usize> {
+    let mut chars = 0u64;
+    let mut best = None;
+    for (index, word) in piece.iter().enumerate() {
+        chars += word.chars().count() as u64 + u64::from(index > 0);
+        if chars * 2 >= max_chars && word.ends_with(['.', ',', '!', '?', ';', ':']) {
+            best = Some(index + 1);
+        }
+    }
+    best.filter(|&n| n < piece.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start_ms: u32, end_ms: u32, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+        }
+    }
+
+    fn assert_tiles(pieces: &[TranscriptSegment], start_ms: u32, end_ms: u32) {
+        assert_eq!(pieces.first().unwrap().start_ms, start_ms);
+        assert_eq!(pieces.last().unwrap().end_ms, end_ms);
+        for pair in pieces.windows(2) {
+            assert!(pair[0].start_ms <= pair[0].end_ms, "{pieces:?}");
+            assert_eq!(pair[0].end_ms, pair[1].start_ms, "{pieces:?}");
+        }
+    }
+
+    #[test]
+    fn splits_at_words_and_keeps_text_and_timing_order() {
+        let text = "So the plan for this week is to ship, then gather feedback from the first users and fix what they find";
+        let limit = SegmentLimit {
+            max_chars: Some(42),
+            max_duration_ms: None,
+        };
+
+        let pieces = split_segments(vec![segment(1_000, 9_000, text)], limit);
+
+        assert!(pieces.len() >= 3, "{pieces:?}");
+        assert!(pieces.iter().all(|p| p.text.chars().count() <= 42));
+        let joined: Vec<&str> = pieces.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(joined.join(" "), text);
+        assert_tiles(&pieces, 1_000, 9_000);
+        // Broke after the comma rather than mid-clause.
+        assert!(pieces[0].text.ends_with("ship,"), "{pieces:?}");
+    }
+
+    #[test]
+    fn duration_limit_splits_and_short_segments_pass_through() {
+        let limit = SegmentLimit {
+            max_chars: None,
+            max_duration_ms: Some(2_000),
+        };
+        let short = segment(0, 1_500, "Hello there.");
+        let long = segment(1_500, 9_500, "one two three four five six seven eight");
+
+        let pieces = split_segments(vec![short.clone(), long], limit);
+
+        assert_eq!(pieces[0], short);
+        let rest = &pieces[1..];
+        assert!(rest.len() >= 4, "{rest:?}");
+        assert!(
+            rest.iter().all(|p| p.end_ms - p.start_ms <= 2_000),
+            "{rest:?}"
+        );
+        assert_tiles(rest, 1_500, 9_500);
+        assert_eq!(
+            split_segments(vec![segment(0, 5_000, "Supercalifragilistic")], limit).len(),
+            1
+        );
+    }
+}
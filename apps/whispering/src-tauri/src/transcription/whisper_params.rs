@@ -0,0 +1,59 @@
+//! User-tunable whisper.cpp decoding knobs, applied to every Whisper
+//! transcription on top of the ambient `TranscriptionConfig`.
+//!
+//! The defaults are what the app always shipped with. They suit most
+//! dictation, but on quiet recordings the no-speech gate can discard real
+//! speech; `set_whisper_defaults` lets those users relax it.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WhisperDefaults {
+    /// Probability of "no speech" above which a segment is dropped (0-1).
+    /// Higher keeps more quiet speech, and more hallucinations on silence.
+    pub no_speech_thold: f32,
+    /// Suppress blank outputs at the start of a segment.
+    pub suppress_blank: bool,
+    /// Suppress non-speech tokens such as "[Music]" or "(laughs)".
+    pub suppress_non_speech_tokens: bool,
+}
+
+impl Default for WhisperDefaults {
+    fn default() -> Self {
+        Self {
+            no_speech_thold: 0.2,
+            suppress_blank: true,
+            suppress_non_speech_tokens: true,
+        }
+    }
+}
+
+impl WhisperDefaults {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.no_speech_thold) {
+            return Err(format!(
+                "noSpeechThold must be between 0 and 1, got {}",
+                self.no_speech_thold
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_thresholds_outside_zero_to_one() {
+        assert!(WhisperDefaults::default().validate().is_ok());
+        for no_speech_thold in [-0.1, 1.5, f32::NAN] {
+            let defaults = WhisperDefaults {
+                no_speech_thold,
+                ..Default::default()
+            };
+            assert!(defaults.validate().is_err());
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Quiesce the audio and transcription engines before the updater replaces
+//! the app binary.
+//!
+//! An update that lands mid-recording would lose the in-memory samples and
+//! can leave the input device held by a process that is about to be killed;
+//! a resident model keeps its files open, which blocks replacement on
+//! Windows. The frontend calls `prepare_for_update` right before handing off
+//! to `tauri_plugin_updater`.
+
+use log::{info, warn};
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::recorder::commands::finalize_and_close;
+use crate::recorder::{Recorder, RecordingArtifact};
+use crate::transcription::ModelManager;
+
+pub const READY_FOR_UPDATE_EVENT: &str = "ready-for-update";
+
+/// What `prepare_for_update` had to shut down. Both fields empty/false means
+/// nothing was active and the app was already safe to replace.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePreparation {
+    /// The recording that was in progress, now saved like a normal stop.
+    pub finalized_recording: Option<RecordingArtifact>,
+    pub model_unloaded: bool,
+}
+
+/// Finalize any active recording, release the input device, and unload the
+/// resident model. Resolves (and emits `READY_FOR_UPDATE_EVENT`) once it is
+/// safe to replace the binary; with nothing active that is immediate.
+///
+/// Waits for an in-flight transcription to finish rather than interrupting
+/// it, so a dictation that is mid-inference still produces its transcript.
+#[tauri::command]
+#[specta::specta]
+pub async fn prepare_for_update(
+    recorder: State<'_, Mutex<Recorder>>,
+    model_manager: State<'_, ModelManager>,
+    app_handle: AppHandle,
+) -> Result<UpdatePreparation, String> {
+    info!("Preparing for update");
+    let finalized_recording = finalize_and_close(&recorder, &app_handle)?;
+
+    let manager = model_manager.inner().clone();
+    let model_unloaded =
+        tauri::async_runtime::spawn_blocking(move || manager.unload_for_shutdown())
+            .await
+            .map_err(|e| format!("Task join error: {e}"))?;
+
+    let preparation = UpdatePreparation {
+        finalized_recording,
+        model_unloaded,
+    };
+    info!(
+        "Ready for update: recording_saved={}, model_unloaded={}",
+        preparation.finalized_recording.is_some(),
+        preparation.model_unloaded,
+    );
+    if let Err(e) = app_handle.emit(READY_FOR_UPDATE_EVENT, &preparation) {
+        warn!("Failed to emit {READY_FOR_UPDATE_EVENT}: {e}");
+    }
+    Ok(preparation)
+}
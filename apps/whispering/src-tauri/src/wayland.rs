@@ -0,0 +1,102 @@
+//! Wayland session detection and the paste keystroke under Wayland.
+//!
+//! enigo injects input through X11 (XTest). Under a Wayland session that only
+//! reaches XWayland clients: native Wayland windows never see the keystroke,
+//! and nothing reports the failure, so `write_text` used to be a silent no-op
+//! there. Wayland has no unprivileged input injection API, so we go through an
+//! external tool, tried in order:
+//!
+//! 1. `wtype`: uses the virtual-keyboard protocol, which wlroots compositors
+//!    (Sway, Hyprland, river) and KDE implement. GNOME does not, and `wtype`
+//!    exits non-zero there.
+//! 2. `ydotool`: writes to `/dev/uinput` through its `ydotoold` daemon, so it
+//!    works on any compositor once the daemon is running with access to
+//!    uinput.
+//!
+//! A tool that is not installed or that fails is skipped. When none works,
+//! the caller gets `PASTE_UNSUPPORTED_ON_WAYLAND` with what was tried, and
+//! the text stays on the clipboard for a manual paste.
+//!
+//! Typing mode (`write_text_typed`) goes through the same two tools, with
+//! their own per-character delay.
+
+use log::{debug, warn};
+use std::io::ErrorKind;
+use std::process::Command;
+
+/// Prefix of the error returned when no paste mechanism works under Wayland.
+/// The frontend matches on it to suggest installing a tool or pasting by hand.
+pub const PASTE_UNSUPPORTED_ON_WAYLAND: &str = "PasteUnsupportedOnWayland";
+
+/// True under a native Wayland session. An XWayland client (GDK_BACKEND=x11)
+/// still cannot stack above, or inject input into, native Wayland surfaces,
+/// so the session type matters, not the backend GTK picked.
+pub fn is_wayland_session() -> bool {
+    std::env::var("XDG_SESSION_TYPE").is_ok_and(|session| session.eq_ignore_ascii_case("wayland"))
+        || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+/// Press and release Ctrl+V through the first paste tool that works.
+pub fn send_paste_keystroke() -> Result<(), String> {
+    // (program, args) for Ctrl+V. ydotool takes evdev key codes:
+    // 29 = KEY_LEFTCTRL, 47 = KEY_V; `:1` presses and `:0` releases.
+    const TOOLS: &[(&str, &[&str])] = &[
+        ("wtype", &["-M", "ctrl", "-k", "v", "-m", "ctrl"]),
+        ("ydotool", &["key", "29:1", "47:1", "47:0", "29:0"]),
+    ];
+
+    run_first_working(TOOLS, "sent Ctrl+V").map_err(|attempts| {
+        format!(
+            "{PASTE_UNSUPPORTED_ON_WAYLAND}: cannot simulate a paste in this Wayland session ({}). \
+             Install wtype (wlroots or KDE) or run ydotoold, or paste manually with Ctrl+V; \
+             the text is on the clipboard.",
+            attempts.join("; ")
+        )
+    })
+}
+
+/// Type `text` through the first typing tool that works, pausing
+/// `delay_ms` between characters.
+pub fn type_text(text: &str, delay_ms: u32) -> Result<(), String> {
+    let delay = delay_ms.to_string();
+    // `--` so a transcript starting with "-" is not read as a flag.
+    let tools: &[(&str, &[&str])] = &[
+        ("wtype", &["-d", &delay, "--", text]),
+        ("ydotool", &["type", "--key-delay", &delay, "--", text]),
+    ];
+
+    run_first_working(tools, "typed text").map_err(|attempts| {
+        format!(
+            "{PASTE_UNSUPPORTED_ON_WAYLAND}: cannot type in this Wayland session ({}). \
+             Install wtype (wlroots or KDE) or run ydotoold.",
+            attempts.join("; ")
+        )
+    })
+}
+
+/// Run each `(program, args)` until one succeeds. On failure, returns what
+/// went wrong with each, for the error message.
+fn run_first_working(tools: &[(&str, &[&str])], action: &str) -> Result<(), Vec<String>> {
+    let mut attempts = Vec::with_capacity(tools.len());
+    for (program, args) in tools {
+        match Command::new(program).args(*args).output() {
+            Ok(output) if output.status.success() => {
+                debug!("[Paste] {action} through {program}");
+                return Ok(());
+            }
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                warn!("[Paste] {program} failed: {}", stderr.trim());
+                attempts.push(format!("{program} failed ({})", stderr.trim()));
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                attempts.push(format!("{program} is not installed"));
+            }
+            Err(e) => {
+                warn!("[Paste] could not run {program}: {e}");
+                attempts.push(format!("{program} could not run ({e})"));
+            }
+        }
+    }
+    Err(attempts)
+}